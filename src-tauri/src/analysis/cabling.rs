@@ -0,0 +1,395 @@
+//! Cable length estimates and a bill of materials for the cabling plan.
+//!
+//! Cables within a rack run vertically between the two devices, plus the
+//! rack depth when they go from front to rear. Cables between racks run up to
+//! an overhead tray, along the tray at right angles across the floor, and
+//! back down. Slack is added for service loops before rounding up to the
+//! next stock length.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{CableId, CableKind, Device, Layout, Port, PortRef, Rack, UNIT_MM};
+
+/// Assumptions about the room used to route cables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Routing {
+    /// Height of the overhead cable tray above the floor.
+    pub tray_height_mm: f64,
+    /// Height of the bottom of unit 1 above the floor.
+    pub base_height_mm: f64,
+    /// Extra length added to every cable for dressing and service loops.
+    pub slack_mm: f64,
+    /// Lengths cables are bought in, in meters.
+    pub stock_lengths_m: Vec<f64>,
+}
+
+impl Default for Routing {
+    fn default() -> Self {
+        Self {
+            tray_height_mm: 2400.0,
+            base_height_mm: 100.0,
+            slack_mm: 1000.0,
+            stock_lengths_m: vec![
+                0.5, 1.0, 1.5, 2.0, 3.0, 5.0, 7.0, 10.0, 15.0, 20.0, 30.0, 50.0,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CableLength {
+    pub cable_id: CableId,
+    pub kind: CableKind,
+    /// Routed length including slack, or `None` if a rack involved has no
+    /// floor position.
+    pub length_m: Option<f64>,
+    /// The shortest stock length that covers `length_m`, or `None` if the
+    /// cable is longer than any stock length.
+    pub stock_m: Option<f64>,
+}
+
+/// How many cables of one kind and stock length to order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillLine {
+    pub kind: CableKind,
+    pub length_m: f64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CableReport {
+    pub cables: Vec<CableLength>,
+    pub bill: Vec<BillLine>,
+    /// Cables between racks that have not been placed on the floor.
+    pub unplaced: Vec<CableId>,
+    /// Cables longer than the longest stock length, which need to be made to
+    /// measure.
+    pub too_long: Vec<CableId>,
+    /// Cables left out of the estimate because an end cannot be found.
+    pub issues: Vec<CableIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CableIssue {
+    /// An end names a device that is not in the layout.
+    #[serde(rename_all = "camelCase")]
+    MissingDevice { cable_id: CableId, end: PortRef },
+    /// An end names a port its device does not have.
+    #[serde(rename_all = "camelCase")]
+    MissingPort { cable_id: CableId, end: PortRef },
+}
+
+pub fn report(layout: &Layout, routing: &Routing) -> CableReport {
+    let mut stock: Vec<f64> = routing
+        .stock_lengths_m
+        .iter()
+        .copied()
+        .filter(|l| l.is_finite() && *l > 0.0)
+        .collect();
+    stock.sort_by(f64::total_cmp);
+
+    let mut cables = Vec::new();
+    let mut unplaced = Vec::new();
+    let mut too_long = Vec::new();
+    let mut issues = Vec::new();
+    let mut bill: BTreeMap<(CableKind, u64), usize> = BTreeMap::new();
+    for cable in &layout.cables {
+        let (a, b) = match (
+            end(layout, cable.id, &cable.a),
+            end(layout, cable.id, &cable.b),
+        ) {
+            (Ok(a), Ok(b)) => (a, b),
+            (a, b) => {
+                issues.extend(a.err());
+                issues.extend(b.err());
+                continue;
+            }
+        };
+        let kind = a.port.cable_kind(b.port);
+        let length_m = route_mm(&a, &b, routing).map(|mm| (mm + routing.slack_mm) / 1000.0);
+        let stock_m = length_m.and_then(|l| stock.iter().copied().find(|s| *s >= l));
+        match (length_m, stock_m) {
+            (None, _) => unplaced.push(cable.id),
+            (Some(_), None) => too_long.push(cable.id),
+            (Some(_), Some(s)) => *bill.entry((kind, s.to_bits())).or_default() += 1,
+        }
+        cables.push(CableLength {
+            cable_id: cable.id,
+            kind,
+            length_m,
+            stock_m,
+        });
+    }
+
+    CableReport {
+        cables,
+        bill: bill
+            .into_iter()
+            .map(|((kind, bits), count)| BillLine {
+                kind,
+                length_m: f64::from_bits(bits),
+                count,
+            })
+            .collect(),
+        unplaced,
+        too_long,
+        issues,
+    }
+}
+
+struct End<'a> {
+    rack: &'a Rack,
+    device: &'a Device,
+    port: &'a Port,
+}
+
+fn end<'a>(layout: &'a Layout, cable_id: CableId, port: &PortRef) -> Result<End<'a>, CableIssue> {
+    let (rack, device) =
+        layout
+            .find_device(port.device_id)
+            .map_err(|_| CableIssue::MissingDevice {
+                cable_id,
+                end: port.clone(),
+            })?;
+    let found = device
+        .port(&port.port)
+        .ok_or_else(|| CableIssue::MissingPort {
+            cable_id,
+            end: port.clone(),
+        })?;
+    Ok(End {
+        rack,
+        device,
+        port: found,
+    })
+}
+
+/// Length of the route between two ends before slack, in millimeters.
+fn route_mm(a: &End, b: &End, routing: &Routing) -> Option<f64> {
+    let height = |end: &End| {
+        routing.base_height_mm
+            + (f64::from(end.device.position_u.saturating_sub(1))
+                + f64::from(end.device.height_u) / 2.0)
+                * UNIT_MM
+    };
+    if a.rack.id == b.rack.id {
+        let across = if a.device.face == b.device.face {
+            0.0
+        } else {
+            f64::from(a.rack.depth_mm)
+        };
+        return Some((height(a) - height(b)).abs() + across);
+    }
+    let (from, to) = (a.rack.position?, b.rack.position?);
+    let rise = |end: &End| (routing.tray_height_mm - height(end)).max(0.0);
+    Some(rise(a) + (from.x_mm - to.x_mm).abs() + (from.y_mm - to.y_mm).abs() + rise(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Cable, Face, FloorPosition, PortKind};
+
+    fn switch(name: &str, position_u: u32, face: Face) -> Device {
+        let mut device = Device::new(name, 1, position_u, 300, face);
+        device.ports = vec![
+            Port::new("eth0", PortKind::Rj45),
+            Port::new("eth1", PortKind::Rj45),
+        ];
+        device
+    }
+
+    fn at(x_mm: f64, y_mm: f64) -> Option<FloorPosition> {
+        Some(FloorPosition {
+            x_mm,
+            y_mm,
+            facing: Default::default(),
+        })
+    }
+
+    fn cable(a: &Device, b: &Device, port: &str) -> Cable {
+        let end = |d: &Device| PortRef {
+            device_id: d.id,
+            port: port.to_owned(),
+        };
+        Cable::new(end(a), end(b))
+    }
+
+    /// Two racks, with a switch at U1 and U11 in the first and one at U1 in
+    /// the second.
+    fn layout() -> Layout {
+        let mut a = Rack::new("A1", 42);
+        a.position = at(0.0, 0.0);
+        a.devices = vec![
+            switch("sw-01", 1, Face::Front),
+            switch("sw-02", 11, Face::Front),
+        ];
+        let mut b = Rack::new("A2", 42);
+        b.position = at(1200.0, 600.0);
+        b.devices = vec![switch("sw-03", 1, Face::Front)];
+        Layout {
+            racks: vec![a, b],
+            ..Layout::default()
+        }
+    }
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-9)
+    }
+
+    #[test]
+    fn runs_vertically_within_a_rack() {
+        let mut layout = layout();
+        let rack = &layout.racks[0];
+        layout.cables = vec![cable(&rack.devices[0], &rack.devices[1], "eth0")];
+        let report = report(&layout, &Routing::default());
+        // Ten units apart plus a meter of slack.
+        assert!(close(report.cables[0].length_m, 1.4445));
+        assert_eq!(report.cables[0].stock_m, Some(1.5));
+        assert_eq!(report.cables[0].kind, CableKind::Copper);
+    }
+
+    #[test]
+    fn crosses_the_rack_from_front_to_rear() {
+        let mut layout = layout();
+        layout.racks[0].devices[1].face = Face::Rear;
+        let rack = &layout.racks[0];
+        layout.cables = vec![cable(&rack.devices[0], &rack.devices[1], "eth0")];
+        let depth = f64::from(rack.depth_mm);
+        let report = report(&layout, &Routing::default());
+        assert!(close(
+            report.cables[0].length_m,
+            (444.5 + depth + 1000.0) / 1000.0
+        ));
+    }
+
+    #[test]
+    fn routes_between_racks_through_the_tray() {
+        let mut layout = layout();
+        layout.cables = vec![cable(
+            &layout.racks[0].devices[0],
+            &layout.racks[1].devices[0],
+            "eth0",
+        )];
+        let report = report(&layout, &Routing::default());
+        // Up 2277.775 mm, 1200 + 600 mm along the tray, down again, plus slack.
+        assert!(close(report.cables[0].length_m, 7.35555));
+        assert_eq!(report.cables[0].stock_m, Some(10.0));
+    }
+
+    #[test]
+    fn leaves_cables_to_unplaced_racks_unmeasured() {
+        let mut layout = layout();
+        layout.racks[1].position = None;
+        let cable = cable(
+            &layout.racks[0].devices[0],
+            &layout.racks[1].devices[0],
+            "eth0",
+        );
+        let id = cable.id;
+        layout.cables = vec![cable];
+        let report = report(&layout, &Routing::default());
+        assert_eq!(report.cables[0].length_m, None);
+        assert_eq!(report.unplaced, [id]);
+        assert!(report.bill.is_empty());
+    }
+
+    #[test]
+    fn flags_cables_longer_than_any_stock_length() {
+        let mut layout = layout();
+        let cable = cable(
+            &layout.racks[0].devices[0],
+            &layout.racks[1].devices[0],
+            "eth0",
+        );
+        let id = cable.id;
+        layout.cables = vec![cable];
+        let routing = Routing {
+            stock_lengths_m: vec![1.0, 5.0],
+            ..Routing::default()
+        };
+        let report = report(&layout, &routing);
+        assert_eq!(report.cables[0].stock_m, None);
+        assert_eq!(report.too_long, [id]);
+        assert!(report.bill.is_empty());
+    }
+
+    #[test]
+    fn groups_the_bill_by_kind_and_stock_length() {
+        let mut layout = layout();
+        let sfp = Port::new("sfp1", PortKind::SfpPlus);
+        layout.racks[0].devices[0].ports.push(sfp.clone());
+        layout.racks[1].devices[0].ports.push(sfp);
+        let (rack, other) = (&layout.racks[0], &layout.racks[1]);
+        layout.cables = vec![
+            cable(&rack.devices[0], &rack.devices[1], "eth0"),
+            cable(&rack.devices[0], &other.devices[0], "sfp1"),
+            cable(&rack.devices[0], &rack.devices[1], "eth1"),
+        ];
+        let report = report(&layout, &Routing::default());
+        let lines: Vec<_> = report
+            .bill
+            .iter()
+            .map(|l| (l.kind, l.length_m, l.count))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (CableKind::Copper, 1.5, 2),
+                (CableKind::DirectAttach, 10.0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_cables_with_missing_ends() {
+        let mut layout = layout();
+        let rack = &layout.racks[0];
+        let mut missing_port = cable(&rack.devices[0], &rack.devices[1], "eth0");
+        missing_port.b.port = String::from("eth9");
+        let mut missing_device = cable(&rack.devices[0], &rack.devices[1], "eth1");
+        missing_device.a.device_id = uuid::Uuid::new_v4();
+        layout.cables = vec![missing_port.clone(), missing_device.clone()];
+        let report = report(&layout, &Routing::default());
+        assert!(report.cables.is_empty());
+        assert_eq!(
+            report.issues,
+            [
+                CableIssue::MissingPort {
+                    cable_id: missing_port.id,
+                    end: missing_port.b,
+                },
+                CableIssue::MissingDevice {
+                    cable_id: missing_device.id,
+                    end: missing_device.a,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_unit_one_from_the_base_of_the_rack() {
+        let mut layout = layout();
+        layout.racks[0].devices[0].height_u = 2;
+        layout.cables = vec![cable(
+            &layout.racks[0].devices[0],
+            &layout.racks[1].devices[0],
+            "eth0",
+        )];
+        let routing = Routing {
+            base_height_mm: 200.0,
+            ..Routing::default()
+        };
+        let report = report(&layout, &routing);
+        // The 2U switch is centred 44.45 mm above the base and the 1U one
+        // 22.225 mm, so they rise 2155.55 and 2177.775 mm to the tray.
+        assert!(close(report.cables[0].length_m, 7.133325));
+    }
+}
@@ -0,0 +1,249 @@
+//! What happens to each rack when one of its A/B power feeds goes down.
+//!
+//! When a feed fails, every PDU supplied from it goes dark and each device
+//! draws its full load through whichever of its connections are left. PDUs
+//! without a feed are assumed to stay up.
+
+use serde::Serialize;
+
+use super::power::{self, Basis, PduLoad};
+use crate::model::{DeviceId, Feed, Layout, Rack, RackId};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverScenario {
+    pub failed_feed: Feed,
+    /// Loads on the rack's PDUs that are still powered.
+    pub pdus: Vec<PduLoad>,
+    /// Devices left without any powered connection.
+    pub dropped: Vec<DeviceId>,
+    /// Whether any surviving circuit goes past the 80% continuous limit.
+    pub over_limit: bool,
+    /// Whether any surviving circuit goes past its breaker rating and would
+    /// trip.
+    pub over_rating: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverReport {
+    pub rack_id: RackId,
+    pub name: String,
+    /// Powered devices that are not connected to both feeds, e.g. dual PSUs
+    /// plugged into two PDUs on the same feed.
+    pub non_redundant: Vec<DeviceId>,
+    pub scenarios: Vec<FailoverScenario>,
+    /// Whether the rack rides through the loss of either feed with every
+    /// device up and every circuit within its continuous limit.
+    pub ok: bool,
+}
+
+pub fn report(layout: &Layout, basis: Basis) -> Vec<FailoverReport> {
+    let scenarios: Vec<_> = [Feed::A, Feed::B]
+        .into_iter()
+        .map(|failed| {
+            let live = |pdu_id| {
+                layout
+                    .find_pdu(pdu_id)
+                    .is_ok_and(|(_, pdu)| pdu.feed != Some(failed))
+            };
+            (failed, power::outlet_draws(layout, basis, live))
+        })
+        .collect();
+
+    layout
+        .racks
+        .iter()
+        .map(|rack| {
+            let scenarios: Vec<_> = scenarios
+                .iter()
+                .map(|(failed, draws)| scenario(layout, rack, *failed, draws))
+                .collect();
+            let non_redundant = non_redundant(layout, rack);
+            FailoverReport {
+                rack_id: rack.id,
+                name: rack.name.clone(),
+                ok: non_redundant.is_empty()
+                    && scenarios
+                        .iter()
+                        .all(|s| s.dropped.is_empty() && !s.over_limit),
+                non_redundant,
+                scenarios,
+            }
+        })
+        .collect()
+}
+
+fn scenario(
+    layout: &Layout,
+    rack: &Rack,
+    failed: Feed,
+    draws: &power::OutletDraws,
+) -> FailoverScenario {
+    let pdus: Vec<PduLoad> = rack
+        .pdus
+        .iter()
+        .filter(|pdu| pdu.feed != Some(failed))
+        .map(|pdu| power::pdu_load(pdu, draws))
+        .collect();
+    let dropped = rack
+        .devices
+        .iter()
+        .filter(|d| d.power.is_some() && !d.power_connections.is_empty())
+        .filter(|d| {
+            d.power_connections.iter().all(|c| {
+                layout
+                    .find_pdu(c.pdu_id)
+                    .map_or(true, |(_, pdu)| pdu.feed == Some(failed))
+            })
+        })
+        .map(|d| d.id)
+        .collect();
+    let circuits = || pdus.iter().flat_map(|p| &p.circuits);
+    FailoverScenario {
+        failed_feed: failed,
+        over_limit: circuits().any(|c| c.over_limit),
+        over_rating: circuits().any(|c| c.percent > 100.0),
+        pdus,
+        dropped,
+    }
+}
+
+fn non_redundant(layout: &Layout, rack: &Rack) -> Vec<DeviceId> {
+    rack.devices
+        .iter()
+        .filter(|d| d.power.is_some() && !d.power_connections.is_empty())
+        .filter(|d| {
+            let feeds: Vec<_> = d
+                .power_connections
+                .iter()
+                .filter_map(|c| layout.find_pdu(c.pdu_id).ok()?.1.feed)
+                .collect();
+            !(feeds.contains(&Feed::A) && feeds.contains(&Feed::B))
+        })
+        .map(|d| d.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Circuit, Device, Face, Outlet, Pdu, Phase, PortKind, PowerConnection, PowerDraw,
+    };
+
+    fn pdu(name: &str, feed: Option<Feed>) -> Pdu {
+        let circuit = Circuit {
+            name: String::from("C1"),
+            phase: Phase::L1,
+            breaker_a: 16.0,
+            outlets: ["1", "2", "3"]
+                .map(|name| Outlet {
+                    name: name.to_owned(),
+                    kind: PortKind::C13,
+                })
+                .to_vec(),
+        };
+        let mut pdu = Pdu::new(name, 230.0, vec![circuit]);
+        pdu.feed = feed;
+        pdu
+    }
+
+    fn device(name: &str, position_u: u32, plugged_into: &[(&Pdu, &str)]) -> Device {
+        let mut device = Device::new(name, 1, position_u, 600, Face::Front);
+        device.power = Some(PowerDraw {
+            nameplate_w: 1000,
+            typical_w: 600,
+        });
+        device.power_connections = plugged_into
+            .iter()
+            .map(|(pdu, outlet)| PowerConnection {
+                pdu_id: pdu.id,
+                outlet: (*outlet).to_owned(),
+            })
+            .collect();
+        device
+    }
+
+    /// A rack with an A PDU, a B PDU and a PDU without a feed.
+    fn layout(devices: impl FnOnce(&Pdu, &Pdu, &Pdu) -> Vec<Device>) -> Layout {
+        let (a, b, none) = (
+            pdu("A", Some(Feed::A)),
+            pdu("B", Some(Feed::B)),
+            pdu("N", None),
+        );
+        let mut rack = Rack::new("A1", 42);
+        rack.devices = devices(&a, &b, &none);
+        rack.pdus = vec![a, b, none];
+        Layout {
+            racks: vec![rack],
+            ..Layout::default()
+        }
+    }
+
+    fn scenario(report: &FailoverReport, feed: Feed) -> &FailoverScenario {
+        report
+            .scenarios
+            .iter()
+            .find(|s| s.failed_feed == feed)
+            .unwrap()
+    }
+
+    #[test]
+    fn dual_corded_devices_survive_losing_either_feed() {
+        let layout = layout(|a, b, _| vec![device("web-01", 1, &[(a, "1"), (b, "1")])]);
+        let report = &report(&layout, Basis::Nameplate)[0];
+        assert!(report.ok);
+        assert!(report.non_redundant.is_empty());
+
+        let lost_a = scenario(report, Feed::A);
+        assert!(lost_a.dropped.is_empty());
+        // The B PDU carries the whole load once A is gone.
+        let b = lost_a.pdus.iter().find(|p| p.name == "B").unwrap();
+        assert_eq!(b.load_w, 1000.0);
+        assert!(lost_a.pdus.iter().all(|p| p.name != "A"));
+    }
+
+    #[test]
+    fn single_corded_devices_drop_with_their_feed() {
+        let layout = layout(|a, _, _| vec![device("web-01", 1, &[(a, "1")])]);
+        let report = &report(&layout, Basis::Nameplate)[0];
+        let id = layout.racks[0].devices[0].id;
+        assert!(!report.ok);
+        assert_eq!(report.non_redundant, [id]);
+        assert_eq!(scenario(report, Feed::A).dropped, [id]);
+        assert!(scenario(report, Feed::B).dropped.is_empty());
+    }
+
+    #[test]
+    fn pdus_without_a_feed_stay_up_but_are_not_redundant() {
+        let layout = layout(|_, _, none| vec![device("web-01", 1, &[(none, "1")])]);
+        let report = &report(&layout, Basis::Nameplate)[0];
+        let id = layout.racks[0].devices[0].id;
+        assert!(scenario(report, Feed::A).dropped.is_empty());
+        assert!(scenario(report, Feed::B).dropped.is_empty());
+        assert_eq!(report.non_redundant, [id]);
+        assert!(!report.ok);
+    }
+
+    #[test]
+    fn flags_a_surviving_circuit_pushed_over_its_limit() {
+        // 2 kW each on A and B is fine until one side takes all 4 kW.
+        let layout = layout(|a, b, _| {
+            (1..=2)
+                .map(|u| {
+                    let outlet = if u == 1 { "1" } else { "2" };
+                    let mut d = device(&format!("web-{u}"), u, &[(a, outlet), (b, outlet)]);
+                    d.power.as_mut().unwrap().nameplate_w = 2000;
+                    d
+                })
+                .collect()
+        });
+        let report = &report(&layout, Basis::Nameplate)[0];
+        let lost_a = scenario(report, Feed::A);
+        assert!(lost_a.dropped.is_empty());
+        assert!(lost_a.over_limit);
+        assert!(lost_a.over_rating);
+        assert!(!report.ok);
+    }
+}
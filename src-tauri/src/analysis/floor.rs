@@ -0,0 +1,452 @@
+//! Clearance and aisle checks for racks on the floor plan.
+//!
+//! Every placed rack needs free floor in front of and behind it, and should
+//! draw its air from a cold aisle and exhaust into a hot one. Racks without a
+//! floor position are left out, and without a room only racks are checked
+//! against each other.
+
+use serde::Serialize;
+
+use crate::model::{footprint, AisleKind, Face, Heading, Layout, Rack, RackId, Rect};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloorReport {
+    pub issues: Vec<FloorIssue>,
+    /// Racks that have not been placed on the floor yet.
+    pub unplaced: Vec<RackId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FloorIssue {
+    /// Part of the rack stands outside the room.
+    #[serde(rename_all = "camelCase")]
+    OutsideRoom { rack_id: RackId, name: String },
+    /// Two racks stand in the same space. Each pair is reported once.
+    #[serde(rename_all = "camelCase")]
+    Overlap {
+        rack_id: RackId,
+        name: String,
+        other_id: RackId,
+        other_name: String,
+    },
+    /// There is less free floor than required on one side of the rack.
+    /// `blocked_by` names the rack in the way, or is `None` for a wall.
+    #[serde(rename_all = "camelCase")]
+    Clearance {
+        rack_id: RackId,
+        name: String,
+        face: Face,
+        available_mm: f64,
+        required_mm: u32,
+        blocked_by: Option<String>,
+    },
+    /// The rack stands in an aisle.
+    #[serde(rename_all = "camelCase")]
+    InAisle {
+        rack_id: RackId,
+        name: String,
+        aisle: String,
+    },
+    /// The rack's cold aisle side opens onto a hot aisle, or its hot side
+    /// onto a cold aisle.
+    #[serde(rename_all = "camelCase")]
+    WrongAisle {
+        rack_id: RackId,
+        name: String,
+        face: Face,
+        aisle: String,
+        aisle_kind: AisleKind,
+    },
+}
+
+struct Placed<'a> {
+    rack: &'a Rack,
+    facing: Heading,
+    rect: Rect,
+}
+
+pub fn report(layout: &Layout) -> FloorReport {
+    let placed: Vec<_> = layout
+        .racks
+        .iter()
+        .filter_map(|rack| {
+            let position = rack.position?;
+            Some(Placed {
+                rack,
+                facing: position.facing,
+                rect: footprint(position, rack.depth_mm),
+            })
+        })
+        .collect();
+    let room = layout.room.as_ref();
+    let clearance = room.map(|r| r.clearance).unwrap_or_default();
+
+    let mut issues = Vec::new();
+    for (i, this) in placed.iter().enumerate() {
+        let (rack_id, name) = (this.rack.id, &this.rack.name);
+        if room.is_some_and(|room| !room.bounds().contains(&this.rect)) {
+            issues.push(FloorIssue::OutsideRoom {
+                rack_id,
+                name: name.clone(),
+            });
+        }
+        for other in &placed[i + 1..] {
+            if this.rect.overlaps(&other.rect) {
+                issues.push(FloorIssue::Overlap {
+                    rack_id,
+                    name: name.clone(),
+                    other_id: other.rack.id,
+                    other_name: other.rack.name.clone(),
+                });
+            }
+        }
+
+        for (face, required_mm) in [
+            (Face::Front, clearance.front_mm),
+            (Face::Rear, clearance.rear_mm),
+        ] {
+            let side = match face {
+                Face::Front => this.facing,
+                Face::Rear => this.facing.opposite(),
+            };
+            let required = f64::from(required_mm);
+            let strip = beyond(&this.rect, side, required);
+
+            let mut available_mm = room.map_or(f64::INFINITY, |room| {
+                wall_gap(&this.rect, side, &room.bounds())
+            });
+            let mut blocked_by = None;
+            // Racks overlapping this one are reported as overlaps instead.
+            for other in placed.iter().filter(|o| {
+                o.rack.id != rack_id && o.rect.overlaps(&strip) && !o.rect.overlaps(&this.rect)
+            }) {
+                let gap = gap(&this.rect, side, &other.rect);
+                if gap < available_mm {
+                    available_mm = gap;
+                    blocked_by = Some(other.rack.name.clone());
+                }
+            }
+            if available_mm < required {
+                issues.push(FloorIssue::Clearance {
+                    rack_id,
+                    name: name.clone(),
+                    face,
+                    available_mm,
+                    required_mm,
+                    blocked_by,
+                });
+            }
+
+            let intake = face == this.rack.cold_aisle;
+            for aisle in room.iter().flat_map(|r| &r.aisles) {
+                let wrong = match aisle.kind {
+                    AisleKind::Cold => !intake,
+                    AisleKind::Hot => intake,
+                };
+                if wrong && aisle.rect().overlaps(&strip) {
+                    issues.push(FloorIssue::WrongAisle {
+                        rack_id,
+                        name: name.clone(),
+                        face,
+                        aisle: aisle.name.clone(),
+                        aisle_kind: aisle.kind,
+                    });
+                }
+            }
+        }
+
+        for aisle in room.iter().flat_map(|r| &r.aisles) {
+            if aisle.rect().overlaps(&this.rect) {
+                issues.push(FloorIssue::InAisle {
+                    rack_id,
+                    name: name.clone(),
+                    aisle: aisle.name.clone(),
+                });
+            }
+        }
+    }
+
+    FloorReport {
+        issues,
+        unplaced: layout
+            .racks
+            .iter()
+            .filter(|r| r.position.is_none())
+            .map(|r| r.id)
+            .collect(),
+    }
+}
+
+/// The strip of floor `depth` deep along the `side` edge of `rect`.
+fn beyond(rect: &Rect, side: Heading, depth: f64) -> Rect {
+    match side {
+        Heading::North => Rect {
+            min_y: rect.min_y - depth,
+            max_y: rect.min_y,
+            ..*rect
+        },
+        Heading::East => Rect {
+            min_x: rect.max_x,
+            max_x: rect.max_x + depth,
+            ..*rect
+        },
+        Heading::South => Rect {
+            min_y: rect.max_y,
+            max_y: rect.max_y + depth,
+            ..*rect
+        },
+        Heading::West => Rect {
+            min_x: rect.min_x - depth,
+            max_x: rect.min_x,
+            ..*rect
+        },
+    }
+}
+
+/// Free floor between the `side` edge of `rect` and `other` beyond it.
+fn gap(rect: &Rect, side: Heading, other: &Rect) -> f64 {
+    let gap = match side {
+        Heading::North => rect.min_y - other.max_y,
+        Heading::East => other.min_x - rect.max_x,
+        Heading::South => other.min_y - rect.max_y,
+        Heading::West => rect.min_x - other.max_x,
+    };
+    gap.max(0.0)
+}
+
+/// Free floor between the `side` edge of `rect` and the wall of `room`.
+fn wall_gap(rect: &Rect, side: Heading, room: &Rect) -> f64 {
+    let gap = match side {
+        Heading::North => rect.min_y - room.min_y,
+        Heading::East => room.max_x - rect.max_x,
+        Heading::South => room.max_y - rect.max_y,
+        Heading::West => rect.min_x - room.min_x,
+    };
+    gap.max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Aisle, Clearance, FloorPosition, Room};
+
+    fn rack(name: &str, x_mm: f64, y_mm: f64, facing: Heading) -> Rack {
+        let mut rack = Rack::new(name, 42);
+        rack.depth_mm = 1000;
+        rack.position = Some(FloorPosition { x_mm, y_mm, facing });
+        rack
+    }
+
+    /// A 6 m square room.
+    fn room(aisles: Vec<Aisle>) -> Room {
+        Room {
+            name: String::from("Hall 1"),
+            tile_mm: 600,
+            width_tiles: 10,
+            depth_tiles: 10,
+            clearance: Clearance::default(),
+            rows: Vec::new(),
+            aisles,
+        }
+    }
+
+    fn aisle(name: &str, kind: AisleKind, y_mm: f64, depth_mm: f64) -> Aisle {
+        Aisle {
+            name: name.to_owned(),
+            kind,
+            x_mm: 0.0,
+            y_mm,
+            width_mm: 6000.0,
+            depth_mm,
+        }
+    }
+
+    fn layout(racks: Vec<Rack>, room: Option<Room>) -> Layout {
+        Layout {
+            racks,
+            room,
+            ..Layout::default()
+        }
+    }
+
+    fn overlaps(report: &FloorReport) -> usize {
+        report
+            .issues
+            .iter()
+            .filter(|i| matches!(i, FloorIssue::Overlap { .. }))
+            .count()
+    }
+
+    #[test]
+    fn racks_side_by_side_do_not_overlap() {
+        let racks = vec![
+            rack("A1", 1200.0, 3000.0, Heading::North),
+            rack("A2", 1800.0, 3000.0, Heading::North),
+        ];
+        let report = report(&layout(racks, Some(room(Vec::new()))));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn reports_each_overlapping_pair_once() {
+        let racks = vec![
+            rack("A1", 1200.0, 3000.0, Heading::North),
+            rack("A2", 1700.0, 3000.0, Heading::North),
+        ];
+        let report = report(&layout(racks, None));
+        assert_eq!(overlaps(&report), 1);
+        assert!(matches!(
+            &report.issues[0],
+            FloorIssue::Overlap { name, other_name, .. } if name == "A1" && other_name == "A2"
+        ));
+    }
+
+    #[test]
+    fn rotated_racks_overlap_by_their_turned_footprint() {
+        // Facing east, the rack is 1000 mm wide across x and reaches A1.
+        let racks = vec![
+            rack("A1", 1200.0, 3000.0, Heading::North),
+            rack("B1", 1900.0, 3000.0, Heading::East),
+        ];
+        assert_eq!(overlaps(&report(&layout(racks, None))), 1);
+        let racks = vec![
+            rack("A1", 1200.0, 3000.0, Heading::North),
+            rack("B1", 1900.0, 3000.0, Heading::North),
+        ];
+        assert_eq!(overlaps(&report(&layout(racks, None))), 0);
+    }
+
+    #[test]
+    fn measures_clearance_to_the_rack_opposite() {
+        // Fronts facing each other 500 mm apart.
+        let racks = vec![
+            rack("A1", 3000.0, 3000.0, Heading::North),
+            rack("B1", 3000.0, 1500.0, Heading::South),
+        ];
+        let report = report(&layout(racks, Some(room(Vec::new()))));
+        let blocked: Vec<_> = report
+            .issues
+            .iter()
+            .filter_map(|i| match i {
+                FloorIssue::Clearance {
+                    name,
+                    face,
+                    available_mm,
+                    required_mm,
+                    blocked_by,
+                    ..
+                } => Some((
+                    name.as_str(),
+                    *face,
+                    *available_mm,
+                    *required_mm,
+                    blocked_by.as_deref(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            blocked,
+            [
+                ("A1", Face::Front, 500.0, 1200, Some("B1")),
+                ("B1", Face::Front, 500.0, 1200, Some("A1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_clearance_to_the_wall() {
+        let layout = layout(
+            vec![rack("A1", 3000.0, 5300.0, Heading::North)],
+            Some(room(Vec::new())),
+        );
+        assert_eq!(
+            report(&layout).issues,
+            [FloorIssue::Clearance {
+                rack_id: layout.racks[0].id,
+                name: String::from("A1"),
+                face: Face::Rear,
+                available_mm: 200.0,
+                required_mm: 900,
+                blocked_by: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn accepts_racks_drawing_from_the_cold_aisle() {
+        let aisles = vec![
+            aisle("Cold 1", AisleKind::Cold, 1300.0, 1200.0),
+            aisle("Hot 1", AisleKind::Hot, 3500.0, 900.0),
+        ];
+        let racks = vec![rack("A1", 3000.0, 3000.0, Heading::North)];
+        let report = report(&layout(racks, Some(room(aisles))));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn flags_racks_turned_the_wrong_way_round() {
+        let aisles = vec![
+            aisle("Cold 1", AisleKind::Cold, 1300.0, 1200.0),
+            aisle("Hot 1", AisleKind::Hot, 3500.0, 900.0),
+        ];
+        let racks = vec![rack("A1", 3000.0, 3000.0, Heading::South)];
+        let report = report(&layout(racks, Some(room(aisles))));
+        let wrong: Vec<_> = report
+            .issues
+            .iter()
+            .filter_map(|i| match i {
+                FloorIssue::WrongAisle {
+                    face, aisle_kind, ..
+                } => Some((*face, *aisle_kind)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            wrong,
+            [(Face::Front, AisleKind::Hot), (Face::Rear, AisleKind::Cold)]
+        );
+    }
+
+    #[test]
+    fn follows_racks_that_take_air_in_at_the_rear() {
+        let aisles = vec![
+            aisle("Cold 1", AisleKind::Cold, 1300.0, 1200.0),
+            aisle("Hot 1", AisleKind::Hot, 3500.0, 900.0),
+        ];
+        let mut reversed = rack("A1", 3000.0, 3000.0, Heading::South);
+        reversed.cold_aisle = Face::Rear;
+        let report = report(&layout(vec![reversed], Some(room(aisles))));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn flags_racks_outside_the_room_or_in_an_aisle() {
+        let aisles = vec![aisle("Cold 1", AisleKind::Cold, 1300.0, 1200.0)];
+        let racks = vec![
+            rack("A1", 100.0, 3500.0, Heading::North),
+            rack("B1", 3000.0, 1800.0, Heading::North),
+        ];
+        let report = report(&layout(racks, Some(room(aisles))));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| matches!(i, FloorIssue::OutsideRoom { name, .. } if name == "A1")));
+        assert!(report.issues.iter().any(
+            |i| matches!(i, FloorIssue::InAisle { name, aisle, .. } if name == "B1" && aisle == "Cold 1")
+        ));
+    }
+
+    #[test]
+    fn lists_unplaced_racks() {
+        let unplaced = Rack::new("C1", 42);
+        let id = unplaced.id;
+        let racks = vec![rack("A1", 3000.0, 3000.0, Heading::North), unplaced];
+        let report = report(&layout(racks, None));
+        assert_eq!(report.unplaced, [id]);
+        // Without a room there are no walls to measure clearance against.
+        assert!(report.issues.is_empty());
+    }
+}
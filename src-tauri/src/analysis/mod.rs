@@ -0,0 +1,9 @@
+//! Checks and calculations over a whole layout, reported as structured data
+//! for the UI to present.
+
+pub mod cabling;
+pub mod failover;
+pub mod floor;
+pub mod power;
+pub mod thermal;
+pub mod weight;
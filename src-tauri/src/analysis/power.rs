@@ -0,0 +1,329 @@
+//! Electrical load per PDU circuit and phase.
+//!
+//! Loads are converted to current as `watts / volts`, i.e. assuming a power
+//! factor of 1, which errs on the side of overestimating current for modern
+//! power supplies.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Device, DeviceId, Layout, Pdu, PduId, Phase, Rack, RackId};
+
+/// The share of a breaker rating a circuit may carry continuously.
+pub const CONTINUOUS_LIMIT: f64 = 0.8;
+
+/// Which device rating loads are summed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Basis {
+    /// Expected draw under normal load.
+    Typical,
+    /// Worst case from the power supply labels.
+    #[default]
+    Nameplate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitLoad {
+    pub name: String,
+    pub phase: Phase,
+    pub breaker_a: f64,
+    pub load_w: f64,
+    pub current_a: f64,
+    /// Current as a percentage of the breaker rating.
+    pub percent: f64,
+    /// Whether the load breaks the 80% continuous-load rule.
+    pub over_limit: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseLoad {
+    pub phase: Phase,
+    pub load_w: f64,
+    pub current_a: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PduLoad {
+    pub pdu_id: PduId,
+    pub name: String,
+    pub load_w: f64,
+    pub circuits: Vec<CircuitLoad>,
+    pub phases: Vec<PhaseLoad>,
+    /// How far the most loaded phase is above the average of all phases in
+    /// use, as a percentage. Zero for single-phase PDUs.
+    pub imbalance_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RackPower {
+    pub rack_id: RackId,
+    pub name: String,
+    /// Draw of every device in the rack with power data, connected or not.
+    pub load_w: f64,
+    pub pdus: Vec<PduLoad>,
+    /// Devices that draw power but are not plugged into any PDU.
+    pub unconnected: Vec<DeviceId>,
+    pub over_limit: bool,
+}
+
+/// Calculates loads for every rack in the layout.
+pub fn report(layout: &Layout, basis: Basis) -> Vec<RackPower> {
+    let draws = outlet_draws(layout, basis, |_| true);
+    layout
+        .racks
+        .iter()
+        .map(|rack| rack_power(rack, basis, &draws))
+        .collect()
+}
+
+/// Watts drawn from each PDU outlet, by PDU and outlet name.
+pub(crate) type OutletDraws = HashMap<(PduId, String), f64>;
+
+/// Sums the draw on every outlet, splitting each device's draw evenly between
+/// the connections `live` accepts.
+pub(crate) fn outlet_draws(
+    layout: &Layout,
+    basis: Basis,
+    live: impl Fn(PduId) -> bool,
+) -> OutletDraws {
+    let mut draws = OutletDraws::new();
+    for (_, device) in layout.devices() {
+        let Some(watts) = device_watts(device, basis) else {
+            continue;
+        };
+        let live: Vec<_> = device
+            .power_connections
+            .iter()
+            .filter(|c| live(c.pdu_id))
+            .collect();
+        for connection in &live {
+            *draws
+                .entry((connection.pdu_id, connection.outlet.clone()))
+                .or_default() += watts / live.len() as f64;
+        }
+    }
+    draws
+}
+
+pub(crate) fn device_watts(device: &Device, basis: Basis) -> Option<f64> {
+    device.power.as_ref().map(|p| {
+        f64::from(match basis {
+            Basis::Typical => p.typical_w,
+            Basis::Nameplate => p.nameplate_w,
+        })
+    })
+}
+
+fn rack_power(rack: &Rack, basis: Basis, draws: &OutletDraws) -> RackPower {
+    let pdus: Vec<PduLoad> = rack.pdus.iter().map(|pdu| pdu_load(pdu, draws)).collect();
+    let load_w = total(rack.devices.iter().filter_map(|d| device_watts(d, basis)));
+    let unconnected = rack
+        .devices
+        .iter()
+        .filter(|d| d.power.is_some() && d.power_connections.is_empty())
+        .map(|d| d.id)
+        .collect();
+    RackPower {
+        rack_id: rack.id,
+        name: rack.name.clone(),
+        load_w,
+        over_limit: pdus.iter().any(|p| p.circuits.iter().any(|c| c.over_limit)),
+        pdus,
+        unconnected,
+    }
+}
+
+pub(crate) fn pdu_load(pdu: &Pdu, draws: &OutletDraws) -> PduLoad {
+    let circuits: Vec<CircuitLoad> = pdu
+        .circuits
+        .iter()
+        .map(|circuit| {
+            let load_w = total(
+                circuit
+                    .outlets
+                    .iter()
+                    .filter_map(|o| draws.get(&(pdu.id, o.name.clone())).copied()),
+            );
+            let current_a = load_w / pdu.voltage_v;
+            let percent = 100.0 * current_a / circuit.breaker_a;
+            CircuitLoad {
+                name: circuit.name.clone(),
+                phase: circuit.phase,
+                breaker_a: circuit.breaker_a,
+                load_w,
+                current_a,
+                percent,
+                over_limit: percent > 100.0 * CONTINUOUS_LIMIT,
+            }
+        })
+        .collect();
+
+    let mut by_phase = BTreeMap::<Phase, f64>::new();
+    for circuit in &circuits {
+        *by_phase.entry(circuit.phase).or_default() += circuit.load_w;
+    }
+    let phases: Vec<PhaseLoad> = by_phase
+        .into_iter()
+        .map(|(phase, load_w)| PhaseLoad {
+            phase,
+            load_w,
+            current_a: load_w / pdu.voltage_v,
+        })
+        .collect();
+    let load_w = total(phases.iter().map(|p| p.load_w));
+    let average = load_w / phases.len().max(1) as f64;
+    let highest = phases.iter().map(|p| p.load_w).fold(0.0, f64::max);
+    let imbalance_percent = if average > 0.0 {
+        100.0 * (highest - average) / average
+    } else {
+        0.0
+    };
+
+    PduLoad {
+        pdu_id: pdu.id,
+        name: pdu.name.clone(),
+        load_w,
+        circuits,
+        phases,
+        imbalance_percent,
+    }
+}
+
+/// Sums watts, starting from `0.0` rather than the `-0.0` that `f64`'s `Sum`
+/// gives for nothing, which would otherwise show up in the UI.
+pub(crate) fn total(values: impl IntoIterator<Item = f64>) -> f64 {
+    values.into_iter().fold(0.0, |sum, v| sum + v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Circuit, Face, Outlet, PortKind, PowerConnection, PowerDraw};
+
+    fn circuit(name: &str, phase: Phase, outlets: &[&str]) -> Circuit {
+        Circuit {
+            name: name.to_owned(),
+            phase,
+            breaker_a: 16.0,
+            outlets: outlets
+                .iter()
+                .map(|name| Outlet {
+                    name: (*name).to_owned(),
+                    kind: PortKind::C13,
+                })
+                .collect(),
+        }
+    }
+
+    fn server(name: &str, position_u: u32, watts: u32) -> Device {
+        let mut device = Device::new(name, 1, position_u, 600, Face::Front);
+        device.power = Some(PowerDraw {
+            nameplate_w: watts,
+            typical_w: watts / 2,
+        });
+        device
+    }
+
+    fn plug(device: &mut Device, pdu: &Pdu, outlet: &str) {
+        device.power_connections.push(PowerConnection {
+            pdu_id: pdu.id,
+            outlet: outlet.to_owned(),
+        });
+    }
+
+    /// A rack with a 230 V PDU with a circuit on each of two phases.
+    fn layout(devices: impl FnOnce(&Pdu) -> Vec<Device>) -> Layout {
+        let pdu = Pdu::new(
+            "PDU-A",
+            230.0,
+            vec![
+                circuit("C1", Phase::L1, &["1", "2"]),
+                circuit("C2", Phase::L2, &["3", "4"]),
+            ],
+        );
+        let mut rack = Rack::new("A1", 42);
+        rack.devices = devices(&pdu);
+        rack.pdus.push(pdu);
+        Layout {
+            racks: vec![rack],
+            ..Layout::default()
+        }
+    }
+
+    #[test]
+    fn flags_circuits_over_the_continuous_limit() {
+        // 16 A * 80% * 230 V = 2944 W.
+        let layout = layout(|pdu| {
+            let mut under = server("under", 1, 2944);
+            plug(&mut under, pdu, "1");
+            let mut over = server("over", 2, 2946);
+            plug(&mut over, pdu, "3");
+            vec![under, over]
+        });
+        let report = report(&layout, Basis::Nameplate);
+        let circuits = &report[0].pdus[0].circuits;
+        assert!(!circuits[0].over_limit);
+        assert!((circuits[0].percent - 80.0).abs() < 1e-9);
+        assert!(circuits[1].over_limit);
+        assert!(report[0].over_limit);
+    }
+
+    #[test]
+    fn splits_draw_evenly_between_power_supplies() {
+        let layout = layout(|pdu| {
+            let mut device = server("web-01", 1, 1000);
+            plug(&mut device, pdu, "1");
+            plug(&mut device, pdu, "3");
+            vec![device]
+        });
+        let report = report(&layout, Basis::Nameplate);
+        let pdu = &report[0].pdus[0];
+        assert_eq!(pdu.circuits[0].load_w, 500.0);
+        assert_eq!(pdu.circuits[1].load_w, 500.0);
+        assert_eq!(pdu.load_w, 1000.0);
+        assert_eq!(pdu.imbalance_percent, 0.0);
+    }
+
+    #[test]
+    fn uses_the_requested_basis() {
+        let layout = layout(|pdu| {
+            let mut device = server("web-01", 1, 1000);
+            plug(&mut device, pdu, "1");
+            vec![device]
+        });
+        let report = report(&layout, Basis::Typical);
+        assert_eq!(report[0].load_w, 500.0);
+        assert_eq!(report[0].pdus[0].circuits[0].load_w, 500.0);
+    }
+
+    #[test]
+    fn measures_imbalance_against_the_average_phase() {
+        let layout = layout(|pdu| {
+            let mut heavy = server("heavy", 1, 3000);
+            plug(&mut heavy, pdu, "1");
+            let mut light = server("light", 2, 1000);
+            plug(&mut light, pdu, "3");
+            vec![heavy, light]
+        });
+        let report = report(&layout, Basis::Nameplate);
+        let pdu = &report[0].pdus[0];
+        assert_eq!(pdu.phases.len(), 2);
+        // Average 2000 W, highest 3000 W.
+        assert!((pdu.imbalance_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lists_devices_that_are_not_plugged_in() {
+        let layout = layout(|_| vec![server("loose", 1, 500)]);
+        let report = report(&layout, Basis::Nameplate);
+        assert_eq!(report[0].unconnected, [layout.racks[0].devices[0].id]);
+        assert_eq!(report[0].load_w, 500.0);
+        assert_eq!(report[0].pdus[0].load_w, 0.0);
+    }
+}
@@ -0,0 +1,140 @@
+//! Heat output and airflow per rack.
+//!
+//! Every watt a device draws ends up as heat, so heat output is the rack's
+//! power draw converted to BTU/hr. It is normally worked out from typical
+//! draw, since nameplate ratings are worst cases a running rack rarely comes
+//! near.
+
+use serde::Serialize;
+
+use super::power::{total, Basis};
+use crate::model::{Device, DeviceId, Face, Layout, Rack, RackId};
+use crate::validation::{self, AirflowWarning};
+
+pub const BTU_PER_HOUR_PER_WATT: f64 = 3.412_142;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RackThermal {
+    pub rack_id: RackId,
+    pub name: String,
+    pub cold_aisle: Face,
+    /// The rating `load_w` is summed from.
+    pub basis: Basis,
+    pub load_w: f64,
+    pub btu_per_hour: f64,
+    /// Devices without power data, excluding passive gear like blanking
+    /// panels. Their heat is not included.
+    pub unknown: Vec<DeviceId>,
+    /// Devices without a typical draw, whose nameplate rating was used
+    /// instead. Always empty for the nameplate basis.
+    pub nameplate_fallback: Vec<DeviceId>,
+    pub airflow: Vec<AirflowWarning>,
+}
+
+pub fn report(layout: &Layout, basis: Basis) -> Vec<RackThermal> {
+    layout
+        .racks
+        .iter()
+        .map(|rack| rack_thermal(rack, basis))
+        .collect()
+}
+
+fn rack_thermal(rack: &Rack, basis: Basis) -> RackThermal {
+    let load_w = total(rack.devices.iter().filter_map(|d| heat_watts(d, basis)));
+    RackThermal {
+        rack_id: rack.id,
+        name: rack.name.clone(),
+        cold_aisle: rack.cold_aisle,
+        basis,
+        load_w,
+        btu_per_hour: load_w * BTU_PER_HOUR_PER_WATT,
+        unknown: rack
+            .devices
+            .iter()
+            .filter(|d| d.power.is_none() && !d.category.is_passive())
+            .map(|d| d.id)
+            .collect(),
+        nameplate_fallback: match basis {
+            Basis::Typical => rack
+                .devices
+                .iter()
+                .filter(|d| d.power.as_ref().is_some_and(|p| p.typical_w == 0))
+                .map(|d| d.id)
+                .collect(),
+            Basis::Nameplate => Vec::new(),
+        },
+        airflow: validation::check_airflow(rack),
+    }
+}
+
+/// A device's draw on `basis`, falling back to the nameplate rating for
+/// devices with no typical draw recorded.
+fn heat_watts(device: &Device, basis: Basis) -> Option<f64> {
+    let power = device.power.as_ref()?;
+    let watts = match basis {
+        Basis::Typical if power.typical_w > 0 => power.typical_w,
+        _ => power.nameplate_w,
+    };
+    Some(f64::from(watts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PowerDraw;
+
+    fn device(nameplate_w: u32, typical_w: u32) -> Device {
+        let mut device = Device::new("d", 1, 1, 600, Face::Front);
+        device.power = Some(PowerDraw {
+            nameplate_w,
+            typical_w,
+        });
+        device
+    }
+
+    fn layout(devices: Vec<Device>) -> Layout {
+        let mut rack = Rack::new("A1", 42);
+        rack.devices = devices;
+        Layout {
+            racks: vec![rack],
+            ..Layout::default()
+        }
+    }
+
+    #[test]
+    fn converts_watts_to_btu_per_hour() {
+        let report = report(&layout(vec![device(1500, 1000)]), Basis::Typical);
+        assert_eq!(report[0].load_w, 1000.0);
+        assert!((report[0].btu_per_hour - 3412.142).abs() < 1e-9);
+        assert_eq!(report[0].basis, Basis::Typical);
+    }
+
+    #[test]
+    fn uses_nameplate_ratings_when_asked() {
+        let report = report(&layout(vec![device(1500, 1000)]), Basis::Nameplate);
+        assert_eq!(report[0].load_w, 1500.0);
+        assert!(report[0].nameplate_fallback.is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_nameplate_without_a_typical_draw() {
+        let layout = layout(vec![device(1500, 1000), device(400, 0)]);
+        let report = report(&layout, Basis::Typical);
+        assert_eq!(report[0].load_w, 1400.0);
+        assert_eq!(
+            report[0].nameplate_fallback,
+            [layout.racks[0].devices[1].id]
+        );
+    }
+
+    #[test]
+    fn lists_active_devices_without_power_data() {
+        let mut unknown = device(0, 0);
+        unknown.power = None;
+        let layout = layout(vec![unknown]);
+        let report = report(&layout, Basis::Typical);
+        assert_eq!(report[0].load_w, 0.0);
+        assert_eq!(report[0].unknown, [layout.racks[0].devices[0].id]);
+    }
+}
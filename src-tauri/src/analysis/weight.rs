@@ -0,0 +1,205 @@
+//! Rack weight, center of gravity and static load checks.
+//!
+//! Each device's weight is taken to act at the middle of the units it
+//! occupies, and the empty rack's at half its height. Devices without a
+//! recorded weight are left out and listed so the UI can point out that the
+//! figures are incomplete.
+
+use serde::Serialize;
+
+use super::power::total;
+use crate::model::{Category, Device, DeviceId, Layout, Rack, RackId, UNIT_MM};
+
+/// A device mounted above lighter equipment.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inversion {
+    pub device_id: DeviceId,
+    pub weight_kg: f64,
+    /// Lighter devices mounted entirely below it.
+    pub lighter_below: Vec<DeviceId>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RackWeight {
+    pub rack_id: RackId,
+    pub name: String,
+    /// The rack and everything in it.
+    pub total_kg: f64,
+    /// The devices alone, which is what the load rating limits.
+    pub load_kg: f64,
+    /// Whether the weight of the empty rack is known and included.
+    pub rack_weighed: bool,
+    /// Devices without a recorded weight.
+    pub unweighed: Vec<DeviceId>,
+    /// Height of the center of gravity above the bottom of unit 1, or `None`
+    /// for a rack with nothing of any weight in it.
+    pub center_of_gravity_mm: Option<f64>,
+    /// Whether the center of gravity is in the upper half of the rack.
+    pub top_heavy: bool,
+    pub inversions: Vec<Inversion>,
+    pub load_rating_kg: Option<f64>,
+    /// Device load as a percentage of the load rating, if the rack has one.
+    pub load_percent: Option<f64>,
+    pub over_rating: bool,
+}
+
+pub fn report(layout: &Layout) -> Vec<RackWeight> {
+    layout.racks.iter().map(rack_weight).collect()
+}
+
+fn rack_weight(rack: &Rack) -> RackWeight {
+    let weighed: Vec<(&Device, f64)> = rack
+        .devices
+        .iter()
+        .filter_map(|d| Some((d, d.weight_kg?)))
+        .collect();
+    let load_kg = total(weighed.iter().map(|(_, kg)| *kg));
+    let rack_kg = rack.weight_kg.unwrap_or(0.0);
+    let total_kg = load_kg + rack_kg;
+    let moment =
+        total(weighed.iter().map(|(d, kg)| center_mm(d) * kg)) + rack_kg * rack_center_mm(rack);
+    let center_of_gravity_mm = (total_kg > 0.0).then(|| moment / total_kg);
+    let load_percent = rack
+        .load_rating_kg
+        .filter(|rating| *rating > 0.0)
+        .map(|rating| load_kg / rating * 100.0);
+
+    RackWeight {
+        rack_id: rack.id,
+        name: rack.name.clone(),
+        total_kg,
+        load_kg,
+        rack_weighed: rack.weight_kg.is_some(),
+        unweighed: rack
+            .devices
+            .iter()
+            .filter(|d| d.weight_kg.is_none())
+            .map(|d| d.id)
+            .collect(),
+        center_of_gravity_mm,
+        top_heavy: center_of_gravity_mm.is_some_and(|cog| cog > rack_center_mm(rack)),
+        inversions: inversions(&weighed),
+        load_rating_kg: rack.load_rating_kg,
+        load_percent,
+        over_rating: rack.load_rating_kg.is_some_and(|rating| load_kg > rating),
+    }
+}
+
+fn rack_center_mm(rack: &Rack) -> f64 {
+    f64::from(rack.height_u) * UNIT_MM / 2.0
+}
+
+fn center_mm(device: &Device) -> f64 {
+    (f64::from(device.position_u.saturating_sub(1)) + f64::from(device.height_u) / 2.0) * UNIT_MM
+}
+
+/// Devices heavier than something mounted below them, heaviest first.
+/// Blanking panels are ignored; they weigh next to nothing and would flag
+/// almost every device above one.
+fn inversions(weighed: &[(&Device, f64)]) -> Vec<Inversion> {
+    let mounted: Vec<_> = weighed
+        .iter()
+        .filter(|(d, _)| d.category != Category::BlankingPanel)
+        .collect();
+    let mut inversions: Vec<_> = mounted
+        .iter()
+        .filter_map(|(device, kg)| {
+            let lighter_below: Vec<_> = mounted
+                .iter()
+                .filter(|(other, other_kg)| {
+                    other.units().top < device.units().bottom && other_kg < kg
+                })
+                .map(|(other, _)| other.id)
+                .collect();
+            (!lighter_below.is_empty()).then_some(Inversion {
+                device_id: device.id,
+                weight_kg: *kg,
+                lighter_below,
+            })
+        })
+        .collect();
+    inversions.sort_by(|a, b| b.weight_kg.total_cmp(&a.weight_kg));
+    inversions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Face;
+
+    fn device(position_u: u32, height_u: u32, weight_kg: Option<f64>) -> Device {
+        let mut device = Device::new("d", height_u, position_u, 600, Face::Front);
+        device.weight_kg = weight_kg;
+        device
+    }
+
+    fn rack(devices: Vec<Device>) -> Rack {
+        let mut rack = Rack::new("A1", 42);
+        rack.devices = devices;
+        rack
+    }
+
+    #[test]
+    fn totals_devices_and_the_rack() {
+        let mut rack = rack(vec![device(1, 2, Some(30.0)), device(3, 1, None)]);
+        rack.weight_kg = Some(120.0);
+        let weight = rack_weight(&rack);
+        assert_eq!(weight.total_kg, 150.0);
+        assert_eq!(weight.load_kg, 30.0);
+        assert!(weight.rack_weighed);
+        assert_eq!(weight.unweighed, [rack.devices[1].id]);
+    }
+
+    #[test]
+    fn the_empty_rack_counts_toward_the_center_of_gravity() {
+        let mut rack = rack(vec![device(1, 2, Some(40.0))]);
+        let alone = rack_weight(&rack).center_of_gravity_mm.unwrap();
+        assert!((alone - UNIT_MM).abs() < 1e-9);
+
+        // 40 kg at 1U and 40 kg at 21U average out at 11U.
+        rack.weight_kg = Some(40.0);
+        let cog = rack_weight(&rack).center_of_gravity_mm.unwrap();
+        assert!((cog - 11.0 * UNIT_MM).abs() < 1e-9);
+    }
+
+    #[test]
+    fn checks_the_device_load_against_the_rating() {
+        let mut rack = rack(vec![device(1, 2, Some(600.0))]);
+        rack.weight_kg = Some(150.0);
+        rack.load_rating_kg = Some(700.0);
+        let weight = rack_weight(&rack);
+        assert!(!weight.over_rating);
+        assert!((weight.load_percent.unwrap() - 600.0 / 7.0).abs() < 1e-9);
+
+        rack.devices.push(device(10, 2, Some(150.0)));
+        let weight = rack_weight(&rack);
+        assert!(weight.over_rating);
+    }
+
+    #[test]
+    fn flags_racks_heavy_at_the_top() {
+        let low = rack(vec![device(1, 4, Some(50.0)), device(40, 1, Some(5.0))]);
+        assert!(!rack_weight(&low).top_heavy);
+        let high = rack(vec![device(1, 4, Some(5.0)), device(38, 4, Some(50.0))]);
+        assert!(rack_weight(&high).top_heavy);
+    }
+
+    #[test]
+    fn reports_heavier_devices_above_lighter_ones() {
+        let rack = rack(vec![device(1, 1, Some(5.0)), device(10, 2, Some(30.0))]);
+        let weight = rack_weight(&rack);
+        assert_eq!(weight.inversions.len(), 1);
+        assert_eq!(weight.inversions[0].device_id, rack.devices[1].id);
+        assert_eq!(weight.inversions[0].lighter_below, [rack.devices[0].id]);
+    }
+
+    #[test]
+    fn an_empty_rack_has_no_center_of_gravity() {
+        let weight = rack_weight(&rack(Vec::new()));
+        assert_eq!(weight.total_kg, 0.0);
+        assert_eq!(weight.center_of_gravity_mm, None);
+        assert!(!weight.top_heavy);
+    }
+}
@@ -0,0 +1,147 @@
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::cabling::{self, CableReport, Routing};
+use crate::error::{Error, Result};
+use crate::history::Edit;
+use crate::model::{Cable, CableId, DeviceId, Layout, Port, PortRef, Transceiver};
+use crate::state::AppState;
+
+/// Cables two ports together after checking they are free and compatible.
+#[tauri::command]
+pub fn connect_ports(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    a: PortRef,
+    b: PortRef,
+) -> Result<Cable> {
+    let cable = Cable::new(a, b);
+    let mut session = state.session(&window)?;
+    session.apply(Edit::Connect {
+        cable: cable.clone(),
+    })?;
+    Ok(cable)
+}
+
+#[tauri::command]
+pub fn disconnect_ports(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    cable_id: CableId,
+) -> Result<()> {
+    let mut session = state.session(&window)?;
+    let cable = find(session.layout(), cable_id)?.clone();
+    session.apply(Edit::Disconnect { cable })
+}
+
+/// Adds a port to a device, e.g. to cable a custom device that was created
+/// without any.
+#[tauri::command]
+pub fn add_port(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    port: Port,
+) -> Result<Port> {
+    let mut session = state.session(&window)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    let index = device.ports.len();
+    session.apply(Edit::InsertPort {
+        device_id,
+        index,
+        port: port.clone(),
+    })?;
+    Ok(port)
+}
+
+/// Removes a port from a device, disconnecting its cable first.
+#[tauri::command]
+pub fn remove_port(window: WebviewWindow, state: State<'_, AppState>, port: PortRef) -> Result<()> {
+    let mut session = state.session(&window)?;
+    let layout = session.layout();
+    let (_, device) = layout.find_device(port.device_id)?;
+    let index = device
+        .ports
+        .iter()
+        .position(|p| p.name == port.port)
+        .ok_or_else(|| Error::PortNotFound {
+            device_id: port.device_id,
+            port: port.port.clone(),
+        })?;
+    let edit = Edit::RemovePort {
+        device_id: port.device_id,
+        index,
+        port: device.ports[index].clone(),
+    };
+    let edit = match layout.cable_at(&port) {
+        Some(cable) => Edit::Batch {
+            label: edit.label(),
+            edits: vec![
+                Edit::Disconnect {
+                    cable: cable.clone(),
+                },
+                edit,
+            ],
+        },
+        None => edit,
+    };
+    session.apply(edit)
+}
+
+/// Sets the color of a cable's jacket, e.g. `blue`. A blank color clears it.
+#[tauri::command]
+pub fn set_cable_color(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    cable_id: CableId,
+    color: Option<String>,
+) -> Result<Cable> {
+    let mut session = state.session(&window)?;
+    let from = find(session.layout(), cable_id)?.color.clone();
+    session.apply(Edit::SetCableColor {
+        cable_id,
+        from,
+        to: color.filter(|c| !c.trim().is_empty()),
+    })?;
+    find(session.layout(), cable_id).cloned()
+}
+
+/// Fits a transceiver to a cage port, or removes it with `None`.
+#[tauri::command]
+pub fn set_transceiver(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    port: PortRef,
+    transceiver: Option<Transceiver>,
+) -> Result<Port> {
+    let mut session = state.session(&window)?;
+    let from = session.layout().port(&port)?.transceiver;
+    session.apply(Edit::SetTransceiver {
+        port: port.clone(),
+        from,
+        to: transceiver,
+    })?;
+    session.layout().port(&port).cloned()
+}
+
+/// Estimated lengths of every cable and the stock cables to order, using the
+/// default room assumptions for anything `routing` leaves out.
+#[tauri::command]
+pub fn cable_report(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    routing: Option<Routing>,
+) -> Result<CableReport> {
+    let session = state.session(&window)?;
+    Ok(cabling::report(
+        session.layout(),
+        &routing.unwrap_or_default(),
+    ))
+}
+
+fn find(layout: &Layout, id: CableId) -> Result<&Cable> {
+    layout
+        .cables
+        .iter()
+        .find(|c| c.id == id)
+        .ok_or(Error::CableNotFound(id))
+}
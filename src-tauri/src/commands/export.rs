@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::cabling::{self, Routing};
+use crate::error::{Error, Result};
+use crate::export::{csv, elevation, pdf, png, report, svg, table, workbook, xlsx};
+use crate::model::{Face, RackId};
+use crate::state::AppState;
+
+/// Faces to draw: the one asked for, or front and rear side by side.
+fn faces(face: Option<Face>) -> Vec<Face> {
+    face.map_or_else(|| vec![Face::Front, Face::Rear], |f| vec![f])
+}
+
+#[tauri::command]
+pub fn export_elevation_svg(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    face: Option<Face>,
+    path: PathBuf,
+) -> Result<()> {
+    let drawing = {
+        let session = state.session(&window)?;
+        elevation::render(session.layout().rack(rack_id)?, &faces(face))
+    };
+    fs::write(&path, svg::render(&drawing)).map_err(|e| Error::io(&path, e))
+}
+
+/// Rasterizes an elevation at `dpi` (150 by default), enlarged by `scale`.
+#[tauri::command]
+pub fn export_elevation_png(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    face: Option<Face>,
+    path: PathBuf,
+    dpi: Option<f64>,
+    scale: Option<f64>,
+) -> Result<()> {
+    let drawing = {
+        let session = state.session(&window)?;
+        elevation::render(session.layout().rack(rack_id)?, &faces(face))
+    };
+    let image = png::render(&drawing, dpi.unwrap_or(150.0), scale.unwrap_or(1.0))?;
+    fs::write(&path, image).map_err(|e| Error::io(&path, e))
+}
+
+/// Writes the documentation package for the whole project as a PDF.
+#[tauri::command]
+pub fn export_report_pdf(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<()> {
+    let (title, pages) = {
+        let session = state.session(&window)?;
+        let project = &session.project;
+        (project.metadata.name.clone(), report::pages(project))
+    };
+    fs::write(&path, pdf::render(&title, &pages)).map_err(|e| Error::io(&path, e))
+}
+
+/// Writes a workbook with a summary sheet and one sheet per rack.
+#[tauri::command]
+pub fn export_workbook_xlsx(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<()> {
+    let sheets = {
+        let session = state.session(&window)?;
+        workbook::sheets(&session.project)
+    };
+    fs::write(&path, xlsx::render(&sheets)?).map_err(|e| Error::io(&path, e))
+}
+
+#[tauri::command]
+pub fn export_devices_csv(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<()> {
+    let table = {
+        let session = state.session(&window)?;
+        table::devices(session.layout())
+    };
+    fs::write(&path, csv::render(&table)).map_err(|e| Error::io(&path, e))
+}
+
+/// Writes every cable with lengths estimated using `routing`.
+#[tauri::command]
+pub fn export_cables_csv(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    routing: Option<Routing>,
+) -> Result<()> {
+    let table = {
+        let session = state.session(&window)?;
+        let layout = session.layout();
+        let lengths = cabling::report(layout, &routing.unwrap_or_default());
+        table::cables(layout, &lengths)
+    };
+    fs::write(&path, csv::render(&table)).map_err(|e| Error::io(&path, e))
+}
+
+#[tauri::command]
+pub fn export_outlets_csv(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<()> {
+    let table = {
+        let session = state.session(&window)?;
+        table::outlets(session.layout())
+    };
+    fs::write(&path, csv::render(&table)).map_err(|e| Error::io(&path, e))
+}
@@ -0,0 +1,58 @@
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::floor::{self, FloorReport};
+use crate::error::{Error, Result};
+use crate::history::Edit;
+use crate::model::{Rack, RackId, Room, RowId};
+use crate::state::AppState;
+
+/// Replaces the room, its rows and its aisles, or removes the floor plan with
+/// `None`.
+#[tauri::command]
+pub fn set_room(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    room: Option<Room>,
+) -> Result<Option<Room>> {
+    let mut session = state.session(&window)?;
+    let from = session.layout().room.clone();
+    session.apply(Edit::SetRoom { from, to: room })?;
+    Ok(session.layout().room.clone())
+}
+
+/// Stands a rack in a slot of a row, with its front on the row's front line.
+#[tauri::command]
+pub fn place_rack_in_row(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    row_id: RowId,
+    slot: u32,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let layout = session.layout();
+    let room = layout.room.as_ref().ok_or(Error::NoRoom)?;
+    let row = room.row(row_id).ok_or(Error::RowNotFound(row_id))?;
+    if slot == 0 || slot > row.slots {
+        return Err(Error::InvalidSlot {
+            row: row.name.clone(),
+            slot,
+        });
+    }
+    let rack = layout.rack(rack_id)?;
+    let edit = Edit::SetRackPosition {
+        rack_id,
+        name: rack.name.clone(),
+        from: rack.position,
+        to: Some(row.slot_position(slot, rack.depth_mm)),
+    };
+    session.apply(edit)?;
+    session.layout().rack(rack_id).cloned()
+}
+
+/// Clearance, overlap and aisle problems for every rack on the floor.
+#[tauri::command]
+pub fn floor_report(window: WebviewWindow, state: State<'_, AppState>) -> Result<FloorReport> {
+    let session = state.session(&window)?;
+    Ok(floor::report(session.layout()))
+}
@@ -0,0 +1,27 @@
+use tauri::{State, WebviewWindow};
+
+use crate::error::Result;
+use crate::history::HistoryInfo;
+use crate::state::AppState;
+
+/// Reverts the most recent edit. The frontend should refetch the layout and
+/// project afterwards.
+#[tauri::command]
+pub fn undo(window: WebviewWindow, state: State<'_, AppState>) -> Result<HistoryInfo> {
+    let mut session = state.session(&window)?;
+    session.undo()?;
+    Ok(session.history.info())
+}
+
+#[tauri::command]
+pub fn redo(window: WebviewWindow, state: State<'_, AppState>) -> Result<HistoryInfo> {
+    let mut session = state.session(&window)?;
+    session.redo()?;
+    Ok(session.history.info())
+}
+
+#[tauri::command]
+pub fn history(window: WebviewWindow, state: State<'_, AppState>) -> Result<HistoryInfo> {
+    let session = state.session(&window)?;
+    Ok(session.history.info())
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{State, WebviewWindow};
+
+use crate::error::{Error, Result};
+use crate::import::csv::{self, ColumnMapping, CsvOptions, ImportReport};
+use crate::import::netbox::{self, Dump, NetboxReport};
+use crate::library;
+use crate::state::AppState;
+
+/// Reads a text file, replacing bytes that are not UTF-8 rather than failing
+/// on files saved in a legacy encoding.
+fn read_text(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The header row of a CSV file, for building a column mapping.
+#[tauri::command]
+pub fn csv_headers(path: PathBuf, delimiter: Option<char>) -> Result<Vec<String>> {
+    csv::headers(&read_text(&path)?, delimiter.unwrap_or(','))
+}
+
+/// Reports what importing a CSV file would do, without changing the project.
+#[tauri::command]
+pub fn preview_csv_import(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    mapping: ColumnMapping,
+    options: Option<CsvOptions>,
+) -> Result<ImportReport> {
+    let text = read_text(&path)?;
+    let templates = library::all(&state.library)?;
+    let session = state.session(&window)?;
+    let (_, report) = csv::plan(
+        session.layout(),
+        &templates,
+        &text,
+        &mapping,
+        &options.unwrap_or_default(),
+    )?;
+    Ok(report)
+}
+
+/// Imports every row of a CSV file that has no blocking issues, as one edit.
+#[tauri::command]
+pub fn import_csv(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    mapping: ColumnMapping,
+    options: Option<CsvOptions>,
+) -> Result<ImportReport> {
+    let text = read_text(&path)?;
+    let templates = library::all(&state.library)?;
+    let mut session = state.session(&window)?;
+    let (plan, report) = csv::plan(
+        session.layout(),
+        &templates,
+        &text,
+        &mapping,
+        &options.unwrap_or_default(),
+    )?;
+    if !plan.is_empty() {
+        let label = format!("Import {} devices from CSV", report.imported);
+        let edit = plan.into_edit(session.layout(), label);
+        session.apply(edit)?;
+    }
+    Ok(report)
+}
+
+/// Where each endpoint of a NetBox dump was saved.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetboxFiles {
+    pub racks: PathBuf,
+    pub devices: PathBuf,
+    pub device_types: PathBuf,
+}
+
+impl NetboxFiles {
+    pub(super) fn read(&self) -> Result<Dump> {
+        Dump::parse(
+            &read_text(&self.racks)?,
+            &read_text(&self.devices)?,
+            &read_text(&self.device_types)?,
+        )
+    }
+}
+
+/// Reports what importing a NetBox dump would do, without changing the
+/// project.
+#[tauri::command]
+pub fn preview_netbox_import(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    files: NetboxFiles,
+) -> Result<NetboxReport> {
+    let dump = files.read()?;
+    let templates = library::all(&state.library)?;
+    let session = state.session(&window)?;
+    let (_, report) = netbox::plan(session.layout(), &templates, &dump);
+    Ok(report)
+}
+
+/// Imports the racks in a NetBox dump and every device that can be placed,
+/// as one edit.
+#[tauri::command]
+pub fn import_netbox(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    files: NetboxFiles,
+) -> Result<NetboxReport> {
+    let dump = files.read()?;
+    let templates = library::all(&state.library)?;
+    let mut session = state.session(&window)?;
+    let (plan, report) = netbox::plan(session.layout(), &templates, &dump);
+    if !plan.is_empty() {
+        let label = format!("Import {} devices from NetBox", report.imported);
+        let edit = plan.into_edit(session.layout(), label);
+        session.apply(edit)?;
+    }
+    Ok(report)
+}
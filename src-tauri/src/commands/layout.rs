@@ -0,0 +1,254 @@
+use tauri::{State, WebviewWindow};
+
+use crate::error::Result;
+use crate::history::{Edit, Placement};
+use crate::model::{Device, DeviceId, Face, FloorPosition, Layout, Rack, RackId, DEFAULT_DEPTH_MM};
+use crate::state::AppState;
+use crate::validation::{self, Conflict};
+
+#[tauri::command]
+pub fn get_layout(window: WebviewWindow, state: State<'_, AppState>) -> Result<Layout> {
+    let session = state.session(&window)?;
+    Ok(session.layout().clone())
+}
+
+#[tauri::command]
+pub fn create_rack(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    name: String,
+    height_u: u32,
+    depth_mm: Option<u32>,
+) -> Result<Rack> {
+    let mut rack = Rack::new(name, height_u);
+    rack.depth_mm = depth_mm.unwrap_or(DEFAULT_DEPTH_MM);
+    let mut session = state.session(&window)?;
+    let index = session.layout().racks.len();
+    session.apply(Edit::InsertRack {
+        index,
+        rack: rack.clone(),
+    })?;
+    Ok(rack)
+}
+
+#[tauri::command]
+pub fn rename_rack(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    name: String,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let from = session.layout().rack(rack_id)?.name.clone();
+    session.apply(Edit::RenameRack {
+        rack_id,
+        from,
+        to: name,
+    })?;
+    session.layout().rack(rack_id).cloned()
+}
+
+/// Places a rack on the floor, or takes it off the floor plan with `None`.
+/// With `snap`, the rack's footprint is lined up with the room's floor tiles.
+#[tauri::command]
+pub fn set_rack_position(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    position: Option<FloorPosition>,
+    snap: Option<bool>,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let layout = session.layout();
+    let rack = layout.rack(rack_id)?;
+    let position = match (position, &layout.room) {
+        (Some(position), Some(room)) if snap.unwrap_or(false) => {
+            Some(room.snap(position, rack.depth_mm))
+        }
+        _ => position,
+    };
+    let edit = Edit::SetRackPosition {
+        rack_id,
+        name: rack.name.clone(),
+        from: rack.position,
+        to: position,
+    };
+    session.apply(edit)?;
+    session.layout().rack(rack_id).cloned()
+}
+
+#[tauri::command]
+pub fn delete_rack(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+) -> Result<()> {
+    let mut session = state.session(&window)?;
+    let layout = session.layout();
+    let rack = layout.rack(rack_id)?;
+    let index = layout.racks.iter().position(|r| r.id == rack_id).unwrap();
+    let devices: Vec<_> = rack.devices.iter().map(|d| d.id).collect();
+    let edit = Edit::RemoveRack {
+        index,
+        rack: rack.clone(),
+    };
+    let edit = unplugging(layout, &devices, unpowering(layout, rack, edit));
+    session.apply(edit)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn add_device(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    name: String,
+    height_u: u32,
+    position_u: u32,
+    depth_mm: u32,
+    face: Face,
+) -> Result<Device> {
+    let device = Device::new(name, height_u, position_u, depth_mm, face);
+    let mut session = state.session(&window)?;
+    session.apply(Edit::AddDevice {
+        rack_id,
+        device: device.clone(),
+    })?;
+    Ok(device)
+}
+
+/// Moves a device, possibly to another rack. Pass `coalesce` for every move
+/// after the first while dragging so the whole drag is undone at once.
+#[tauri::command]
+pub fn move_device(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    rack_id: RackId,
+    position_u: u32,
+    face: Face,
+    coalesce: Option<bool>,
+) -> Result<Device> {
+    let mut session = state.session(&window)?;
+    let (rack, device) = session.layout().find_device(device_id)?;
+    let edit = Edit::MoveDevice {
+        device_id,
+        name: device.name.clone(),
+        from: Placement {
+            rack_id: rack.id,
+            position_u: device.position_u,
+            face: device.face,
+        },
+        to: Placement {
+            rack_id,
+            position_u,
+            face,
+        },
+    };
+    session.apply_coalescing(edit, coalesce.unwrap_or(false))?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    Ok(device.clone())
+}
+
+#[tauri::command]
+pub fn remove_device(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+) -> Result<()> {
+    let mut session = state.session(&window)?;
+    let (rack, device) = session.layout().find_device(device_id)?;
+    let edit = Edit::RemoveDevice {
+        rack_id: rack.id,
+        device: device.clone(),
+    };
+    let edit = unplugging(session.layout(), &[device_id], edit);
+    session.apply(edit)
+}
+
+/// Sets a device's serial number. A blank serial clears it.
+#[tauri::command]
+pub fn set_serial(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    serial: Option<String>,
+) -> Result<Device> {
+    let mut session = state.session(&window)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    let edit = Edit::SetSerial {
+        device_id,
+        name: device.name.clone(),
+        from: device.serial.clone(),
+        to: serial.filter(|s| !s.trim().is_empty()),
+    };
+    session.apply(edit)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    Ok(device.clone())
+}
+
+/// Reports what would stop a device from going at a position, without placing
+/// it. Pass `device_id` when previewing a move so the device ignores itself.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn check_placement(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    device_id: Option<DeviceId>,
+    height_u: u32,
+    position_u: u32,
+    depth_mm: u32,
+    face: Face,
+) -> Result<Vec<Conflict>> {
+    let session = state.session(&window)?;
+    let rack = session.layout().rack(rack_id)?;
+    let mut device = Device::new("", height_u, position_u, depth_mm, face);
+    if let Some(id) = device_id {
+        device.id = id;
+    }
+    Ok(validation::check_placement(rack, &device))
+}
+
+/// Puts `edit` in a batch after unplugging devices in other racks from the
+/// PDUs in `rack`, so undo plugs them back in once the rack is restored.
+fn unpowering(layout: &Layout, rack: &Rack, edit: Edit) -> Edit {
+    let mut edits: Vec<_> = layout
+        .devices()
+        .filter(|(r, _)| r.id != rack.id)
+        .flat_map(|(_, device)| {
+            device
+                .power_connections
+                .iter()
+                .filter(|c| rack.pdu(c.pdu_id).is_some())
+                .map(|connection| Edit::DisconnectPower {
+                    device_id: device.id,
+                    connection: connection.clone(),
+                })
+        })
+        .collect();
+    if edits.is_empty() {
+        return edit;
+    }
+    let label = edit.label();
+    edits.push(edit);
+    Edit::Batch { label, edits }
+}
+
+/// Puts `edit` in a batch after unplugging every cable on `devices`, so undo
+/// plugs them back in.
+fn unplugging(layout: &Layout, devices: &[DeviceId], edit: Edit) -> Edit {
+    let cables = layout.cables_of(devices);
+    if cables.is_empty() {
+        return edit;
+    }
+    let label = edit.label();
+    let mut edits: Vec<_> = cables
+        .into_iter()
+        .map(|cable| Edit::Disconnect {
+            cable: cable.clone(),
+        })
+        .collect();
+    edits.push(edit);
+    Edit::Batch { label, edits }
+}
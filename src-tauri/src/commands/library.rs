@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{State, WebviewWindow};
+
+use crate::error::Result;
+use crate::history::Edit;
+use crate::library::devicetype::{self, SkippedFile};
+use crate::library::{self, DeviceTemplate};
+use crate::model::{Category, Device, Face, RackId};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_templates(state: State<'_, AppState>) -> Result<Vec<DeviceTemplate>> {
+    library::all(&state.library)
+}
+
+#[tauri::command]
+pub fn search_templates(
+    state: State<'_, AppState>,
+    query: String,
+    category: Option<Category>,
+) -> Result<Vec<DeviceTemplate>> {
+    let templates = library::all(&state.library)?;
+    Ok(library::search(&templates, &query, category)
+        .into_iter()
+        .cloned()
+        .collect())
+}
+
+#[tauri::command]
+pub fn create_template(
+    state: State<'_, AppState>,
+    template: DeviceTemplate,
+) -> Result<DeviceTemplate> {
+    state.library.create(template)
+}
+
+#[tauri::command]
+pub fn edit_template(
+    state: State<'_, AppState>,
+    template: DeviceTemplate,
+) -> Result<DeviceTemplate> {
+    state.library.edit(template)
+}
+
+#[tauri::command]
+pub fn delete_template(state: State<'_, AppState>, template_id: String) -> Result<()> {
+    state.library.delete(&template_id)
+}
+
+#[tauri::command]
+pub fn import_templates(state: State<'_, AppState>, path: PathBuf) -> Result<Vec<DeviceTemplate>> {
+    state.library.import(&path)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicetypeImport {
+    pub added: usize,
+    pub replaced: usize,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Imports every device type in a checkout of the NetBox devicetype-library
+/// into the user library. Files that cannot be imported are reported rather
+/// than failing the whole import.
+#[tauri::command]
+pub fn import_devicetype_library(
+    state: State<'_, AppState>,
+    dir: PathBuf,
+) -> Result<DevicetypeImport> {
+    let batch = devicetype::read_dir(&dir)?;
+    let (added, replaced) = state.library.merge(batch.templates)?;
+    Ok(DevicetypeImport {
+        added,
+        replaced,
+        skipped: batch.skipped,
+    })
+}
+
+/// Exports the templates with the given ids, or the whole user library when
+/// `template_ids` is omitted. Returns how many templates were written.
+#[tauri::command]
+pub fn export_templates(
+    state: State<'_, AppState>,
+    path: PathBuf,
+    template_ids: Option<Vec<String>>,
+) -> Result<usize> {
+    state.library.export(&path, template_ids.as_deref())
+}
+
+/// Places a new device built from a library template. The device is named
+/// after the template's model unless `name` is given.
+#[tauri::command]
+pub fn add_device_from_template(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    template_id: String,
+    name: Option<String>,
+    position_u: u32,
+    face: Face,
+) -> Result<Device> {
+    let template = library::find(&state.library, &template_id)?;
+    let name = name.unwrap_or_else(|| template.model.clone());
+    let device = template.instantiate(name, position_u, face);
+    let mut session = state.session(&window)?;
+    session.apply(Edit::AddDevice {
+        rack_id,
+        device: device.clone(),
+    })?;
+    Ok(device)
+}
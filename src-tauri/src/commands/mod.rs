@@ -0,0 +1,13 @@
+pub mod cabling;
+pub mod export;
+pub mod floor;
+pub mod history;
+pub mod import;
+pub mod layout;
+pub mod library;
+pub mod netbox;
+pub mod power;
+pub mod project;
+pub mod thermal;
+pub mod weight;
+pub mod window;
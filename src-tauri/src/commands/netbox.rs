@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{State, WebviewWindow};
+
+use super::import::NetboxFiles;
+use crate::error::{Error, Result};
+use crate::export::netbox::{self, NetboxOptions};
+use crate::state::AppState;
+use crate::sync::{self, NetboxServer, PushPlan, PushReport, SyncDiff};
+
+/// Writes NetBox API payloads for every rack and device. With a snapshot,
+/// imported objects refer to their NetBox device types and roles by id.
+/// Returns the names of devices left out for lack of a model.
+#[tauri::command]
+pub fn export_netbox_json(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    options: NetboxOptions,
+    snapshot: Option<NetboxFiles>,
+) -> Result<Vec<String>> {
+    let snapshot = snapshot.map(|files| files.read()).transpose()?;
+    let (payloads, unexported) = {
+        let session = state.session(&window)?;
+        netbox::payloads(session.layout(), snapshot.as_ref(), &options)
+    };
+    let json = serde_json::to_vec_pretty(&payloads).map_err(|e| Error::Encode(e.to_string()))?;
+    fs::write(&path, json).map_err(|e| Error::io(&path, e))?;
+    Ok(unexported)
+}
+
+/// What changed in the project since a NetBox snapshot was imported.
+#[tauri::command]
+pub fn netbox_diff(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    snapshot: NetboxFiles,
+) -> Result<SyncDiff> {
+    let snapshot = snapshot.read()?;
+    let session = state.session(&window)?;
+    Ok(sync::diff(session.layout(), &snapshot))
+}
+
+/// Sends the differences from a snapshot to NetBox, deleting removed devices
+/// only if asked to, and links anything created to its new id.
+#[tauri::command]
+pub async fn push_to_netbox(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    snapshot: NetboxFiles,
+    server: NetboxServer,
+    options: NetboxOptions,
+    delete_removed: Option<bool>,
+) -> Result<PushReport> {
+    let snapshot = snapshot.read()?;
+    let plan = {
+        let session = state.session(&window)?;
+        let diff = sync::diff(session.layout(), &snapshot);
+        PushPlan::new(
+            session.layout(),
+            &snapshot,
+            &diff,
+            &options,
+            delete_removed.unwrap_or(false),
+        )
+    };
+    let report = sync::push(&server, plan).await;
+    let mut session = state.session(&window)?;
+    if let Some(edit) = report.link_edit(session.layout()) {
+        session.apply(edit)?;
+    }
+    Ok(report)
+}
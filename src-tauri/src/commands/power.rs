@@ -0,0 +1,121 @@
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::failover::{self, FailoverReport};
+use crate::analysis::power::{self, Basis, RackPower};
+use crate::error::Result;
+use crate::history::Edit;
+use crate::model::{Circuit, DeviceId, Feed, Pdu, PduId, PowerConnection, RackId};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn add_pdu(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    name: String,
+    voltage_v: f64,
+    feed: Option<Feed>,
+    circuits: Vec<Circuit>,
+) -> Result<Pdu> {
+    let mut pdu = Pdu::new(name, voltage_v, circuits);
+    pdu.feed = feed;
+    let mut session = state.session(&window)?;
+    let index = session.layout().rack(rack_id)?.pdus.len();
+    session.apply(Edit::InsertPdu {
+        rack_id,
+        index,
+        pdu: pdu.clone(),
+        connections: Vec::new(),
+    })?;
+    Ok(pdu)
+}
+
+/// Removes a PDU, unplugging every device connected to it.
+#[tauri::command]
+pub fn remove_pdu(window: WebviewWindow, state: State<'_, AppState>, pdu_id: PduId) -> Result<()> {
+    let mut session = state.session(&window)?;
+    // Dry-run the removal on a copy to capture the connections for undo.
+    let removed = session.layout().clone().remove_pdu(pdu_id)?;
+    session.apply(Edit::RemovePdu {
+        rack_id: removed.rack_id,
+        index: removed.index,
+        pdu: removed.pdu,
+        connections: removed.connections,
+    })
+}
+
+#[tauri::command]
+pub fn set_pdu_feed(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    pdu_id: PduId,
+    feed: Option<Feed>,
+) -> Result<Pdu> {
+    let mut session = state.session(&window)?;
+    let (_, pdu) = session.layout().find_pdu(pdu_id)?;
+    let edit = Edit::SetPduFeed {
+        pdu_id,
+        name: pdu.name.clone(),
+        from: pdu.feed,
+        to: feed,
+    };
+    session.apply(edit)?;
+    let (_, pdu) = session.layout().find_pdu(pdu_id)?;
+    Ok(pdu.clone())
+}
+
+#[tauri::command]
+pub fn connect_power(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    pdu_id: PduId,
+    outlet: String,
+) -> Result<()> {
+    let mut session = state.session(&window)?;
+    session.apply(Edit::ConnectPower {
+        device_id,
+        connection: PowerConnection { pdu_id, outlet },
+    })
+}
+
+#[tauri::command]
+pub fn disconnect_power(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    pdu_id: PduId,
+    outlet: String,
+) -> Result<()> {
+    let mut session = state.session(&window)?;
+    session.apply(Edit::DisconnectPower {
+        device_id,
+        connection: PowerConnection { pdu_id, outlet },
+    })
+}
+
+/// Loads per rack, PDU, circuit and phase, summed from nameplate ratings
+/// unless `basis` says otherwise.
+#[tauri::command]
+pub fn power_report(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    basis: Option<Basis>,
+) -> Result<Vec<RackPower>> {
+    let session = state.session(&window)?;
+    Ok(power::report(session.layout(), basis.unwrap_or_default()))
+}
+
+/// Simulates the loss of feed A and then feed B for every rack.
+#[tauri::command]
+pub fn failover_report(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    basis: Option<Basis>,
+) -> Result<Vec<FailoverReport>> {
+    let session = state.session(&window)?;
+    Ok(failover::report(
+        session.layout(),
+        basis.unwrap_or_default(),
+    ))
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use tauri::{Manager, State, WebviewWindow};
+
+use crate::error::{Error, Result};
+use crate::history::Edit;
+use crate::project::{Metadata, Project, FILE_EXTENSION};
+use crate::state::{close_windows, AppState, ProjectInfo, Session};
+
+/// Replaces the open project with an empty one. Unsaved changes are
+/// discarded; the frontend is expected to check `dirty` first.
+#[tauri::command]
+pub fn new_project(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<ProjectInfo> {
+    replace(&window, &state, Session::new(Project::new(name), None))
+}
+
+#[tauri::command]
+pub fn get_project(window: WebviewWindow, state: State<'_, AppState>) -> Result<ProjectInfo> {
+    let session = state.session(&window)?;
+    Ok(session.info())
+}
+
+#[tauri::command]
+pub fn set_project_metadata(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    metadata: Metadata,
+) -> Result<ProjectInfo> {
+    let mut session = state.session(&window)?;
+    let edit = Edit::SetMetadata {
+        from: session.project.metadata.clone(),
+        to: metadata,
+    };
+    session.apply(edit)?;
+    Ok(session.info())
+}
+
+/// Loads `path` into the calling window. A file already open in another
+/// window is focused there instead, so the two copies cannot diverge.
+#[tauri::command]
+pub fn open_project(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<ProjectInfo> {
+    if let Some(label) = state.window_showing(&path) {
+        if label != window.label() {
+            if let Some(other) = window.get_webview_window(&label) {
+                let _ = other.set_focus();
+            }
+            return Err(Error::AlreadyOpen {
+                path,
+                window: label,
+            });
+        }
+    }
+    let project = Project::load(&path)?;
+    replace(&window, &state, Session::new(project, Some(path)))
+}
+
+#[tauri::command]
+pub fn save_project(window: WebviewWindow, state: State<'_, AppState>) -> Result<ProjectInfo> {
+    let mut session = state.session(&window)?;
+    let path = session.path.clone().ok_or(Error::NoProjectPath)?;
+    session.project.save(&path)?;
+    session.history.mark_saved();
+    Ok(session.info())
+}
+
+#[tauri::command]
+pub fn save_project_as(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    mut path: PathBuf,
+) -> Result<ProjectInfo> {
+    if path.extension().is_none() {
+        path.set_extension(FILE_EXTENSION);
+    }
+    let mut session = state.session(&window)?;
+    session.project.save(&path)?;
+    session.path = Some(path);
+    session.history.mark_saved();
+    Ok(session.info())
+}
+
+/// Puts `session` in the calling window, closing rack windows detached from
+/// the old project that show racks the new one does not have.
+fn replace(window: &WebviewWindow, state: &AppState, session: Session) -> Result<ProjectInfo> {
+    let (info, stale) = {
+        let mut guard = state.session(window)?;
+        let stale = guard.replace(session);
+        (guard.info(), stale)
+    };
+    close_windows(window, &stale);
+    Ok(info)
+}
@@ -0,0 +1,62 @@
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::power::Basis;
+use crate::analysis::thermal::{self, RackThermal};
+use crate::error::Result;
+use crate::history::Edit;
+use crate::model::{Airflow, Device, DeviceId, Face, Rack, RackId};
+use crate::state::AppState;
+
+/// Sets or clears (with `None`) a device's airflow direction.
+#[tauri::command]
+pub fn set_airflow(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    airflow: Option<Airflow>,
+) -> Result<Device> {
+    let mut session = state.session(&window)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    let edit = Edit::SetAirflow {
+        device_id,
+        name: device.name.clone(),
+        from: device.airflow,
+        to: airflow,
+    };
+    session.apply(edit)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    Ok(device.clone())
+}
+
+#[tauri::command]
+pub fn set_cold_aisle(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    cold_aisle: Face,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let rack = session.layout().rack(rack_id)?;
+    let edit = Edit::SetColdAisle {
+        rack_id,
+        name: rack.name.clone(),
+        from: rack.cold_aisle,
+        to: cold_aisle,
+    };
+    session.apply(edit)?;
+    session.layout().rack(rack_id).cloned()
+}
+
+/// Heat output and airflow warnings for every rack.
+#[tauri::command]
+pub fn thermal_report(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    basis: Option<Basis>,
+) -> Result<Vec<RackThermal>> {
+    let session = state.session(&window)?;
+    Ok(thermal::report(
+        session.layout(),
+        basis.unwrap_or(Basis::Typical),
+    ))
+}
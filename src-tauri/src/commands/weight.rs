@@ -0,0 +1,74 @@
+use tauri::{State, WebviewWindow};
+
+use crate::analysis::weight::{self, RackWeight};
+use crate::error::Result;
+use crate::history::Edit;
+use crate::model::{Device, DeviceId, Rack, RackId};
+use crate::state::AppState;
+
+/// Sets or clears (with `None`) a device's weight.
+#[tauri::command]
+pub fn set_device_weight(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    device_id: DeviceId,
+    weight_kg: Option<f64>,
+) -> Result<Device> {
+    let mut session = state.session(&window)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    let edit = Edit::SetDeviceWeight {
+        device_id,
+        name: device.name.clone(),
+        from: device.weight_kg,
+        to: weight_kg,
+    };
+    session.apply(edit)?;
+    let (_, device) = session.layout().find_device(device_id)?;
+    Ok(device.clone())
+}
+
+/// Sets or clears (with `None`) the weight of an empty rack.
+#[tauri::command]
+pub fn set_rack_weight(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    weight_kg: Option<f64>,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let rack = session.layout().rack(rack_id)?;
+    let edit = Edit::SetRackWeight {
+        rack_id,
+        name: rack.name.clone(),
+        from: rack.weight_kg,
+        to: weight_kg,
+    };
+    session.apply(edit)?;
+    session.layout().rack(rack_id).cloned()
+}
+
+/// Sets or clears (with `None`) a rack's static load rating.
+#[tauri::command]
+pub fn set_load_rating(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+    load_rating_kg: Option<f64>,
+) -> Result<Rack> {
+    let mut session = state.session(&window)?;
+    let rack = session.layout().rack(rack_id)?;
+    let edit = Edit::SetLoadRating {
+        rack_id,
+        name: rack.name.clone(),
+        from: rack.load_rating_kg,
+        to: load_rating_kg,
+    };
+    session.apply(edit)?;
+    session.layout().rack(rack_id).cloned()
+}
+
+#[tauri::command]
+pub fn weight_report(window: WebviewWindow, state: State<'_, AppState>) -> Result<Vec<RackWeight>> {
+    let session = state.session(&window)?;
+    Ok(weight::report(session.layout()))
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::error::{Error, Result};
+use crate::model::RackId;
+use crate::project::Project;
+use crate::state::{AppState, Session};
+
+/// Label prefixes of windows opened by these commands. The window opened at
+/// startup is labelled `main`.
+const PROJECT_WINDOW: &str = "project";
+const RACK_WINDOW: &str = "rack";
+
+/// What the calling window should show.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub label: String,
+    /// The rack shown by a window detached with `open_rack_window`, or `None`
+    /// for a project window.
+    pub rack_id: Option<RackId>,
+}
+
+#[tauri::command]
+pub fn window_info(window: WebviewWindow, state: State<'_, AppState>) -> WindowInfo {
+    WindowInfo {
+        label: window.label().to_owned(),
+        rack_id: state.window_rack(window.label()),
+    }
+}
+
+/// Opens a project in a new window, or an empty project without `path`, and
+/// returns the window's label. A project already open in a window has that
+/// window focused instead.
+#[tauri::command]
+pub async fn open_window(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: Option<PathBuf>,
+) -> Result<String> {
+    let session = match path {
+        Some(path) => {
+            if let Some(label) = state.window_showing(&path) {
+                if let Some(window) = app.get_webview_window(&label) {
+                    let _ = window.set_focus();
+                }
+                return Ok(label);
+            }
+            Session::new(Project::load(&path)?, Some(path))
+        }
+        None => Session::default(),
+    };
+    let title = session.title(None);
+    let label = state.open_window(PROJECT_WINDOW, session);
+    if let Err(err) = build(&app, &label, title, (1280.0, 800.0)) {
+        state.close_window(&label);
+        return Err(err);
+    }
+    Ok(label)
+}
+
+/// Opens a rack of the calling window's project in a window of its own, for
+/// comparing racks side by side. Edits made there apply to the same project
+/// and are undone with it.
+#[tauri::command]
+pub async fn open_rack_window(
+    window: WebviewWindow,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    rack_id: RackId,
+) -> Result<String> {
+    let title = {
+        let session = state.session(&window)?;
+        session.layout().rack(rack_id)?;
+        session.title(Some(rack_id))
+    };
+    let label = state.detach_rack(RACK_WINDOW, window.label(), rack_id)?;
+    if let Err(err) = build(&app, &label, title, (560.0, 900.0)) {
+        state.close_window(&label);
+        return Err(err);
+    }
+    Ok(label)
+}
+
+/// Creates a window showing the frontend. Only call this from async commands:
+/// creating a window from a sync command deadlocks on Windows.
+fn build(app: &AppHandle, label: &str, title: String, (width, height): (f64, f64)) -> Result<()> {
+    WebviewWindowBuilder::new(app, label, WebviewUrl::default())
+        .title(title)
+        .inner_size(width, height)
+        .build()
+        .map_err(|e| Error::Window(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::model::{CableId, DeviceId, PduId, PortKind, RackId, RowId};
+use crate::validation::Conflict;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned to the frontend from commands.
+///
+/// Serialized as `{ "kind": ..., "details": ... }` so the UI can react to
+/// specific failures instead of parsing messages.
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "camelCase")]
+pub enum Error {
+    #[error("rack {0} does not exist")]
+    RackNotFound(RackId),
+    #[error("device {0} does not exist")]
+    DeviceNotFound(DeviceId),
+    #[error("rack row {0} does not exist")]
+    RowNotFound(RowId),
+    #[error("the project has no floor plan")]
+    NoRoom,
+    #[error("row {row} has no slot {slot}")]
+    InvalidSlot { row: String, slot: u32 },
+    #[error("PDU {0} does not exist")]
+    PduNotFound(PduId),
+    #[error("PDU {pdu_id} has no outlet {outlet}")]
+    #[serde(rename_all = "camelCase")]
+    OutletNotFound { pdu_id: PduId, outlet: String },
+    #[error("outlet {outlet} is already used by device {device_id}")]
+    #[serde(rename_all = "camelCase")]
+    OutletInUse { outlet: String, device_id: DeviceId },
+    #[error("invalid PDU: {0}")]
+    InvalidPdu(String),
+    #[error("device {device_id} has no port {port}")]
+    #[serde(rename_all = "camelCase")]
+    PortNotFound { device_id: DeviceId, port: String },
+    #[error("port {port} on device {device_id} is already cabled")]
+    #[serde(rename_all = "camelCase")]
+    PortInUse {
+        device_id: DeviceId,
+        port: String,
+        cable_id: CableId,
+    },
+    #[error("device {device_id} already has a port named {port}")]
+    #[serde(rename_all = "camelCase")]
+    DuplicatePort { device_id: DeviceId, port: String },
+    #[error("cable {0} does not exist")]
+    CableNotFound(CableId),
+    #[error("cannot connect {a} to {b}: {reason}")]
+    IncompatiblePorts {
+        a: String,
+        b: String,
+        reason: &'static str,
+    },
+    #[error("{} port {port} cannot take that transceiver", .kind.label())]
+    InvalidTransceiver { port: String, kind: PortKind },
+    #[error("device template {0} does not exist")]
+    TemplateNotFound(String),
+    #[error("device template {id}: {field} {message}")]
+    InvalidTemplate {
+        id: String,
+        field: String,
+        message: String,
+    },
+    #[error("invalid device library at {path}: {message}")]
+    InvalidLibrary { path: String, message: String },
+    #[error("{0} must not be empty")]
+    EmptyName(&'static str),
+    #[error("{field} must be greater than zero")]
+    ZeroSize { field: &'static str },
+    #[error("{field} must be a non-negative number")]
+    InvalidWeight { field: &'static str },
+    #[error("floor position must be a finite number of millimeters")]
+    InvalidPosition,
+    #[error("device cannot be placed there ({} conflicts)", .0.len())]
+    InvalidPlacement(Vec<Conflict>),
+    #[error("{}: {message}", .path.display())]
+    Io { path: PathBuf, message: String },
+    /// `path` points at the offending value, e.g. `layout.racks[2].heightU`.
+    #[error("invalid project file at {path}: {message}")]
+    InvalidProject { path: String, message: String },
+    #[error("project file version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u64, supported: u32 },
+    #[error("invalid resolution {dpi} dpi at scale {scale}")]
+    InvalidResolution { dpi: f64, scale: f64 },
+    #[error("a {width}x{height} pixel image is too large to export")]
+    ImageTooLarge { width: u32, height: u32 },
+    #[error("could not encode file: {0}")]
+    Encode(String),
+    #[error("invalid CSV at line {line}: {message}")]
+    InvalidCsv { line: usize, message: String },
+    #[error("the file has no column named {0}")]
+    UnknownColumn(String),
+    #[error("invalid NetBox {endpoint} dump: {message}")]
+    InvalidNetbox {
+        endpoint: &'static str,
+        message: String,
+    },
+    #[error("NetBox request failed: {message}")]
+    NetboxRequest {
+        status: Option<u16>,
+        message: String,
+    },
+    #[error("could not open window: {0}")]
+    Window(String),
+    #[error("window {0} is not showing a project")]
+    UnknownWindow(String),
+    #[error("the project has not been saved yet")]
+    NoProjectPath,
+    /// The file is open in the window labelled `window`, which has been
+    /// brought to the front instead.
+    #[error("{} is already open in another window", .path.display())]
+    AlreadyOpen { path: PathBuf, window: String },
+}
+
+impl Error {
+    pub fn io(path: &Path, err: std::io::Error) -> Self {
+        Error::Io {
+            path: path.to_owned(),
+            message: err.to_string(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Writes tables as RFC 4180 CSV with a header row.
+
+use super::table::Table;
+
+pub fn render(table: &Table) -> String {
+    let mut csv = String::new();
+    write_row(&mut csv, table.columns.iter().copied());
+    for row in &table.rows {
+        write_row(&mut csv, row.iter().map(String::as_str));
+    }
+    csv
+}
+
+fn write_row<'a>(csv: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(field);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: Vec<Vec<&str>>) -> Table {
+        Table {
+            columns: &["Name", "Notes"],
+            rows: rows
+                .into_iter()
+                .map(|r| r.into_iter().map(String::from).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn writes_a_header_and_crlf_rows() {
+        let csv = render(&table(vec![vec!["web-01", "spare"]]));
+        assert_eq!(csv, "Name,Notes\r\nweb-01,spare\r\n");
+    }
+
+    #[test]
+    fn quotes_fields_with_commas() {
+        let csv = render(&table(vec![vec!["web-01, web-02", ""]]));
+        assert_eq!(csv, "Name,Notes\r\n\"web-01, web-02\",\r\n");
+    }
+
+    #[test]
+    fn doubles_quotes_inside_fields() {
+        let csv = render(&table(vec![vec!["19\" shelf", "x"]]));
+        assert_eq!(csv, "Name,Notes\r\n\"19\"\" shelf\",x\r\n");
+    }
+
+    #[test]
+    fn quotes_fields_with_line_breaks() {
+        let csv = render(&table(vec![
+            vec!["a", "line one\nline two"],
+            vec!["b", "cr\r"],
+        ]));
+        assert_eq!(
+            csv,
+            "Name,Notes\r\na,\"line one\nline two\"\r\nb,\"cr\r\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! A minimal vector scene shared by the SVG, PDF and PNG renderers.
+//!
+//! Coordinates are in points (1/72 inch) with the origin at the top left and
+//! y growing downwards.
+
+use crate::model::Category;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0x00, 0x00, 0x00);
+    pub const WHITE: Color = Color(0xff, 0xff, 0xff);
+    pub const DARK_GRAY: Color = Color(0x40, 0x40, 0x40);
+    pub const GRAY: Color = Color(0x99, 0x99, 0x99);
+    pub const LIGHT_GRAY: Color = Color(0xe6, 0xe6, 0xe6);
+
+    pub fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Fill color used for devices of a category.
+    pub fn for_category(category: Category) -> Color {
+        match category {
+            Category::Server => Color(0x8e, 0xc5, 0xfc),
+            Category::Switch => Color(0x9a, 0xe6, 0xa6),
+            Category::PatchPanel => Color(0xfc, 0xe3, 0x8a),
+            Category::Pdu => Color(0xf9, 0xa8, 0x8b),
+            Category::Ups => Color(0xd8, 0xb4, 0xfe),
+            Category::BlankingPanel => Color(0xd4, 0xd4, 0xd4),
+            Category::Shelf => Color(0xc8, 0xb0, 0x96),
+            Category::Other => Color(0xe5, 0xe7, 0xeb),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        color: Color,
+        width: f64,
+    },
+    /// Text whose baseline sits `size / 3` below `y`, so `y` is roughly the
+    /// vertical middle of the glyphs.
+    Text {
+        x: f64,
+        y: f64,
+        size: f64,
+        anchor: Anchor,
+        bold: bool,
+        color: Color,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drawing {
+    pub width: f64,
+    pub height: f64,
+    pub shapes: Vec<Shape>,
+}
+
+impl Drawing {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            shapes: Vec::new(),
+        }
+    }
+
+    pub fn rect(
+        &mut self,
+        (x, y, width, height): (f64, f64, f64, f64),
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    ) {
+        self.shapes.push(Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            stroke,
+        });
+    }
+
+    pub fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), color: Color, width: f64) {
+        self.shapes.push(Shape::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            color,
+            width,
+        });
+    }
+
+    pub fn text(&mut self, at: (f64, f64), size: f64, anchor: Anchor, text: impl Into<String>) {
+        self.styled_text(at, size, anchor, false, Color::BLACK, text);
+    }
+
+    pub fn heading(&mut self, at: (f64, f64), size: f64, text: impl Into<String>) {
+        self.styled_text(at, size, Anchor::Start, true, Color::BLACK, text);
+    }
+
+    pub fn styled_text(
+        &mut self,
+        (x, y): (f64, f64),
+        size: f64,
+        anchor: Anchor,
+        bold: bool,
+        color: Color,
+        text: impl Into<String>,
+    ) {
+        self.shapes.push(Shape::Text {
+            x,
+            y,
+            size,
+            anchor,
+            bold,
+            color,
+            text: text.into(),
+        });
+    }
+
+    /// Fills a rectangle with 45° hatching, clipped to its bounds.
+    pub fn hatch(
+        &mut self,
+        (x, y, width, height): (f64, f64, f64, f64),
+        spacing: f64,
+        color: Color,
+    ) {
+        let mut offset = spacing;
+        while offset < width + height {
+            // Each line runs from the left or top edge down to the bottom or
+            // right edge.
+            let start = (
+                x + (offset - height).max(0.0),
+                y + height - offset.min(height),
+            );
+            let end = (
+                x + offset.min(width),
+                y + height - (offset - width).max(0.0),
+            );
+            self.line(start, end, color, 0.5);
+            offset += spacing;
+        }
+    }
+
+    /// Places every shape of `other` inside this drawing, scaled by `scale`
+    /// and then moved by `offset`.
+    pub fn embed(&mut self, other: &Drawing, (dx, dy): (f64, f64), scale: f64) {
+        let place = |x: &mut f64, y: &mut f64| {
+            *x = *x * scale + dx;
+            *y = *y * scale + dy;
+        };
+        self.shapes.extend(other.shapes.iter().map(|shape| {
+            let mut shape = shape.clone();
+            match &mut shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    ..
+                } => {
+                    place(x, y);
+                    *width *= scale;
+                    *height *= scale;
+                }
+                Shape::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    width,
+                    ..
+                } => {
+                    place(x1, y1);
+                    place(x2, y2);
+                    *width *= scale;
+                }
+                Shape::Text { x, y, size, .. } => {
+                    place(x, y);
+                    *size *= scale;
+                }
+            }
+            shape
+        }));
+    }
+}
+
+/// Width of `text` set in Helvetica at `size` points.
+///
+/// Every renderer lays text out with these metrics, so labels line up the same
+/// way in each output format.
+pub fn text_width(text: &str, size: f64, bold: bool) -> f64 {
+    let widths = if bold { &HELVETICA_BOLD } else { &HELVETICA };
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => u32::from(widths[c as usize - 32]),
+            _ => 556,
+        })
+        .sum();
+    f64::from(units) * size / 1000.0
+}
+
+/// Cuts `text` short with an ellipsis so it fits in `width`.
+pub fn truncate(text: &str, size: f64, bold: bool, width: f64) -> String {
+    if text_width(text, size, bold) <= width {
+        return text.to_owned();
+    }
+    let mut fitted: String = text.to_owned();
+    while !fitted.is_empty() && text_width(&format!("{fitted}..."), size, bold) > width {
+        fitted.pop();
+    }
+    format!("{}...", fitted.trim_end())
+}
+
+/// Advance widths of the printable ASCII characters, from the Adobe
+/// Helvetica font metrics, in thousandths of an em.
+#[rustfmt::skip]
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[rustfmt::skip]
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_colors_as_hex() {
+        assert_eq!(Color(0x8e, 0xc5, 0x0f).hex(), "#8ec50f");
+    }
+
+    #[test]
+    fn measures_text_with_helvetica_metrics() {
+        assert_eq!(text_width("Ab", 10.0, false), 12.23);
+        assert_eq!(text_width("Ab", 10.0, true), 13.33);
+        assert_eq!(text_width("é", 10.0, false), 5.56);
+    }
+
+    #[test]
+    fn truncates_only_text_that_does_not_fit() {
+        assert_eq!(truncate("web-01", 7.0, false, 100.0), "web-01");
+        let name = "a very long device name that cannot fit";
+        let cut = truncate(name, 7.0, false, 60.0);
+        assert!(cut.ends_with("...") && !cut.ends_with(" ..."));
+        assert!(text_width(&cut, 7.0, false) <= 60.0);
+        assert_eq!(truncate(name, 7.0, false, 1.0), "...");
+    }
+
+    #[test]
+    fn hatches_within_the_bounds() {
+        let mut drawing = Drawing::new(100.0, 100.0);
+        drawing.hatch((10.0, 20.0, 30.0, 10.0), 4.0, Color::GRAY);
+        assert_eq!(drawing.shapes.len(), 9);
+        for shape in &drawing.shapes {
+            let Shape::Line { x1, y1, x2, y2, .. } = *shape else {
+                panic!("expected a line, got {shape:?}");
+            };
+            for (x, y) in [(x1, y1), (x2, y2)] {
+                assert!((10.0..=40.0).contains(&x), "{x}");
+                assert!((20.0..=30.0).contains(&y), "{y}");
+            }
+            assert_eq!(x2 - x1, y2 - y1);
+        }
+    }
+
+    #[test]
+    fn embeds_scaled_and_offset() {
+        let mut inner = Drawing::new(10.0, 10.0);
+        inner.rect((1.0, 2.0, 3.0, 4.0), None, Some(Color::BLACK));
+        inner.text((5.0, 5.0), 8.0, Anchor::Start, "x");
+        let mut outer = Drawing::new(100.0, 100.0);
+        outer.embed(&inner, (10.0, 20.0), 2.0);
+        assert_eq!(
+            outer.shapes[0],
+            Shape::Rect {
+                x: 12.0,
+                y: 24.0,
+                width: 6.0,
+                height: 8.0,
+                fill: None,
+                stroke: Some(Color::BLACK),
+            }
+        );
+        assert!(matches!(
+            outer.shapes[1],
+            Shape::Text {
+                x: 20.0,
+                y: 30.0,
+                size: 16.0,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+//! Front and rear elevation drawings of a rack.
+
+use super::drawing::{truncate, Anchor, Color, Drawing};
+use crate::model::{Category, Face, Rack};
+
+/// Height of one rack unit: 1.75 in.
+pub const UNIT: f64 = 1.75 * 72.0;
+/// Scale applied to real dimensions so a 42U rack fits on a page.
+const SCALE: f64 = 0.18;
+const U: f64 = UNIT * SCALE;
+/// Width of the 19 in mounting area.
+const INNER_WIDTH: f64 = 17.75 * 72.0 * SCALE;
+const RAIL_WIDTH: f64 = 18.0;
+const MARGIN: f64 = 12.0;
+const TITLE_HEIGHT: f64 = 24.0;
+const GAP: f64 = 24.0;
+
+pub fn face_label(face: Face) -> &'static str {
+    match face {
+        Face::Front => "Front",
+        Face::Rear => "Rear",
+    }
+}
+
+/// Draws `rack` as seen from each of `faces`, side by side.
+pub fn render(rack: &Rack, faces: &[Face]) -> Drawing {
+    let panel_width = INNER_WIDTH + 2.0 * RAIL_WIDTH;
+    let panel_height = TITLE_HEIGHT + f64::from(rack.height_u) * U;
+    let count = faces.len().max(1) as f64;
+    let mut drawing = Drawing::new(
+        2.0 * MARGIN + count * panel_width + (count - 1.0) * GAP,
+        2.0 * MARGIN + panel_height,
+    );
+    for (i, &face) in faces.iter().enumerate() {
+        let x = MARGIN + i as f64 * (panel_width + GAP);
+        drawing.embed(&render_face(rack, face), (x, MARGIN), 1.0);
+    }
+    drawing
+}
+
+/// Draws one face of `rack` with its top left corner at the origin.
+pub fn render_face(rack: &Rack, face: Face) -> Drawing {
+    let width = INNER_WIDTH + 2.0 * RAIL_WIDTH;
+    let body = f64::from(rack.height_u) * U;
+    let mut drawing = Drawing::new(width, TITLE_HEIGHT + body);
+    drawing.heading(
+        (0.0, TITLE_HEIGHT / 2.0),
+        11.0,
+        format!("{} ({})", rack.name, face_label(face)),
+    );
+
+    let top = TITLE_HEIGHT;
+    drawing.rect((0.0, top, width, body), Some(Color::DARK_GRAY), None);
+    drawing.rect(
+        (RAIL_WIDTH, top, INNER_WIDTH, body),
+        Some(Color::WHITE),
+        Some(Color::BLACK),
+    );
+    for unit in 1..=rack.height_u {
+        let y = top + f64::from(rack.height_u - unit) * U;
+        for x in [RAIL_WIDTH / 2.0, width - RAIL_WIDTH / 2.0] {
+            drawing.styled_text(
+                (x, y + U / 2.0),
+                6.0,
+                Anchor::Middle,
+                false,
+                Color::WHITE,
+                unit.to_string(),
+            );
+        }
+        if unit > 1 {
+            drawing.line(
+                (RAIL_WIDTH, y + U),
+                (RAIL_WIDTH + INNER_WIDTH, y + U),
+                Color::LIGHT_GRAY,
+                0.5,
+            );
+        }
+    }
+
+    for device in rack.devices_on(face) {
+        let units = device.units();
+        let visible_top = units.top.min(rack.height_u);
+        if units.bottom > visible_top {
+            continue;
+        }
+        let y = top + f64::from(rack.height_u - visible_top) * U;
+        let height = f64::from(visible_top - units.bottom + 1) * U;
+        let bounds = (RAIL_WIDTH, y, INNER_WIDTH, height);
+        drawing.rect(bounds, Some(Color::for_category(device.category)), None);
+        if device.category == Category::BlankingPanel {
+            drawing.hatch(bounds, 4.0, Color::GRAY);
+        }
+        drawing.rect(bounds, None, Some(Color::BLACK));
+        drawing.text(
+            (RAIL_WIDTH + INNER_WIDTH / 2.0, y + height / 2.0),
+            7.0,
+            Anchor::Middle,
+            truncate(&device.name, 7.0, false, INNER_WIDTH - 8.0),
+        );
+    }
+    drawing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::drawing::{text_width, Shape};
+    use crate::model::Device;
+
+    fn rack() -> Rack {
+        let mut rack = Rack::new("A1", 10);
+        rack.devices
+            .push(Device::new("web-01", 2, 3, 700, Face::Front));
+        rack.devices
+            .push(Device::new("patch-01", 1, 10, 100, Face::Rear));
+        rack
+    }
+
+    fn texts(drawing: &Drawing) -> Vec<(f64, f64, &str)> {
+        drawing
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text { x, y, text, .. } => Some((*x, *y, text.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn hatch_lines(drawing: &Drawing) -> usize {
+        drawing
+            .shapes
+            .iter()
+            .filter(|shape| {
+                matches!(
+                    shape,
+                    Shape::Line {
+                        color: Color::GRAY,
+                        ..
+                    }
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn numbers_units_from_the_bottom() {
+        let drawing = render_face(&rack(), Face::Front);
+        let labels = texts(&drawing);
+        let unit = |n: &str| labels.iter().find(|(_, _, t)| *t == n).unwrap().1;
+        assert_eq!(unit("1"), TITLE_HEIGHT + 9.5 * U);
+        assert_eq!(unit("10"), TITLE_HEIGHT + 0.5 * U);
+        // Each unit is labelled on both rails.
+        assert_eq!(labels.iter().filter(|(_, _, t)| *t == "10").count(), 2);
+        assert!(!labels.iter().any(|(_, _, t)| *t == "11"));
+    }
+
+    #[test]
+    fn places_devices_at_their_units() {
+        let drawing = render_face(&rack(), Face::Front);
+        // web-01 spans U3 and U4, so its top edge is six units down.
+        assert!(drawing.shapes.contains(&Shape::Rect {
+            x: RAIL_WIDTH,
+            y: TITLE_HEIGHT + 6.0 * U,
+            width: INNER_WIDTH,
+            height: 2.0 * U,
+            fill: Some(Color::for_category(Category::Other)),
+            stroke: None,
+        }));
+        let (_, y, _) = texts(&drawing)
+            .into_iter()
+            .find(|(_, _, t)| *t == "web-01")
+            .unwrap();
+        assert_eq!(y, TITLE_HEIGHT + 7.0 * U);
+    }
+
+    #[test]
+    fn draws_each_face_with_its_own_devices() {
+        let rack = rack();
+        let front = texts(&render_face(&rack, Face::Front))
+            .into_iter()
+            .map(|(_, _, t)| t.to_owned())
+            .collect::<Vec<_>>();
+        assert!(front.contains(&String::from("A1 (Front)")));
+        assert!(front.contains(&String::from("web-01")));
+        assert!(!front.contains(&String::from("patch-01")));
+
+        let both = render(&rack, &[Face::Front, Face::Rear]);
+        let panel = INNER_WIDTH + 2.0 * RAIL_WIDTH;
+        assert_eq!(both.width, 2.0 * MARGIN + 2.0 * panel + GAP);
+        let labels = texts(&both);
+        let heading = |name: &str| labels.iter().find(|(_, _, t)| *t == name).unwrap().0;
+        assert_eq!(heading("A1 (Front)"), MARGIN);
+        assert_eq!(heading("A1 (Rear)"), MARGIN + panel + GAP);
+        assert!(heading("patch-01") > MARGIN + panel + GAP);
+    }
+
+    #[test]
+    fn hatches_blanking_panels_only() {
+        let mut rack = rack();
+        assert_eq!(hatch_lines(&render_face(&rack, Face::Front)), 0);
+        let mut blank = Device::new("blank", 1, 1, 10, Face::Front);
+        blank.category = Category::BlankingPanel;
+        rack.devices.push(blank);
+        assert!(hatch_lines(&render_face(&rack, Face::Front)) > 0);
+    }
+
+    #[test]
+    fn clips_devices_above_the_top_of_the_rack() {
+        let mut rack = Rack::new("A1", 10);
+        rack.devices
+            .push(Device::new("tall", 4, 9, 700, Face::Front));
+        rack.devices
+            .push(Device::new("outside", 1, 12, 700, Face::Front));
+        let drawing = render_face(&rack, Face::Front);
+        let labels = texts(&drawing);
+        assert!(labels.iter().any(|(_, _, t)| *t == "tall"));
+        assert!(!labels.iter().any(|(_, _, t)| *t == "outside"));
+        assert!(drawing.shapes.contains(&Shape::Rect {
+            x: RAIL_WIDTH,
+            y: TITLE_HEIGHT,
+            width: INNER_WIDTH,
+            height: 2.0 * U,
+            fill: Some(Color::for_category(Category::Other)),
+            stroke: None,
+        }));
+    }
+
+    #[test]
+    fn truncates_long_device_names() {
+        let mut rack = Rack::new("A1", 10);
+        let name = "an extremely long hostname for a database server in the lab.example.com";
+        rack.devices.push(Device::new(name, 1, 1, 700, Face::Front));
+        let drawing = render_face(&rack, Face::Front);
+        let label = texts(&drawing)
+            .into_iter()
+            .find(|(_, _, t)| t.starts_with("an extremely"))
+            .unwrap()
+            .2;
+        assert!(label.ends_with("..."));
+        assert!(text_width(label, 7.0, false) <= INNER_WIDTH - 8.0);
+    }
+}
@@ -0,0 +1,13 @@
+//! Printable output generated from the project model.
+
+pub mod csv;
+pub mod drawing;
+pub mod elevation;
+pub mod netbox;
+pub mod pdf;
+pub mod png;
+pub mod report;
+pub mod svg;
+pub mod table;
+pub mod workbook;
+pub mod xlsx;
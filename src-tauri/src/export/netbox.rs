@@ -0,0 +1,166 @@
+//! NetBox-compatible JSON for the racks and devices in a project.
+//!
+//! Payloads are shaped for the REST API's bulk endpoints, so they can be
+//! posted as they are. Objects linked to NetBox by an earlier import carry
+//! their id and update it; anything else is created. Related objects are
+//! referenced by id where the project knows it, and otherwise by attributes
+//! NetBox resolves on write: a site slug, a rack name, or a device type's
+//! manufacturer and model. Device roles are looked up by a slug derived from
+//! the device category, so those roles must exist in NetBox.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::import::netbox::{netbox_position, Dump};
+use crate::model::{Category, Device, Face, Layout, Rack};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetboxOptions {
+    /// Slug of the site new racks and devices are created in.
+    pub site: String,
+    /// Status given to racks and devices created in NetBox.
+    #[serde(default = "planned")]
+    pub status: String,
+}
+
+fn planned() -> String {
+    String::from("planned")
+}
+
+/// Request bodies for `dcim/racks` and `dcim/devices`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Payloads {
+    #[serde(rename = "dcim/racks")]
+    pub racks: Vec<Value>,
+    #[serde(rename = "dcim/devices")]
+    pub devices: Vec<Value>,
+}
+
+/// Payloads for every rack and device, with the names of devices that could
+/// not be exported because they have no model to match a device type by.
+pub fn payloads(
+    layout: &Layout,
+    snapshot: Option<&Dump>,
+    options: &NetboxOptions,
+) -> (Payloads, Vec<String>) {
+    let mut payloads = Payloads::default();
+    let mut unexported = Vec::new();
+    for rack in &layout.racks {
+        payloads.racks.push(rack_payload(rack, options));
+        for device in &rack.devices {
+            match device_payload(rack, device, snapshot, options) {
+                Some(payload) => payloads.devices.push(payload),
+                None => unexported.push(device.name.clone()),
+            }
+        }
+    }
+    (payloads, unexported)
+}
+
+pub fn rack_payload(rack: &Rack, options: &NetboxOptions) -> Value {
+    let mut payload = Map::new();
+    match rack.netbox_id {
+        Some(id) => {
+            payload.insert("id".into(), json!(id));
+        }
+        None => {
+            payload.insert("site".into(), json!({ "slug": options.site }));
+            payload.insert("status".into(), json!(options.status));
+        }
+    }
+    payload.insert("name".into(), json!(rack.name));
+    payload.insert("u_height".into(), json!(rack.height_u));
+    payload.insert("outer_depth".into(), json!(rack.depth_mm));
+    payload.insert("outer_unit".into(), json!("mm"));
+    if let Some(weight) = rack.weight_kg {
+        payload.insert("weight".into(), json!(weight));
+    }
+    if let Some(rating) = rack.load_rating_kg {
+        payload.insert("max_weight".into(), json!(rating.round() as u64));
+    }
+    if rack.weight_kg.is_some() || rack.load_rating_kg.is_some() {
+        payload.insert("weight_unit".into(), json!("kg"));
+    }
+    Value::Object(payload)
+}
+
+/// The payload for one device, or `None` if it has neither a NetBox device
+/// type nor a model to look one up by.
+pub fn device_payload(
+    rack: &Rack,
+    device: &Device,
+    snapshot: Option<&Dump>,
+    options: &NetboxOptions,
+) -> Option<Value> {
+    let known = snapshot
+        .zip(device.netbox_id)
+        .and_then(|(dump, id)| dump.device(id));
+    let device_type = match (known, &device.model) {
+        (Some(known), _) => json!(known.device_type.id),
+        (None, Some(model)) => match &device.manufacturer {
+            Some(manufacturer) => {
+                json!({ "manufacturer": { "name": manufacturer }, "model": model })
+            }
+            None => json!({ "model": model }),
+        },
+        (None, None) => return None,
+    };
+    let role = match known.and_then(|d| d.role()) {
+        Some(role) => json!(role.id),
+        None => json!({ "slug": role_slug(device.category) }),
+    };
+    let netbox_rack = snapshot
+        .zip(rack.netbox_id)
+        .and_then(|(dump, id)| dump.rack(id));
+    let position = netbox_position(netbox_rack, device.position_u, device.height_u);
+
+    let mut payload = Map::new();
+    match device.netbox_id {
+        Some(id) => {
+            payload.insert("id".into(), json!(id));
+        }
+        None => {
+            payload.insert("site".into(), json!({ "slug": options.site }));
+            payload.insert("status".into(), json!(options.status));
+        }
+    }
+    payload.insert("name".into(), json!(device.name));
+    payload.insert("device_type".into(), device_type);
+    payload.insert("role".into(), role);
+    payload.insert(
+        "rack".into(),
+        match rack.netbox_id {
+            Some(id) => json!(id),
+            None => json!({ "name": rack.name, "site": { "slug": options.site } }),
+        },
+    );
+    payload.insert("position".into(), json!(position));
+    payload.insert("face".into(), json!(face_value(device.face)));
+    payload.insert(
+        "serial".into(),
+        json!(device.serial.as_deref().unwrap_or_default()),
+    );
+    Some(Value::Object(payload))
+}
+
+fn face_value(face: Face) -> &'static str {
+    match face {
+        Face::Front => "front",
+        Face::Rear => "rear",
+    }
+}
+
+/// Slug of the device role a category is exported as.
+pub fn role_slug(category: Category) -> &'static str {
+    match category {
+        Category::Server => "server",
+        Category::Switch => "switch",
+        Category::PatchPanel => "patch-panel",
+        Category::Pdu => "pdu",
+        Category::Ups => "ups",
+        Category::BlankingPanel => "blanking-panel",
+        Category::Shelf => "shelf",
+        Category::Other => "other",
+    }
+}
@@ -0,0 +1,285 @@
+//! Renders drawings as pages of a PDF document.
+//!
+//! Text uses the standard Helvetica fonts every PDF reader provides, so no
+//! fonts are embedded and the files stay small.
+
+use std::fmt::Write;
+
+use super::drawing::{text_width, Anchor, Color, Drawing, Shape};
+
+/// Builds a PDF with one page per drawing, each page sized to its drawing.
+pub fn render(title: &str, pages: &[Drawing]) -> Vec<u8> {
+    let mut writer = Writer::default();
+    // Object ids are fixed up front: catalog, page tree, two fonts, info, then
+    // a page and its content stream for every drawing.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 6 + 2 * i).collect();
+    writer.object(1, "<< /Type /Catalog /Pages 2 0 R >>");
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{id} 0 R")).collect();
+    writer.object(
+        2,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    );
+    writer.object(
+        3,
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+    );
+    writer.object(
+        4,
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+    );
+    writer.object(
+        5,
+        &format!("<< /Title {} /Producer (rack-designer) >>", string(title)),
+    );
+    for (page, &id) in pages.iter().zip(&page_ids) {
+        writer.object(
+            id,
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                page.width,
+                page.height,
+                id + 1
+            ),
+        );
+        let content = content(page);
+        writer.object(
+            id + 1,
+            &format!(
+                "<< /Length {} >>\nstream\n{content}\nendstream",
+                content.len()
+            ),
+        );
+    }
+    writer.finish(1, 5)
+}
+
+/// Draws a page in the drawing's top-left coordinate space by flipping the
+/// y axis once up front.
+fn content(page: &Drawing) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "1 0 0 -1 0 {:.2} cm", page.height);
+    for shape in &page.shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let _ = write!(out, "{x:.2} {y:.2} {width:.2} {height:.2} re ");
+                match (fill, stroke) {
+                    (Some(fill), Some(stroke)) => {
+                        let _ =
+                            writeln!(out, "{} {} 0.75 w B", rgb(*fill, "rg"), rgb(*stroke, "RG"));
+                    }
+                    (Some(fill), None) => {
+                        let _ = writeln!(out, "{} f", rgb(*fill, "rg"));
+                    }
+                    (None, Some(stroke)) => {
+                        let _ = writeln!(out, "{} 0.75 w S", rgb(*stroke, "RG"));
+                    }
+                    (None, None) => out.push_str("n\n"),
+                }
+            }
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                width,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{} {width:.2} w {x1:.2} {y1:.2} m {x2:.2} {y2:.2} l S",
+                    rgb(*color, "RG"),
+                );
+            }
+            Shape::Text {
+                x,
+                y,
+                size,
+                anchor,
+                bold,
+                color,
+                text,
+            } => {
+                let width = text_width(text, *size, *bold);
+                let x = match anchor {
+                    Anchor::Start => *x,
+                    Anchor::Middle => x - width / 2.0,
+                    Anchor::End => x - width,
+                };
+                // The text matrix flips glyphs back upright.
+                let _ = writeln!(
+                    out,
+                    "BT /{} {size:.2} Tf {} 1 0 0 -1 {x:.2} {:.2} Tm {} Tj ET",
+                    if *bold { "F2" } else { "F1" },
+                    rgb(*color, "rg"),
+                    y + size / 3.0,
+                    string(text),
+                );
+            }
+        }
+    }
+    out
+}
+
+fn rgb(Color(r, g, b): Color, operator: &str) -> String {
+    let c = |v: u8| f64::from(v) / 255.0;
+    format!("{:.3} {:.3} {:.3} {operator}", c(r), c(g), c(b))
+}
+
+/// Encodes `text` as a PDF literal string in WinAnsi, replacing characters
+/// it cannot represent with `?`.
+fn string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            // The typographic punctuation WinAnsi adds over Latin-1.
+            '€' | '‘' | '’' | '“' | '”' | '•' | '–' | '—' => {
+                let code = match c {
+                    '€' => 0o200,
+                    '‘' => 0o221,
+                    '’' => 0o222,
+                    '“' => 0o223,
+                    '”' => 0o224,
+                    '•' => 0o225,
+                    '–' => 0o226,
+                    _ => 0o227,
+                };
+                let _ = write!(out, "\\{code:03o}");
+            }
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Accumulates numbered objects and writes the cross-reference table.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl Writer {
+    fn object(&mut self, id: usize, body: &str) {
+        if self.buf.is_empty() {
+            self.buf.extend_from_slice(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n");
+        }
+        self.offsets.push((id, self.buf.len()));
+        self.buf
+            .extend_from_slice(format!("{id} 0 obj\n{body}\nendobj\n").as_bytes());
+    }
+
+    fn finish(mut self, root: usize, info: usize) -> Vec<u8> {
+        self.offsets.sort_unstable();
+        let xref = self.buf.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for (_, offset) in &self.offsets {
+            let _ = writeln!(trailer, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root {root} 0 R /Info {info} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.buf.extend_from_slice(trailer.as_bytes());
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(pdf: &[u8]) -> String {
+        String::from_utf8_lossy(pdf).into_owned()
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string(r"a(b)\c"), r"(a\(b\)\\c)");
+        assert_eq!(string("café – 5€"), r"(caf\351 \226 5\200)");
+        assert_eq!(string("机架"), "(??)");
+    }
+
+    #[test]
+    fn escapes_the_title_and_text() {
+        let mut page = Drawing::new(100.0, 100.0);
+        page.text((0.0, 0.0), 10.0, Anchor::Start, "db (primary)");
+        let pdf = text(&render("Cage (4)", &[page]));
+        assert!(pdf.contains(r"/Title (Cage \(4\))"));
+        assert!(pdf.contains(r"(db \(primary\)) Tj"));
+    }
+
+    #[test]
+    fn adds_a_page_per_drawing() {
+        let pages = vec![Drawing::new(100.0, 200.0), Drawing::new(300.0, 400.0)];
+        let pdf = text(&render("Cage 4", &pages));
+        assert!(pdf.contains("/Kids [6 0 R 8 0 R] /Count 2"));
+        assert!(pdf.contains("/MediaBox [0 0 100.00 200.00]"));
+        assert!(pdf.contains("/MediaBox [0 0 300.00 400.00]"));
+        assert_eq!(pdf.matches("/Type /Page ").count(), 2);
+    }
+
+    #[test]
+    fn points_the_xref_table_at_each_object() {
+        let mut page = Drawing::new(100.0, 100.0);
+        page.text((0.0, 0.0), 10.0, Anchor::Start, "x");
+        let pdf = render("Cage 4", &[page.clone(), page]);
+        let text = text(&pdf);
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref\n0 10\n"));
+        // The binary comment in the header means offsets must be counted in
+        // bytes, not characters.
+        let table = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        let entries = table.lines().skip(3).take(9);
+        for (id, entry) in (1..).zip(entries) {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                pdf[offset..].starts_with(format!("{id} 0 obj\n").as_bytes()),
+                "object {id}"
+            );
+        }
+    }
+
+    #[test]
+    fn measures_stream_lengths_in_bytes() {
+        let mut page = Drawing::new(100.0, 100.0);
+        page.text((0.0, 0.0), 10.0, Anchor::Start, "x");
+        let pdf = text(&render("Cage 4", &[page]));
+        let (head, rest) = pdf.split_once("stream\n").unwrap();
+        let length: usize = head
+            .rsplit("/Length ")
+            .next()
+            .and_then(|tail| tail.split_whitespace().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(&rest[length..length + 10], "\nendstream");
+    }
+}
@@ -0,0 +1,283 @@
+//! Rasterizes drawings to PNG without a webview.
+//!
+//! Shapes are filled by tiny-skia and text is set in DejaVu Sans Condensed,
+//! which is embedded in the binary so output looks the same everywhere.
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use tiny_skia::{Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+use super::drawing::{Anchor, Color, Drawing, Shape};
+use crate::error::{Error, Result};
+
+/// Largest width or height accepted, to keep a typo in the DPI from
+/// allocating gigabytes.
+const MAX_SIDE: u32 = 16_384;
+
+static REGULAR: &[u8] = include_bytes!("../../fonts/DejaVuSansCondensed.ttf");
+static BOLD: &[u8] = include_bytes!("../../fonts/DejaVuSansCondensed-Bold.ttf");
+
+/// Renders `drawing` at `dpi` pixels per inch, enlarged by `scale`.
+pub fn render(drawing: &Drawing, dpi: f64, scale: f64) -> Result<Vec<u8>> {
+    let px_per_pt = (dpi / 72.0 * scale) as f32;
+    if !px_per_pt.is_finite() || px_per_pt <= 0.0 {
+        return Err(Error::InvalidResolution { dpi, scale });
+    }
+    let width = (drawing.width as f32 * px_per_pt).ceil() as u32;
+    let height = (drawing.height as f32 * px_per_pt).ceil() as u32;
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(Error::ImageTooLarge { width, height });
+    }
+    let mut pixmap =
+        Pixmap::new(width.max(1), height.max(1)).ok_or(Error::ImageTooLarge { width, height })?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+
+    let regular = FontRef::try_from_slice(REGULAR).expect("embedded font is valid");
+    let bold = FontRef::try_from_slice(BOLD).expect("embedded font is valid");
+    let transform = Transform::from_scale(px_per_pt, px_per_pt);
+    for shape in &drawing.shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let Some(rect) =
+                    Rect::from_xywh(*x as f32, *y as f32, *width as f32, *height as f32)
+                else {
+                    continue;
+                };
+                if let Some(fill) = fill {
+                    pixmap.fill_rect(rect, &paint(*fill), transform, None);
+                }
+                if let Some(stroke) = stroke {
+                    let path = PathBuilder::from_rect(rect);
+                    pixmap.stroke_path(&path, &paint(*stroke), &line(0.75), transform, None);
+                }
+            }
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                width,
+            } => {
+                let mut path = PathBuilder::new();
+                path.move_to(*x1 as f32, *y1 as f32);
+                path.line_to(*x2 as f32, *y2 as f32);
+                if let Some(path) = path.finish() {
+                    pixmap.stroke_path(
+                        &path,
+                        &paint(*color),
+                        &line(*width as f32),
+                        transform,
+                        None,
+                    );
+                }
+            }
+            Shape::Text {
+                x,
+                y,
+                size,
+                anchor,
+                bold: is_bold,
+                color,
+                text,
+            } => {
+                let font = if *is_bold { &bold } else { &regular };
+                let origin = (*x as f32 * px_per_pt, (*y + size / 3.0) as f32 * px_per_pt);
+                let em = *size as f32 * px_per_pt;
+                draw_text(&mut pixmap, font, em, origin, *anchor, *color, text);
+            }
+        }
+    }
+
+    encode(&pixmap, dpi)
+}
+
+fn paint(Color(r, g, b): Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn line(width: f32) -> Stroke {
+    Stroke {
+        width,
+        ..Stroke::default()
+    }
+}
+
+/// Sets a line of text with its baseline at `origin`, blending glyph coverage
+/// straight into the pixmap.
+fn draw_text(
+    pixmap: &mut Pixmap,
+    font: &FontRef<'_>,
+    em: f32,
+    (x, baseline): (f32, f32),
+    anchor: Anchor,
+    Color(r, g, b): Color,
+    text: &str,
+) {
+    let units_per_em = font.units_per_em().unwrap_or(2048.0);
+    let scale = PxScale::from(em * font.height_unscaled() / units_per_em);
+    let scaled = font.as_scaled(scale);
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, point(caret, 0.0)));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    let left = match anchor {
+        Anchor::Start => x,
+        Anchor::Middle => x - caret / 2.0,
+        Anchor::End => x - caret,
+    };
+
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let pixels = pixmap.pixels_mut();
+    for mut glyph in glyphs {
+        glyph.position.x += left;
+        glyph.position.y += baseline;
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+            if px < 0 || py < 0 || px >= width || py >= height {
+                return;
+            }
+            let pixel = &mut pixels[(py * width + px) as usize];
+            let blend = |under: u8, over: u8| {
+                let under = f32::from(under);
+                (under + (f32::from(over) - under) * coverage.min(1.0)).round() as u8
+            };
+            // The canvas is opaque, so premultiplied and straight colors are
+            // the same.
+            if let Some(blended) = tiny_skia::PremultipliedColorU8::from_rgba(
+                blend(pixel.red(), r),
+                blend(pixel.green(), g),
+                blend(pixel.blue(), b),
+                255,
+            ) {
+                *pixel = blended;
+            }
+        });
+    }
+}
+
+/// Encodes an opaque pixmap, recording `dpi` so images print at the
+/// intended size.
+fn encode(pixmap: &Pixmap, dpi: f64) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, pixmap.width(), pixmap.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let pixels_per_meter = (dpi / 0.0254).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_meter,
+        yppu: pixels_per_meter,
+        unit: png::Unit::Meter,
+    }));
+    let encoded = encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixmap.data()));
+    encoded.map_err(|e| Error::Encode(e.to_string()))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(bytes: &[u8]) -> (u32, u32, u32) {
+        let reader = png::Decoder::new(bytes).read_info().unwrap();
+        let info = reader.info();
+        let dims = info.pixel_dims.unwrap();
+        assert_eq!(dims.unit, png::Unit::Meter);
+        (info.width, info.height, dims.xppu)
+    }
+
+    fn drawing() -> Drawing {
+        let mut drawing = Drawing::new(144.0, 72.0);
+        drawing.rect(
+            (0.0, 0.0, 144.0, 72.0),
+            Some(Color::WHITE),
+            Some(Color::BLACK),
+        );
+        drawing.line((0.0, 0.0), (144.0, 72.0), Color::GRAY, 1.0);
+        drawing.text((72.0, 36.0), 10.0, Anchor::Middle, "web-01");
+        drawing
+    }
+
+    #[test]
+    fn sizes_the_image_by_dpi_and_scale() {
+        let drawing = drawing();
+        assert_eq!(info(&render(&drawing, 72.0, 1.0).unwrap()), (144, 72, 2835));
+        assert_eq!(
+            info(&render(&drawing, 150.0, 1.0).unwrap()),
+            (300, 150, 5906)
+        );
+        assert_eq!(
+            info(&render(&drawing, 300.0, 1.0).unwrap()),
+            (600, 300, 11811)
+        );
+        assert_eq!(info(&render(&drawing, 72.0, 2.5).unwrap()).0, 360);
+    }
+
+    #[test]
+    fn rounds_partial_pixels_up() {
+        let drawing = Drawing::new(10.1, 0.0);
+        let (width, height, _) = info(&render(&drawing, 72.0, 1.0).unwrap());
+        assert_eq!((width, height), (11, 1));
+    }
+
+    #[test]
+    fn rejects_invalid_resolutions() {
+        let drawing = drawing();
+        for (dpi, scale) in [
+            (0.0, 1.0),
+            (-96.0, 1.0),
+            (f64::NAN, 1.0),
+            (f64::INFINITY, 1.0),
+            (1e300, 1.0),
+            (96.0, 0.0),
+        ] {
+            assert!(
+                matches!(
+                    render(&drawing, dpi, scale),
+                    Err(Error::InvalidResolution { .. })
+                ),
+                "{dpi} dpi at {scale}x"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_images_that_are_too_large() {
+        let drawing = drawing();
+        // 144 pt at 8192 dpi is exactly the limit.
+        assert!(render(&Drawing::new(144.0, 1.0), 8192.0, 1.0).is_ok());
+        assert!(matches!(
+            render(&drawing, 8200.0, 1.0),
+            Err(Error::ImageTooLarge { width: 16_400, .. })
+        ));
+        assert!(matches!(
+            render(&Drawing::new(1.0, 1000.0), 96.0, 20.0),
+            Err(Error::ImageTooLarge { height: 26_667, .. })
+        ));
+    }
+}
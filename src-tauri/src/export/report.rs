@@ -0,0 +1,390 @@
+//! The multi-page rack documentation package.
+//!
+//! Pages are laid out as [`Drawing`]s so the package can be rendered to any
+//! output format; [`super::pdf`] is the one it is normally printed with.
+
+use std::time::SystemTime;
+
+use super::drawing::{truncate, Anchor, Color, Drawing};
+use super::elevation;
+use crate::model::{Face, Rack};
+use crate::project::Project;
+
+/// A4 portrait, in points.
+const PAGE_WIDTH: f64 = 595.28;
+const PAGE_HEIGHT: f64 = 841.89;
+const MARGIN: f64 = 48.0;
+const ROW_HEIGHT: f64 = 16.0;
+const TEXT_SIZE: f64 = 8.5;
+
+/// Lays out the whole package: a cover sheet, then for each rack its
+/// elevations followed by its inventory and power summary.
+pub fn pages(project: &Project) -> Vec<Drawing> {
+    let mut pages = vec![cover(project)];
+    for rack in &project.layout.racks {
+        pages.push(elevations(rack));
+        pages.extend(inventory(rack));
+    }
+    pages
+}
+
+fn cover(project: &Project) -> Drawing {
+    let mut page = Drawing::new(PAGE_WIDTH, PAGE_HEIGHT);
+    let metadata = &project.metadata;
+    let mut y = MARGIN + 24.0;
+    page.heading((MARGIN, y), 24.0, metadata.name.clone());
+    y += 30.0;
+    page.text(
+        (MARGIN, y),
+        10.0,
+        Anchor::Start,
+        format!("Generated {}", today()),
+    );
+    if !metadata.author.is_empty() {
+        y += 14.0;
+        page.text(
+            (MARGIN, y),
+            10.0,
+            Anchor::Start,
+            format!("Author: {}", metadata.author),
+        );
+    }
+    for line in metadata.description.lines() {
+        y += 14.0;
+        page.text((MARGIN, y), 10.0, Anchor::Start, line);
+    }
+
+    y += 36.0;
+    page.heading((MARGIN, y), 14.0, "Racks");
+    y += 16.0;
+    let rows: Vec<Vec<String>> = project
+        .layout
+        .racks
+        .iter()
+        .map(|rack| {
+            let power = PowerTotals::of(rack);
+            vec![
+                rack.name.clone(),
+                format!("{}U", rack.height_u),
+                format!("{}U", used_units(rack)),
+                rack.devices.len().to_string(),
+                format!("{} W", power.typical_w),
+                format!("{} W", power.nameplate_w),
+            ]
+        })
+        .collect();
+    table(&mut page, y, RACK_COLUMNS, &rows);
+    page
+}
+
+fn elevations(rack: &Rack) -> Drawing {
+    let mut page = Drawing::new(PAGE_WIDTH, PAGE_HEIGHT);
+    page.heading((MARGIN, MARGIN), 16.0, format!("{} elevations", rack.name));
+    let drawing = elevation::render(rack, &[Face::Front, Face::Rear]);
+    let top = MARGIN + 20.0;
+    let scale = ((PAGE_WIDTH - 2.0 * MARGIN) / drawing.width)
+        .min((PAGE_HEIGHT - MARGIN - top) / drawing.height)
+        .min(1.5);
+    let left = (PAGE_WIDTH - drawing.width * scale) / 2.0;
+    page.embed(&drawing, (left, top), scale);
+    page
+}
+
+/// The device table for a rack, continued over as many pages as it needs,
+/// with the power summary after it.
+fn inventory(rack: &Rack) -> Vec<Drawing> {
+    let mut devices: Vec<_> = rack.devices.iter().collect();
+    devices.sort_by_key(|d| (std::cmp::Reverse(d.position_u), d.face == Face::Rear));
+    let rows: Vec<Vec<String>> = devices
+        .iter()
+        .map(|d| {
+            let units = d.units();
+            let power = d.power.as_ref();
+            vec![
+                if units.bottom == units.top {
+                    units.bottom.to_string()
+                } else {
+                    format!("{}-{}", units.bottom, units.top)
+                },
+                elevation::face_label(d.face).to_owned(),
+                d.name.clone(),
+                d.category.label().to_owned(),
+                d.template_id.clone().unwrap_or_default(),
+                power.map_or_else(String::new, |p| format!("{} W", p.typical_w)),
+                power.map_or_else(String::new, |p| format!("{} W", p.nameplate_w)),
+            ]
+        })
+        .collect();
+
+    let first_top = MARGIN + 20.0;
+    let per_page = ((PAGE_HEIGHT - 2.0 * MARGIN - 20.0) / ROW_HEIGHT) as usize - 1;
+    let mut pages = Vec::new();
+    let mut chunks = rows.chunks(per_page.max(1)).peekable();
+    let mut y;
+    loop {
+        let mut page = Drawing::new(PAGE_WIDTH, PAGE_HEIGHT);
+        let suffix = if pages.is_empty() { "" } else { " (continued)" };
+        page.heading(
+            (MARGIN, MARGIN),
+            16.0,
+            format!("{} inventory{suffix}", rack.name),
+        );
+        let chunk = chunks.next().unwrap_or(&[]);
+        y = table(&mut page, first_top, DEVICE_COLUMNS, chunk);
+        pages.push(page);
+        if chunks.peek().is_none() {
+            break;
+        }
+    }
+
+    // Start a fresh page for the summary if it does not fit under the table.
+    let summary_height = 4.0 * ROW_HEIGHT;
+    if y + summary_height > PAGE_HEIGHT - MARGIN {
+        let mut page = Drawing::new(PAGE_WIDTH, PAGE_HEIGHT);
+        page.heading(
+            (MARGIN, MARGIN),
+            16.0,
+            format!("{} inventory (continued)", rack.name),
+        );
+        pages.push(page);
+        y = first_top;
+    }
+    let page = pages.last_mut().unwrap();
+    power_summary(page, y + ROW_HEIGHT, rack);
+    pages
+}
+
+fn power_summary(page: &mut Drawing, top: f64, rack: &Rack) {
+    let power = PowerTotals::of(rack);
+    page.heading((MARGIN, top), 12.0, "Power");
+    let mut y = top + ROW_HEIGHT;
+    page.text(
+        (MARGIN, y),
+        TEXT_SIZE,
+        Anchor::Start,
+        format!("Typical draw: {} W", power.typical_w),
+    );
+    y += ROW_HEIGHT * 0.8;
+    page.text(
+        (MARGIN, y),
+        TEXT_SIZE,
+        Anchor::Start,
+        format!("Nameplate total: {} W", power.nameplate_w),
+    );
+    if power.unknown > 0 {
+        y += ROW_HEIGHT * 0.8;
+        page.text(
+            (MARGIN, y),
+            TEXT_SIZE,
+            Anchor::Start,
+            format!(
+                "{} devices have no power data and are not included",
+                power.unknown
+            ),
+        );
+    }
+}
+
+struct Column {
+    title: &'static str,
+    width: f64,
+    anchor: Anchor,
+}
+
+const fn column(title: &'static str, width: f64, anchor: Anchor) -> Column {
+    Column {
+        title,
+        width,
+        anchor,
+    }
+}
+
+const RACK_COLUMNS: &[Column] = &[
+    column("Rack", 170.0, Anchor::Start),
+    column("Height", 50.0, Anchor::End),
+    column("Used", 50.0, Anchor::End),
+    column("Devices", 50.0, Anchor::End),
+    column("Typical", 90.0, Anchor::End),
+    column("Nameplate", 89.28, Anchor::End),
+];
+
+const DEVICE_COLUMNS: &[Column] = &[
+    column("U", 40.0, Anchor::Start),
+    column("Face", 36.0, Anchor::Start),
+    column("Name", 140.0, Anchor::Start),
+    column("Category", 70.0, Anchor::Start),
+    column("Template", 107.28, Anchor::Start),
+    column("Typical", 53.0, Anchor::End),
+    column("Nameplate", 53.0, Anchor::End),
+];
+
+/// Draws a table with a header row starting at `top`, returning the y
+/// coordinate just below it.
+fn table(page: &mut Drawing, top: f64, columns: &[Column], rows: &[Vec<String>]) -> f64 {
+    let width: f64 = columns.iter().map(|c| c.width).sum();
+    page.rect(
+        (MARGIN, top, width, ROW_HEIGHT),
+        Some(Color::LIGHT_GRAY),
+        None,
+    );
+    let mut y = top;
+    let header: Vec<String> = columns.iter().map(|c| c.title.to_owned()).collect();
+    for (i, row) in std::iter::once(&header).chain(rows).enumerate() {
+        let mut x = MARGIN;
+        for (column, cell) in columns.iter().zip(row) {
+            let text_x = match column.anchor {
+                Anchor::Start => x + 3.0,
+                Anchor::Middle => x + column.width / 2.0,
+                Anchor::End => x + column.width - 3.0,
+            };
+            let text = truncate(cell, TEXT_SIZE, i == 0, column.width - 6.0);
+            let at = (text_x, y + ROW_HEIGHT / 2.0);
+            page.styled_text(at, TEXT_SIZE, column.anchor, i == 0, Color::BLACK, text);
+            x += column.width;
+        }
+        y += ROW_HEIGHT;
+        page.line((MARGIN, y), (MARGIN + width, y), Color::GRAY, 0.5);
+    }
+    y
+}
+
+/// Summed in `u64` since a rack of large devices can pass `u32::MAX` watts,
+/// and a hand-edited project could put anything in there.
+#[derive(Default)]
+pub(super) struct PowerTotals {
+    pub typical_w: u64,
+    pub nameplate_w: u64,
+    /// Devices without power data, excluding gear that draws none itself, like
+    /// blanking panels and PDUs.
+    pub unknown: usize,
+}
+
+impl PowerTotals {
+    pub fn of(rack: &Rack) -> Self {
+        let mut totals = Self::default();
+        for device in &rack.devices {
+            match &device.power {
+                Some(power) => {
+                    totals.typical_w += u64::from(power.typical_w);
+                    totals.nameplate_w += u64::from(power.nameplate_w);
+                }
+                None if !device.category.is_passive() => {
+                    totals.unknown += 1;
+                }
+                None => {}
+            }
+        }
+        totals
+    }
+}
+
+pub(super) fn used_units(rack: &Rack) -> u32 {
+    (1..=rack.height_u)
+        .filter(|&u| rack.devices.iter().any(|d| d.units().contains(u)))
+        .count() as u32
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+pub(super) fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Civil-from-days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::drawing::Shape;
+    use crate::model::{Category, Device, PowerDraw};
+
+    fn headings(page: &Drawing) -> Vec<&str> {
+        page.shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text {
+                    bold: true, text, ..
+                } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn rack(name: &str, devices: u32) -> Rack {
+        let mut rack = Rack::new(name, 42);
+        for i in 0..devices {
+            rack.devices.push(Device::new(
+                format!("srv-{i}"),
+                1,
+                i % 42 + 1,
+                700,
+                Face::Front,
+            ));
+        }
+        rack
+    }
+
+    #[test]
+    fn gives_each_rack_elevations_and_an_inventory() {
+        let mut project = Project::new("Cage 4");
+        project.layout.racks = vec![rack("A1", 3), rack("A2", 0)];
+        let pages = pages(&project);
+        assert_eq!(pages.len(), 5);
+        assert_eq!(headings(&pages[0])[0], "Cage 4");
+        assert_eq!(headings(&pages[1])[0], "A1 elevations");
+        assert_eq!(headings(&pages[2])[0], "A1 inventory");
+        assert_eq!(headings(&pages[3])[0], "A2 elevations");
+        assert_eq!(headings(&pages[4])[0], "A2 inventory");
+    }
+
+    #[test]
+    fn continues_long_inventories_over_pages() {
+        let mut project = Project::new("Cage 4");
+        project.layout.racks = vec![rack("A1", 100)];
+        let pages = pages(&project);
+        // 44 rows fit on a page.
+        assert_eq!(pages.len(), 5);
+        assert_eq!(headings(&pages[3])[0], "A1 inventory (continued)");
+        assert!(headings(&pages[4]).contains(&"Power"));
+    }
+
+    #[test]
+    fn moves_the_power_summary_to_a_new_page_when_full() {
+        let mut project = Project::new("Cage 4");
+        project.layout.racks = vec![rack("A1", 44)];
+        let pages = pages(&project);
+        assert_eq!(pages.len(), 4);
+        assert_eq!(headings(&pages[3]), ["A1 inventory (continued)", "Power"]);
+    }
+
+    #[test]
+    fn totals_power_without_overflowing() {
+        let mut rack = rack("A1", 3);
+        for device in &mut rack.devices {
+            device.power = Some(PowerDraw {
+                nameplate_w: u32::MAX,
+                typical_w: 2_000_000_000,
+            });
+        }
+        rack.devices
+            .push(Device::new("unknown", 1, 10, 700, Face::Front));
+        let mut blank = Device::new("blank", 1, 11, 10, Face::Front);
+        blank.category = Category::BlankingPanel;
+        rack.devices.push(blank);
+
+        let totals = PowerTotals::of(&rack);
+        assert_eq!(totals.typical_w, 6_000_000_000);
+        assert_eq!(totals.nameplate_w, 3 * u64::from(u32::MAX));
+        assert_eq!(totals.unknown, 1);
+    }
+}
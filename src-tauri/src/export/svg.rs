@@ -0,0 +1,137 @@
+//! Renders drawings as standalone SVG documents.
+
+use std::fmt::Write;
+
+use super::drawing::{Anchor, Drawing, Shape};
+
+pub fn render(drawing: &Drawing) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}pt" height="{h}pt" viewBox="0 0 {w} {h}" font-family="Helvetica, Arial, sans-serif">"#,
+        w = num(drawing.width),
+        h = num(drawing.height),
+    );
+    for shape in &drawing.shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let fill = fill.map_or_else(|| String::from("none"), |c| c.hex());
+                let stroke = stroke.map_or_else(|| String::from("none"), |c| c.hex());
+                let _ = writeln!(
+                    svg,
+                    r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{fill}" stroke="{stroke}" stroke-width="0.75"/>"#,
+                    num(*x),
+                    num(*y),
+                    num(*width),
+                    num(*height),
+                );
+            }
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                width,
+            } => {
+                let _ = writeln!(
+                    svg,
+                    r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}"/>"#,
+                    num(*x1),
+                    num(*y1),
+                    num(*x2),
+                    num(*y2),
+                    color.hex(),
+                    num(*width),
+                );
+            }
+            Shape::Text {
+                x,
+                y,
+                size,
+                anchor,
+                bold,
+                color,
+                text,
+            } => {
+                let anchor = match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                    Anchor::End => "end",
+                };
+                let weight = if *bold { "bold" } else { "normal" };
+                let _ = writeln!(
+                    svg,
+                    r#"  <text x="{}" y="{}" font-size="{}" font-weight="{weight}" text-anchor="{anchor}" fill="{}">{}</text>"#,
+                    num(*x),
+                    num(y + size / 3.0),
+                    num(*size),
+                    color.hex(),
+                    escape(text),
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Formats a coordinate without float noise, e.g. `12.5` rather than
+/// `12.499999999999998`.
+fn num(value: f64) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::drawing::Color;
+
+    #[test]
+    fn sizes_the_document_in_points() {
+        let svg = render(&Drawing::new(120.5, 80.0));
+        assert!(svg.contains(r#"width="120.5pt" height="80pt" viewBox="0 0 120.5 80""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn rounds_coordinates() {
+        assert_eq!(num(12.499999999999998), "12.5");
+        assert_eq!(num(3.0), "3");
+        assert_eq!(num(0.126), "0.13");
+    }
+
+    #[test]
+    fn escapes_text() {
+        let mut drawing = Drawing::new(100.0, 100.0);
+        drawing.heading((0.0, 0.0), 11.0, r#"R&D <"lab"> (Front)"#);
+        drawing.text((0.0, 20.0), 7.0, Anchor::Middle, "db<01>");
+        let svg = render(&drawing);
+        assert!(svg.contains(">R&amp;D &lt;&quot;lab&quot;&gt; (Front)</text>"));
+        assert!(svg.contains(">db&lt;01&gt;</text>"));
+        assert!(!svg.contains("<01>"));
+    }
+
+    #[test]
+    fn renders_fill_and_stroke() {
+        let mut drawing = Drawing::new(10.0, 10.0);
+        drawing.rect((0.0, 0.0, 10.0, 10.0), Some(Color::WHITE), None);
+        let svg = render(&drawing);
+        assert!(svg.contains(r##"fill="#ffffff" stroke="none""##));
+    }
+}
@@ -0,0 +1,266 @@
+//! Tabular listings of a project, shared by the spreadsheet exports.
+//!
+//! Column layouts are part of the export format: teams build spreadsheets
+//! and scripts on top of them, so columns are only ever added at the end.
+
+use std::cmp::Reverse;
+
+use super::elevation::face_label;
+use crate::analysis::cabling::CableReport;
+use crate::model::{Face, Layout, PortRef, PowerConnection};
+
+pub struct Table {
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Every device, rack by rack from the top of each rack down.
+pub fn devices(layout: &Layout) -> Table {
+    let mut rows = Vec::new();
+    for rack in &layout.racks {
+        let mut devices: Vec<_> = rack.devices.iter().collect();
+        devices.sort_by_key(|d| (Reverse(d.units().top), d.face == Face::Rear));
+        for device in devices {
+            let units = device.units();
+            rows.push(vec![
+                rack.name.clone(),
+                units.top.to_string(),
+                units.bottom.to_string(),
+                device.height_u.to_string(),
+                face_label(device.face).to_owned(),
+                device.name.clone(),
+                device.category.label().to_owned(),
+                device.manufacturer.clone().unwrap_or_default(),
+                device.model.clone().unwrap_or_default(),
+                device.serial.clone().unwrap_or_default(),
+                device.id.to_string(),
+            ]);
+        }
+    }
+    Table {
+        columns: &[
+            "Rack",
+            "Top U",
+            "Bottom U",
+            "Height (U)",
+            "Face",
+            "Name",
+            "Category",
+            "Manufacturer",
+            "Model",
+            "Serial",
+            "Device ID",
+        ],
+        rows,
+    }
+}
+
+/// Every cable with its estimated stock length from `lengths`.
+pub fn cables(layout: &Layout, lengths: &CableReport) -> Table {
+    let end = |port: &PortRef| match layout.find_device(port.device_id) {
+        Ok((rack, device)) => [rack.name.clone(), device.name.clone(), port.port.clone()],
+        Err(_) => [String::new(), String::new(), port.port.clone()],
+    };
+    let rows = layout
+        .cables
+        .iter()
+        .map(|cable| {
+            let length = lengths.cables.iter().find(|l| l.cable_id == cable.id);
+            let mut row = Vec::with_capacity(11);
+            row.extend(end(&cable.a));
+            row.extend(end(&cable.b));
+            row.push(length.map_or("", |l| l.kind.label()).to_owned());
+            row.push(
+                length
+                    .and_then(|l| l.length_m)
+                    .map_or_else(String::new, |m| format!("{m:.2}")),
+            );
+            row.push(
+                length
+                    .and_then(|l| l.stock_m)
+                    .map_or_else(String::new, |m| m.to_string()),
+            );
+            row.push(cable.color.clone().unwrap_or_default());
+            row.push(cable.id.to_string());
+            row
+        })
+        .collect();
+    Table {
+        columns: &[
+            "From Rack",
+            "From Device",
+            "From Port",
+            "To Rack",
+            "To Device",
+            "To Port",
+            "Type",
+            "Estimated Length (m)",
+            "Stock Length (m)",
+            "Color",
+            "Cable ID",
+        ],
+        rows,
+    }
+}
+
+/// Every PDU outlet and the device plugged into it, if any.
+pub fn outlets(layout: &Layout) -> Table {
+    let mut rows = Vec::new();
+    for rack in &layout.racks {
+        for pdu in &rack.pdus {
+            for circuit in &pdu.circuits {
+                for outlet in &circuit.outlets {
+                    let connection = PowerConnection {
+                        pdu_id: pdu.id,
+                        outlet: outlet.name.clone(),
+                    };
+                    let user = layout.outlet_user(&connection);
+                    let user_rack = user.and_then(|d| layout.find_device(d.id).ok());
+                    rows.push(vec![
+                        rack.name.clone(),
+                        pdu.name.clone(),
+                        pdu.feed.map_or_else(String::new, |f| format!("{f:?}")),
+                        circuit.name.clone(),
+                        format!("{:?}", circuit.phase),
+                        outlet.name.clone(),
+                        outlet.kind.label().to_owned(),
+                        user_rack.map_or_else(String::new, |(r, _)| r.name.clone()),
+                        user.map_or_else(String::new, |d| d.name.clone()),
+                    ]);
+                }
+            }
+        }
+    }
+    Table {
+        columns: &[
+            "Rack",
+            "PDU",
+            "Feed",
+            "Circuit",
+            "Phase",
+            "Outlet",
+            "Outlet Type",
+            "Device Rack",
+            "Device",
+        ],
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::cabling::{self, Routing};
+    use crate::model::{Cable, Device, Port, PortKind, Rack};
+
+    fn layout() -> Layout {
+        let mut rack = Rack::new("A1", 42);
+        let mut low = Device::new("db-01", 2, 1, 600, Face::Front);
+        low.ports = vec![Port::new("eth0", PortKind::Rj45)];
+        let mut high = Device::new("web, \"edge\"", 1, 10, 600, Face::Rear);
+        high.ports = vec![Port::new("eth0", PortKind::Rj45)];
+        rack.devices = vec![low, high];
+        Layout {
+            racks: vec![rack],
+            ..Layout::default()
+        }
+    }
+
+    #[test]
+    fn keeps_the_device_columns_in_order() {
+        assert_eq!(
+            devices(&Layout::default()).columns,
+            [
+                "Rack",
+                "Top U",
+                "Bottom U",
+                "Height (U)",
+                "Face",
+                "Name",
+                "Category",
+                "Manufacturer",
+                "Model",
+                "Serial",
+                "Device ID",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_cable_columns_in_order() {
+        let layout = Layout::default();
+        let lengths = cabling::report(&layout, &Routing::default());
+        assert_eq!(
+            cables(&layout, &lengths).columns,
+            [
+                "From Rack",
+                "From Device",
+                "From Port",
+                "To Rack",
+                "To Device",
+                "To Port",
+                "Type",
+                "Estimated Length (m)",
+                "Stock Length (m)",
+                "Color",
+                "Cable ID",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_outlet_columns_in_order() {
+        assert_eq!(
+            outlets(&Layout::default()).columns,
+            [
+                "Rack",
+                "PDU",
+                "Feed",
+                "Circuit",
+                "Phase",
+                "Outlet",
+                "Outlet Type",
+                "Device Rack",
+                "Device",
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_devices_from_the_top_down() {
+        let table = devices(&layout());
+        let names: Vec<_> = table.rows.iter().map(|r| r[5].as_str()).collect();
+        assert_eq!(names, ["web, \"edge\"", "db-01"]);
+        assert_eq!(table.rows[1][1..4], ["2", "1", "2"]);
+        assert!(table.rows.iter().all(|r| r.len() == table.columns.len()));
+    }
+
+    #[test]
+    fn escapes_device_names_in_csv() {
+        let csv = crate::export::csv::render(&devices(&layout()));
+        let line = csv.lines().nth(1).unwrap();
+        assert!(line.starts_with("A1,10,10,1,"));
+        assert!(line.contains(",\"web, \"\"edge\"\"\","));
+    }
+
+    #[test]
+    fn fills_every_cable_column() {
+        let mut layout = layout();
+        let devices = &layout.racks[0].devices;
+        let end = |d: &Device| PortRef {
+            device_id: d.id,
+            port: String::from("eth0"),
+        };
+        let mut cable = Cable::new(end(&devices[0]), end(&devices[1]));
+        cable.color = Some(String::from("blue"));
+        layout.cables = vec![cable];
+        let lengths = cabling::report(&layout, &Routing::default());
+        let table = cables(&layout, &lengths);
+        let row = &table.rows[0];
+        assert_eq!(row.len(), table.columns.len());
+        assert_eq!(row[..3], ["A1", "db-01", "eth0"]);
+        assert_eq!(row[3..6], ["A1", "web, \"edge\"", "eth0"]);
+        assert_eq!(row[6], "Copper");
+        assert_eq!(row[9], "blue");
+    }
+}
@@ -0,0 +1,167 @@
+//! The rack workbook: a summary sheet and one sheet per rack listing every
+//! unit from the top down, as datacenter remote-hands forms expect.
+
+use super::elevation::face_label;
+use super::report::{today, used_units, PowerTotals};
+use super::xlsx::{Cell, Merge, Sheet};
+use crate::analysis::power::total;
+use crate::model::{Device, Face, Rack};
+use crate::project::Project;
+
+/// Columns per face on a rack sheet, after the unit number.
+const FACE_COLUMNS: [&str; 4] = ["", "Category", "Model", "Serial"];
+
+pub fn sheets(project: &Project) -> Vec<Sheet> {
+    let mut sheets = vec![summary(project)];
+    sheets.extend(project.layout.racks.iter().map(rack_sheet));
+    sheets
+}
+
+fn summary(project: &Project) -> Sheet {
+    let mut sheet = Sheet::new("Summary");
+    sheet
+        .rows
+        .push(vec![Cell::Heading(project.metadata.name.clone())]);
+    sheet
+        .rows
+        .push(vec![Cell::text("Generated"), Cell::text(today())]);
+    if !project.metadata.author.is_empty() {
+        sheet.rows.push(vec![
+            Cell::text("Author"),
+            Cell::text(&project.metadata.author),
+        ]);
+    }
+    sheet.rows.push(Vec::new());
+    sheet.rows.push(
+        [
+            "Rack",
+            "Height (U)",
+            "Used (U)",
+            "Free (U)",
+            "Devices",
+            "Typical (W)",
+            "Nameplate (W)",
+            "Weight (kg)",
+        ]
+        .into_iter()
+        .map(|h| Cell::Heading(h.to_owned()))
+        .collect(),
+    );
+    for rack in &project.layout.racks {
+        let power = PowerTotals::of(rack);
+        let used = used_units(rack);
+        sheet.rows.push(vec![
+            Cell::text(&rack.name),
+            Cell::Number(f64::from(rack.height_u)),
+            Cell::Number(f64::from(used)),
+            Cell::Number(f64::from(rack.height_u.saturating_sub(used))),
+            Cell::Number(rack.devices.len() as f64),
+            Cell::Number(power.typical_w as f64),
+            Cell::Number(power.nameplate_w as f64),
+            Cell::Number(total(
+                rack.weight_kg
+                    .into_iter()
+                    .chain(rack.devices.iter().filter_map(|d| d.weight_kg)),
+            )),
+        ]);
+    }
+    sheet
+}
+
+fn rack_sheet(rack: &Rack) -> Sheet {
+    let mut sheet = Sheet::new(&rack.name);
+    sheet.freeze_header = true;
+    let mut header = vec![Cell::Heading(String::from("U"))];
+    for face in [Face::Front, Face::Rear] {
+        for column in FACE_COLUMNS {
+            let title = if column.is_empty() {
+                face_label(face).to_owned()
+            } else {
+                format!("{} {}", face_label(face), column.to_lowercase())
+            };
+            header.push(Cell::Heading(title));
+        }
+    }
+    sheet.rows.push(header);
+
+    for unit in (1..=rack.height_u).rev() {
+        let mut row = vec![Cell::Number(f64::from(unit))];
+        for face in [Face::Front, Face::Rear] {
+            let device = rack
+                .devices
+                .iter()
+                .find(|d| d.face == face && d.units().contains(unit));
+            match device {
+                Some(device) if device.units().top == unit => row.extend(device_cells(device)),
+                _ => row.extend(std::iter::repeat_n(Cell::Empty, FACE_COLUMNS.len())),
+            }
+        }
+        sheet.rows.push(row);
+    }
+
+    for (i, face) in [Face::Front, Face::Rear].into_iter().enumerate() {
+        for device in rack.devices.iter().filter(|d| d.face == face) {
+            let units = device.units();
+            if units.top > rack.height_u || units.bottom == units.top {
+                continue;
+            }
+            let first_row = (rack.height_u - units.top + 1) as usize;
+            let last_row = (rack.height_u - units.bottom + 1) as usize;
+            for column in 0..FACE_COLUMNS.len() {
+                sheet.merges.push(Merge {
+                    column: 1 + i * FACE_COLUMNS.len() + column,
+                    first_row,
+                    last_row,
+                });
+            }
+        }
+    }
+    sheet
+}
+
+fn device_cells(device: &Device) -> [Cell; 4] {
+    let model = [device.manufacturer.as_deref(), device.model.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    [
+        Cell::text(&device.name),
+        Cell::text(device.category.label()),
+        optional(Some(model).filter(|m| !m.is_empty())),
+        optional(device.serial.clone()),
+    ]
+}
+
+fn optional(text: Option<String>) -> Cell {
+    text.map_or(Cell::Empty, Cell::Text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighs_racks_with_their_devices() {
+        let mut project = Project::new("Cage 4");
+        let mut rack = Rack::new("A1", 42);
+        rack.weight_kg = Some(120.0);
+        for (u, kg) in [(1, Some(20.5)), (2, None), (3, Some(9.5))] {
+            let mut device = Device::new(format!("srv-{u}"), 1, u, 700, Face::Front);
+            device.weight_kg = kg;
+            rack.devices.push(device);
+        }
+        project.layout.racks.push(rack);
+        project.layout.racks.push(Rack::new("A2", 42));
+
+        let summary = summary(&project);
+        let weights: Vec<_> = summary
+            .rows
+            .iter()
+            .rev()
+            .take(2)
+            .map(|row| row.last().unwrap())
+            .collect();
+        assert_eq!(weights, [&Cell::Number(0.0), &Cell::Number(150.0)]);
+    }
+}
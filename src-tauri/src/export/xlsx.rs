@@ -0,0 +1,424 @@
+//! Writes Office Open XML (.xlsx) workbooks.
+//!
+//! Only what the exports need is supported: text and number cells, bold
+//! headings, vertically merged cells and a frozen header row. Strings are
+//! stored inline rather than in a shared string table, which every reader
+//! handles and keeps the writer stateless.
+
+use std::fmt::Write as _;
+use std::io::{Cursor, Write as _};
+
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::error::{Error, Result};
+
+/// Excel's limit on sheet name length.
+const MAX_SHEET_NAME: usize = 31;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+    Heading(String),
+}
+
+impl Cell {
+    pub fn text(text: impl Into<String>) -> Self {
+        Cell::Text(text.into())
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Cell::Empty => 0,
+            Cell::Text(s) | Cell::Heading(s) => s.chars().count(),
+            Cell::Number(n) => n.to_string().len(),
+        }
+    }
+}
+
+/// Cells in one column merged over several rows, counted from 0.
+#[derive(Debug, Clone, Copy)]
+pub struct Merge {
+    pub column: usize,
+    pub first_row: usize,
+    pub last_row: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: Vec<Vec<Cell>>,
+    pub merges: Vec<Merge>,
+    /// Whether the first row stays in view while scrolling.
+    pub freeze_header: bool,
+}
+
+impl Sheet {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            rows: Vec::new(),
+            merges: Vec::new(),
+            freeze_header: false,
+        }
+    }
+}
+
+pub fn render(sheets: &[Sheet]) -> Result<Vec<u8>> {
+    let names = sheet_names(sheets);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut add = |path: &str, contents: String| -> Result<()> {
+        zip.start_file(path, SimpleFileOptions::default())
+            .map_err(|e| Error::Encode(e.to_string()))?;
+        zip.write_all(contents.as_bytes())
+            .map_err(|e| Error::Encode(e.to_string()))
+    };
+
+    add("[Content_Types].xml", content_types(sheets.len()))?;
+    add("_rels/.rels", String::from(ROOT_RELS))?;
+    add("xl/workbook.xml", workbook(&names))?;
+    add("xl/_rels/workbook.xml.rels", workbook_rels(sheets.len()))?;
+    add("xl/styles.xml", String::from(STYLES))?;
+    for (i, sheet) in sheets.iter().enumerate() {
+        add(
+            &format!("xl/worksheets/sheet{}.xml", i + 1),
+            worksheet(sheet),
+        )?;
+    }
+    let cursor = zip.finish().map_err(|e| Error::Encode(e.to_string()))?;
+    Ok(cursor.into_inner())
+}
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+/// Style 0 is the default, 1 bold, 2 vertically centered for merged cells.
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="3"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0" applyAlignment="1"><alignment vertical="center"/></xf></cellXfs></styleSheet>"#;
+
+fn content_types(sheets: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    );
+    for i in 1..=sheets {
+        let _ = write!(
+            xml,
+            r#"<Override PartName="/xl/worksheets/sheet{i}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        );
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+fn workbook(names: &[String]) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    );
+    for (i, name) in names.iter().enumerate() {
+        let _ = write!(
+            xml,
+            r#"<sheet name="{}" sheetId="{id}" r:id="rId{id}"/>"#,
+            escape(name),
+            id = i + 1
+        );
+    }
+    xml.push_str("</sheets></workbook>");
+    xml
+}
+
+fn workbook_rels(sheets: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for i in 1..=sheets {
+        let _ = write!(
+            xml,
+            r#"<Relationship Id="rId{i}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{i}.xml"/>"#
+        );
+    }
+    let _ = write!(
+        xml,
+        r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+        sheets + 1
+    );
+    xml
+}
+
+fn worksheet(sheet: &Sheet) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    );
+    if sheet.freeze_header {
+        xml.push_str(r#"<sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews>"#);
+    }
+
+    let columns = sheet.rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns > 0 {
+        xml.push_str("<cols>");
+        for col in 0..columns {
+            let widest = sheet
+                .rows
+                .iter()
+                .filter_map(|row| row.get(col))
+                .map(Cell::width)
+                .max()
+                .unwrap_or(0);
+            let _ = write!(
+                xml,
+                r#"<col min="{n}" max="{n}" width="{}" customWidth="1"/>"#,
+                (widest + 2).clamp(6, 60),
+                n = col + 1
+            );
+        }
+        xml.push_str("</cols>");
+    }
+
+    xml.push_str("<sheetData>");
+    for (r, row) in sheet.rows.iter().enumerate() {
+        let _ = write!(xml, r#"<row r="{}">"#, r + 1);
+        for (c, cell) in row.iter().enumerate() {
+            let merged = sheet
+                .merges
+                .iter()
+                .any(|m| m.column == c && (m.first_row..=m.last_row).contains(&r));
+            let style = match cell {
+                Cell::Heading(_) => r#" s="1""#,
+                _ if merged => r#" s="2""#,
+                _ => "",
+            };
+            let at = reference(r, c);
+            match cell {
+                Cell::Empty if merged => {
+                    let _ = write!(xml, r#"<c r="{at}"{style}/>"#);
+                }
+                Cell::Empty => {}
+                Cell::Number(n) => {
+                    let _ = write!(xml, r#"<c r="{at}"{style}><v>{n}</v></c>"#);
+                }
+                Cell::Text(s) | Cell::Heading(s) => {
+                    let _ = write!(
+                        xml,
+                        r#"<c r="{at}" t="inlineStr"{style}><is><t xml:space="preserve">{}</t></is></c>"#,
+                        escape(s)
+                    );
+                }
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData>");
+
+    let merges: Vec<_> = sheet
+        .merges
+        .iter()
+        .filter(|m| m.last_row > m.first_row)
+        .collect();
+    if !merges.is_empty() {
+        let _ = write!(xml, r#"<mergeCells count="{}">"#, merges.len());
+        for m in merges {
+            let _ = write!(
+                xml,
+                r#"<mergeCell ref="{}:{}"/>"#,
+                reference(m.first_row, m.column),
+                reference(m.last_row, m.column)
+            );
+        }
+        xml.push_str("</mergeCells>");
+    }
+    xml.push_str("</worksheet>");
+    xml
+}
+
+/// An A1-style cell reference for a zero-based row and column.
+fn reference(row: usize, column: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = column + 1;
+    while n > 0 {
+        n -= 1;
+        letters.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8(letters).unwrap(), row + 1)
+}
+
+/// Makes sheet names valid and unique: Excel rejects `[]:*?/\`, names over
+/// 31 characters and duplicates that differ only in case.
+fn sheet_names(sheets: &[Sheet]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(sheets.len());
+    for sheet in sheets {
+        let cleaned: String = sheet
+            .name
+            .chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+            .collect();
+        let cleaned = cleaned.trim_matches('\'').trim();
+        let base = if cleaned.is_empty() { "Sheet" } else { cleaned };
+        let mut name: String = base.chars().take(MAX_SHEET_NAME).collect();
+        let mut n = 2;
+        while names.iter().any(|other| other.eq_ignore_ascii_case(&name)) {
+            let suffix = format!(" ({n})");
+            let keep = MAX_SHEET_NAME - suffix.len();
+            let kept: String = base.chars().take(keep).collect();
+            name = format!("{}{suffix}", kept.trim_end());
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Escapes text for XML, dropping control characters XML 1.0 cannot hold.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    fn open(bytes: Vec<u8>) -> ZipArchive<Cursor<Vec<u8>>> {
+        ZipArchive::new(Cursor::new(bytes)).unwrap()
+    }
+
+    fn part(zip: &mut ZipArchive<Cursor<Vec<u8>>>, path: &str) -> String {
+        let mut xml = String::new();
+        zip.by_name(path).unwrap().read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    #[test]
+    fn writes_every_part_of_the_package() {
+        let sheets = [Sheet::new("Devices"), Sheet::new("Cables")];
+        let mut zip = open(render(&sheets).unwrap());
+        let mut paths: Vec<_> = zip.file_names().map(String::from).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/_rels/workbook.xml.rels",
+                "xl/styles.xml",
+                "xl/workbook.xml",
+                "xl/worksheets/sheet1.xml",
+                "xl/worksheets/sheet2.xml",
+            ]
+        );
+
+        let types = part(&mut zip, "[Content_Types].xml");
+        for i in 1..=2 {
+            assert!(types.contains(&format!(r#"PartName="/xl/worksheets/sheet{i}.xml""#)));
+        }
+        let workbook = part(&mut zip, "xl/workbook.xml");
+        assert!(workbook.contains(r#"<sheet name="Devices" sheetId="1" r:id="rId1"/>"#));
+        assert!(workbook.contains(r#"<sheet name="Cables" sheetId="2" r:id="rId2"/>"#));
+        let rels = part(&mut zip, "xl/_rels/workbook.xml.rels");
+        assert!(rels.contains(r#"Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles""#));
+    }
+
+    #[test]
+    fn escapes_inline_strings() {
+        let mut sheet = Sheet::new("Devices");
+        sheet.rows = vec![
+            vec![Cell::Heading(String::from("Name")), Cell::text("U")],
+            vec![Cell::text("R&D <lab> \"x\"\u{7}"), Cell::Number(42.0)],
+        ];
+        let xml = part(
+            &mut open(render(&[sheet]).unwrap()),
+            "xl/worksheets/sheet1.xml",
+        );
+        assert!(xml.contains(
+            r#"<c r="A1" t="inlineStr" s="1"><is><t xml:space="preserve">Name</t></is></c>"#
+        ));
+        assert!(xml.contains(
+            r#"<c r="A2" t="inlineStr"><is><t xml:space="preserve">R&amp;D &lt;lab&gt; &quot;x&quot;</t></is></c>"#
+        ));
+        assert!(xml.contains(r#"<c r="B2"><v>42</v></c>"#));
+    }
+
+    #[test]
+    fn writes_merged_ranges() {
+        let mut sheet = Sheet::new("Elevation");
+        sheet.rows = vec![
+            vec![Cell::text("42"), Cell::text("sw-01")],
+            vec![Cell::text("41"), Cell::Empty],
+            vec![Cell::text("40"), Cell::Empty],
+        ];
+        sheet.merges = vec![
+            Merge {
+                column: 1,
+                first_row: 0,
+                last_row: 2,
+            },
+            // A single cell is not a range and is left out.
+            Merge {
+                column: 0,
+                first_row: 1,
+                last_row: 1,
+            },
+        ];
+        let xml = part(
+            &mut open(render(&[sheet]).unwrap()),
+            "xl/worksheets/sheet1.xml",
+        );
+        assert!(xml.contains(r#"<mergeCells count="1"><mergeCell ref="B1:B3"/></mergeCells>"#));
+        assert!(xml.contains(r#"<c r="B2" s="2"/>"#));
+    }
+
+    #[test]
+    fn cleans_up_sheet_names() {
+        let long = "A very long rack name that goes on and on";
+        let sheets: Vec<_> = ["Rack [A1]: *?/\\", long, long, "devices", "Devices", "''"]
+            .into_iter()
+            .map(Sheet::new)
+            .collect();
+        let names = sheet_names(&sheets);
+        assert_eq!(
+            names,
+            [
+                "Rack _A1__ ____",
+                "A very long rack name that goes",
+                "A very long rack name that (2)",
+                "devices",
+                "Devices (2)",
+                "Sheet",
+            ]
+        );
+        assert!(names.iter().all(|n| n.chars().count() <= MAX_SHEET_NAME));
+
+        let workbook = part(&mut open(render(&sheets).unwrap()), "xl/workbook.xml");
+        assert!(workbook.contains(r#"name="Devices (2)""#));
+    }
+
+    #[test]
+    fn names_columns_past_z() {
+        assert_eq!(reference(0, 0), "A1");
+        assert_eq!(reference(9, 25), "Z10");
+        assert_eq!(reference(0, 26), "AA1");
+        assert_eq!(reference(0, 701), "ZZ1");
+        assert_eq!(reference(0, 702), "AAA1");
+    }
+}
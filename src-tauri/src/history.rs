@@ -0,0 +1,890 @@
+//! Undo and redo of project edits.
+//!
+//! Every change to a project is expressed as an [`Edit`] that knows how to
+//! apply itself and how to build its own inverse. Commands never mutate the
+//! project directly; they build an edit and hand it to [`History::apply`].
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::model::{
+    Airflow, Cable, CableId, Device, DeviceId, Face, Feed, FloorPosition, Pdu, PduId, Port,
+    PortRef, PowerConnection, Rack, RackId, Room, Transceiver,
+};
+use crate::project::{Metadata, Project};
+
+/// How many edits are kept before the oldest are forgotten.
+const LIMIT: usize = 200;
+
+/// Where a device sits, for edits that move it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub rack_id: RackId,
+    pub position_u: u32,
+    pub face: Face,
+}
+
+#[derive(Debug, Clone)]
+pub enum Edit {
+    InsertRack {
+        index: usize,
+        rack: Rack,
+    },
+    RemoveRack {
+        index: usize,
+        rack: Rack,
+    },
+    RenameRack {
+        rack_id: RackId,
+        from: String,
+        to: String,
+    },
+    SetRackWeight {
+        rack_id: RackId,
+        name: String,
+        from: Option<f64>,
+        to: Option<f64>,
+    },
+    SetLoadRating {
+        rack_id: RackId,
+        name: String,
+        from: Option<f64>,
+        to: Option<f64>,
+    },
+    SetColdAisle {
+        rack_id: RackId,
+        name: String,
+        from: Face,
+        to: Face,
+    },
+    SetRackPosition {
+        rack_id: RackId,
+        name: String,
+        from: Option<FloorPosition>,
+        to: Option<FloorPosition>,
+    },
+    SetRackNetboxId {
+        rack_id: RackId,
+        name: String,
+        from: Option<u64>,
+        to: Option<u64>,
+    },
+    AddDevice {
+        rack_id: RackId,
+        device: Device,
+    },
+    RemoveDevice {
+        rack_id: RackId,
+        device: Device,
+    },
+    MoveDevice {
+        device_id: DeviceId,
+        name: String,
+        from: Placement,
+        to: Placement,
+    },
+    SetDeviceWeight {
+        device_id: DeviceId,
+        name: String,
+        from: Option<f64>,
+        to: Option<f64>,
+    },
+    SetSerial {
+        device_id: DeviceId,
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    SetAirflow {
+        device_id: DeviceId,
+        name: String,
+        from: Option<Airflow>,
+        to: Option<Airflow>,
+    },
+    SetDeviceNetboxId {
+        device_id: DeviceId,
+        name: String,
+        from: Option<u64>,
+        to: Option<u64>,
+    },
+    SetMetadata {
+        from: Metadata,
+        to: Metadata,
+    },
+    SetRoom {
+        from: Option<Room>,
+        to: Option<Room>,
+    },
+    InsertPdu {
+        rack_id: RackId,
+        index: usize,
+        pdu: Pdu,
+        /// Devices to plug back in when restoring a removed PDU.
+        connections: Vec<(DeviceId, PowerConnection)>,
+    },
+    RemovePdu {
+        rack_id: RackId,
+        index: usize,
+        pdu: Pdu,
+        connections: Vec<(DeviceId, PowerConnection)>,
+    },
+    SetPduFeed {
+        pdu_id: PduId,
+        name: String,
+        from: Option<Feed>,
+        to: Option<Feed>,
+    },
+    ConnectPower {
+        device_id: DeviceId,
+        connection: PowerConnection,
+    },
+    DisconnectPower {
+        device_id: DeviceId,
+        connection: PowerConnection,
+    },
+    InsertPort {
+        device_id: DeviceId,
+        index: usize,
+        port: Port,
+    },
+    RemovePort {
+        device_id: DeviceId,
+        index: usize,
+        port: Port,
+    },
+    Connect {
+        cable: Cable,
+    },
+    Disconnect {
+        cable: Cable,
+    },
+    SetCableColor {
+        cable_id: CableId,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    SetTransceiver {
+        port: PortRef,
+        from: Option<Transceiver>,
+        to: Option<Transceiver>,
+    },
+    /// Several edits undone and redone as one, e.g. unplugging a device's
+    /// cables before removing it.
+    Batch {
+        label: String,
+        edits: Vec<Edit>,
+    },
+}
+
+impl Edit {
+    pub fn apply(&self, project: &mut Project) -> Result<()> {
+        let layout = &mut project.layout;
+        match self {
+            Edit::InsertRack { index, rack } => {
+                layout.insert_rack(*index, rack.clone())?;
+            }
+            Edit::RemoveRack { rack, .. } => {
+                layout.remove_rack(rack.id)?;
+            }
+            Edit::RenameRack { rack_id, to, .. } => {
+                layout.rename_rack(*rack_id, to.clone())?;
+            }
+            Edit::SetRackWeight { rack_id, to, .. } => {
+                layout.set_rack_weight(*rack_id, *to)?;
+            }
+            Edit::SetLoadRating { rack_id, to, .. } => {
+                layout.set_load_rating(*rack_id, *to)?;
+            }
+            Edit::SetColdAisle { rack_id, to, .. } => {
+                layout.set_cold_aisle(*rack_id, *to)?;
+            }
+            Edit::SetRackPosition { rack_id, to, .. } => {
+                layout.set_rack_position(*rack_id, *to)?;
+            }
+            Edit::SetRackNetboxId { rack_id, to, .. } => {
+                layout.set_rack_netbox_id(*rack_id, *to)?;
+            }
+            Edit::AddDevice { rack_id, device } => {
+                layout.add_device(*rack_id, device.clone())?;
+            }
+            Edit::RemoveDevice { device, .. } => {
+                layout.remove_device(device.id)?;
+            }
+            Edit::MoveDevice { device_id, to, .. } => {
+                layout.move_device(*device_id, to.rack_id, to.position_u, to.face)?;
+            }
+            Edit::SetDeviceWeight { device_id, to, .. } => {
+                layout.set_device_weight(*device_id, *to)?;
+            }
+            Edit::SetSerial { device_id, to, .. } => {
+                layout.set_serial(*device_id, to.clone())?;
+            }
+            Edit::SetAirflow { device_id, to, .. } => {
+                layout.set_airflow(*device_id, *to)?;
+            }
+            Edit::SetDeviceNetboxId { device_id, to, .. } => {
+                layout.set_device_netbox_id(*device_id, *to)?;
+            }
+            Edit::SetMetadata { to, .. } => project.metadata = to.clone(),
+            Edit::SetRoom { to, .. } => {
+                layout.set_room(to.clone())?;
+            }
+            Edit::InsertPdu {
+                rack_id,
+                index,
+                pdu,
+                connections,
+            } => {
+                layout.insert_pdu(*rack_id, *index, pdu.clone(), connections)?;
+            }
+            Edit::RemovePdu { pdu, .. } => {
+                layout.remove_pdu(pdu.id)?;
+            }
+            Edit::SetPduFeed { pdu_id, to, .. } => {
+                layout.set_pdu_feed(*pdu_id, *to)?;
+            }
+            Edit::ConnectPower {
+                device_id,
+                connection,
+            } => layout.connect_power(*device_id, connection.clone())?,
+            Edit::DisconnectPower {
+                device_id,
+                connection,
+            } => layout.disconnect_power(*device_id, connection)?,
+            Edit::InsertPort {
+                device_id,
+                index,
+                port,
+            } => {
+                layout.insert_port(*device_id, *index, port.clone())?;
+            }
+            Edit::RemovePort {
+                device_id, port, ..
+            } => {
+                layout.remove_port(&PortRef {
+                    device_id: *device_id,
+                    port: port.name.clone(),
+                })?;
+            }
+            Edit::Connect { cable } => {
+                layout.connect(cable.clone())?;
+            }
+            Edit::Disconnect { cable } => {
+                layout.disconnect(cable.id)?;
+            }
+            Edit::SetCableColor { cable_id, to, .. } => {
+                layout.set_cable_color(*cable_id, to.clone())?;
+            }
+            Edit::SetTransceiver { port, to, .. } => {
+                layout.set_transceiver(port, *to)?;
+            }
+            Edit::Batch { edits, .. } => {
+                for (i, edit) in edits.iter().enumerate() {
+                    if let Err(e) = edit.apply(project) {
+                        for done in edits[..i].iter().rev() {
+                            let _ = done.inverse().apply(project);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::InsertRack { index, rack } => Edit::RemoveRack { index, rack },
+            Edit::RemoveRack { index, rack } => Edit::InsertRack { index, rack },
+            Edit::RenameRack { rack_id, from, to } => Edit::RenameRack {
+                rack_id,
+                from: to,
+                to: from,
+            },
+            Edit::SetRackWeight {
+                rack_id,
+                name,
+                from,
+                to,
+            } => Edit::SetRackWeight {
+                rack_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetLoadRating {
+                rack_id,
+                name,
+                from,
+                to,
+            } => Edit::SetLoadRating {
+                rack_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetColdAisle {
+                rack_id,
+                name,
+                from,
+                to,
+            } => Edit::SetColdAisle {
+                rack_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetRackPosition {
+                rack_id,
+                name,
+                from,
+                to,
+            } => Edit::SetRackPosition {
+                rack_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetRackNetboxId {
+                rack_id,
+                name,
+                from,
+                to,
+            } => Edit::SetRackNetboxId {
+                rack_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::AddDevice { rack_id, device } => Edit::RemoveDevice { rack_id, device },
+            Edit::RemoveDevice { rack_id, device } => Edit::AddDevice { rack_id, device },
+            Edit::MoveDevice {
+                device_id,
+                name,
+                from,
+                to,
+            } => Edit::MoveDevice {
+                device_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetDeviceWeight {
+                device_id,
+                name,
+                from,
+                to,
+            } => Edit::SetDeviceWeight {
+                device_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetSerial {
+                device_id,
+                name,
+                from,
+                to,
+            } => Edit::SetSerial {
+                device_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetAirflow {
+                device_id,
+                name,
+                from,
+                to,
+            } => Edit::SetAirflow {
+                device_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetDeviceNetboxId {
+                device_id,
+                name,
+                from,
+                to,
+            } => Edit::SetDeviceNetboxId {
+                device_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::SetMetadata { from, to } => Edit::SetMetadata { from: to, to: from },
+            Edit::SetRoom { from, to } => Edit::SetRoom { from: to, to: from },
+            Edit::InsertPdu {
+                rack_id,
+                index,
+                pdu,
+                connections,
+            } => Edit::RemovePdu {
+                rack_id,
+                index,
+                pdu,
+                connections,
+            },
+            Edit::RemovePdu {
+                rack_id,
+                index,
+                pdu,
+                connections,
+            } => Edit::InsertPdu {
+                rack_id,
+                index,
+                pdu,
+                connections,
+            },
+            Edit::SetPduFeed {
+                pdu_id,
+                name,
+                from,
+                to,
+            } => Edit::SetPduFeed {
+                pdu_id,
+                name,
+                from: to,
+                to: from,
+            },
+            Edit::ConnectPower {
+                device_id,
+                connection,
+            } => Edit::DisconnectPower {
+                device_id,
+                connection,
+            },
+            Edit::DisconnectPower {
+                device_id,
+                connection,
+            } => Edit::ConnectPower {
+                device_id,
+                connection,
+            },
+            Edit::InsertPort {
+                device_id,
+                index,
+                port,
+            } => Edit::RemovePort {
+                device_id,
+                index,
+                port,
+            },
+            Edit::RemovePort {
+                device_id,
+                index,
+                port,
+            } => Edit::InsertPort {
+                device_id,
+                index,
+                port,
+            },
+            Edit::Connect { cable } => Edit::Disconnect { cable },
+            Edit::Disconnect { cable } => Edit::Connect { cable },
+            Edit::SetCableColor { cable_id, from, to } => Edit::SetCableColor {
+                cable_id,
+                from: to,
+                to: from,
+            },
+            Edit::SetTransceiver { port, from, to } => Edit::SetTransceiver {
+                port,
+                from: to,
+                to: from,
+            },
+            Edit::Batch { label, edits } => Edit::Batch {
+                label,
+                edits: edits.iter().rev().map(Edit::inverse).collect(),
+            },
+        }
+    }
+
+    /// A short description for undo/redo menus.
+    pub fn label(&self) -> String {
+        match self {
+            Edit::InsertRack { rack, .. } => format!("Add rack {}", rack.name),
+            Edit::RemoveRack { rack, .. } => format!("Delete rack {}", rack.name),
+            Edit::RenameRack { from, to, .. } => format!("Rename rack {from} to {to}"),
+            Edit::SetRackWeight { name, .. } => format!("Change weight of rack {name}"),
+            Edit::SetLoadRating { name, .. } => format!("Change load rating of {name}"),
+            Edit::SetColdAisle { name, .. } => format!("Change aisle orientation of {name}"),
+            Edit::SetRackPosition { name, .. } => format!("Move rack {name}"),
+            Edit::AddDevice { device, .. } => format!("Add {}", device.name),
+            Edit::RemoveDevice { device, .. } => format!("Remove {}", device.name),
+            Edit::MoveDevice { name, .. } => format!("Move {name}"),
+            Edit::SetDeviceWeight { name, .. } => format!("Change weight of {name}"),
+            Edit::SetSerial { name, .. } => format!("Change serial number of {name}"),
+            Edit::SetAirflow { name, .. } => format!("Change airflow of {name}"),
+            Edit::SetRackNetboxId { name, .. } | Edit::SetDeviceNetboxId { name, .. } => {
+                format!("Link {name} to NetBox")
+            }
+            Edit::SetMetadata { .. } => String::from("Edit project details"),
+            Edit::SetRoom { .. } => String::from("Edit floor plan"),
+            Edit::InsertPdu { pdu, .. } => format!("Add PDU {}", pdu.name),
+            Edit::RemovePdu { pdu, .. } => format!("Remove PDU {}", pdu.name),
+            Edit::SetPduFeed { name, .. } => format!("Change feed of {name}"),
+            Edit::ConnectPower { connection, .. } => format!("Plug into {}", connection.outlet),
+            Edit::DisconnectPower { connection, .. } => {
+                format!("Unplug from {}", connection.outlet)
+            }
+            Edit::InsertPort { port, .. } => format!("Add port {}", port.name),
+            Edit::RemovePort { port, .. } => format!("Remove port {}", port.name),
+            Edit::Connect { cable } => format!("Connect {} to {}", cable.a.port, cable.b.port),
+            Edit::Disconnect { cable } => {
+                format!("Disconnect {} from {}", cable.a.port, cable.b.port)
+            }
+            Edit::SetCableColor { .. } => String::from("Change cable color"),
+            Edit::SetTransceiver { port, .. } => format!("Change transceiver in {}", port.port),
+            Edit::Batch { label, .. } => label.clone(),
+        }
+    }
+
+    /// Folds a later edit into this one if both are part of the same drag.
+    fn coalesce(&mut self, next: &Edit) -> bool {
+        match (self, next) {
+            (
+                Edit::MoveDevice { device_id, to, .. },
+                Edit::MoveDevice {
+                    device_id: next_id,
+                    to: next_to,
+                    ..
+                },
+            ) if device_id == next_id => {
+                *to = *next_to;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Labels of the edits that can be undone and redone, most recent first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryInfo {
+    pub undo: Vec<String>,
+    pub redo: Vec<String>,
+}
+
+/// An edit on the undo or redo stack, numbered so the state it leads to can
+/// be told apart from every other.
+#[derive(Debug)]
+struct Entry {
+    id: u64,
+    edit: Edit,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    /// The state below the oldest edit on the undo stack: 0 for the project
+    /// as opened, or the last edit forgotten past [`LIMIT`].
+    base: u64,
+    /// The state the project was last saved in.
+    saved: u64,
+    next_id: u64,
+}
+
+impl History {
+    /// Applies `edit` to `project` and records it.
+    ///
+    /// With `coalesce`, a move of the same device as the most recent edit is
+    /// merged into it, so a whole drag is undone in one step. The frontend
+    /// passes it for every move after the first in a drag.
+    pub fn apply(&mut self, project: &mut Project, edit: Edit, coalesce: bool) -> Result<()> {
+        edit.apply(project)?;
+        self.redo.clear();
+        self.next_id += 1;
+        let id = self.next_id;
+        if coalesce {
+            if let Some(last) = self.undo.last_mut() {
+                if last.edit.coalesce(&edit) {
+                    last.id = id;
+                    return Ok(());
+                }
+            }
+        }
+        self.undo.push(Entry { id, edit });
+        if self.undo.len() > LIMIT {
+            self.base = self.undo.remove(0).id;
+        }
+        Ok(())
+    }
+
+    /// Reverts the most recent edit, returning its label.
+    pub fn undo(&mut self, project: &mut Project) -> Result<Option<String>> {
+        let Some(entry) = self.undo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = entry.edit.inverse().apply(project) {
+            self.undo.push(entry);
+            return Err(e);
+        }
+        let label = entry.edit.label();
+        self.redo.push(entry);
+        Ok(Some(label))
+    }
+
+    /// Re-applies the most recently undone edit, returning its label.
+    pub fn redo(&mut self, project: &mut Project) -> Result<Option<String>> {
+        let Some(entry) = self.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = entry.edit.apply(project) {
+            self.redo.push(entry);
+            return Err(e);
+        }
+        let label = entry.edit.label();
+        self.undo.push(entry);
+        Ok(Some(label))
+    }
+
+    fn current(&self) -> u64 {
+        self.undo.last().map_or(self.base, |e| e.id)
+    }
+
+    /// Records that the project as it is now has been saved.
+    pub fn mark_saved(&mut self) {
+        self.saved = self.current();
+    }
+
+    /// Whether the project differs from when it was last saved, or opened.
+    /// Undoing back to the saved state makes it clean again.
+    pub fn is_modified(&self) -> bool {
+        self.current() != self.saved
+    }
+
+    pub fn info(&self) -> HistoryInfo {
+        HistoryInfo {
+            undo: self.undo.iter().rev().map(|e| e.edit.label()).collect(),
+            redo: self.redo.iter().rev().map(|e| e.edit.label()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PortKind;
+
+    fn project() -> (Project, RackId) {
+        let mut project = Project::new("Cage 4");
+        let rack = Rack::new("A1", 42);
+        let id = rack.id;
+        project.layout.racks.push(rack);
+        (project, id)
+    }
+
+    fn rename(rack_id: RackId, from: &str, to: &str) -> Edit {
+        Edit::RenameRack {
+            rack_id,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        }
+    }
+
+    fn name(project: &Project) -> &str {
+        &project.layout.racks[0].name
+    }
+
+    #[test]
+    fn undoes_and_redoes() {
+        let (mut project, id) = project();
+        let mut history = History::default();
+        history
+            .apply(&mut project, rename(id, "A1", "B1"), false)
+            .unwrap();
+        assert_eq!(name(&project), "B1");
+
+        let label = history.undo(&mut project).unwrap();
+        assert_eq!(label.as_deref(), Some("Rename rack A1 to B1"));
+        assert_eq!(name(&project), "A1");
+        assert_eq!(history.undo(&mut project).unwrap(), None);
+
+        history.redo(&mut project).unwrap();
+        assert_eq!(name(&project), "B1");
+        assert_eq!(history.redo(&mut project).unwrap(), None);
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let (mut project, id) = project();
+        let mut history = History::default();
+        history
+            .apply(&mut project, rename(id, "A1", "B1"), false)
+            .unwrap();
+        history.undo(&mut project).unwrap();
+        history
+            .apply(&mut project, rename(id, "A1", "C1"), false)
+            .unwrap();
+        assert!(history.info().redo.is_empty());
+        assert_eq!(history.redo(&mut project).unwrap(), None);
+        assert_eq!(name(&project), "C1");
+    }
+
+    #[test]
+    fn coalesces_moves_of_the_same_device() {
+        let (mut project, rack_id) = project();
+        let device = Device::new("web-01", 1, 1, 500, Face::Front);
+        let device_id = device.id;
+        project.layout.racks[0].devices.push(device);
+        let mut history = History::default();
+        let placement = |position_u| Placement {
+            rack_id,
+            position_u,
+            face: Face::Front,
+        };
+        for (i, (from, to)) in [(1, 2), (2, 3), (3, 4)].into_iter().enumerate() {
+            let edit = Edit::MoveDevice {
+                device_id,
+                name: String::from("web-01"),
+                from: placement(from),
+                to: placement(to),
+            };
+            history.apply(&mut project, edit, i > 0).unwrap();
+        }
+        assert_eq!(history.info().undo.len(), 1);
+
+        history.undo(&mut project).unwrap();
+        assert_eq!(project.layout.racks[0].devices[0].position_u, 1);
+    }
+
+    #[test]
+    fn a_failing_batch_rolls_back() {
+        let (mut project, id) = project();
+        let batch = Edit::Batch {
+            label: String::from("Rename twice"),
+            edits: vec![rename(id, "A1", "B1"), rename(RackId::new_v4(), "X", "Y")],
+        };
+        let mut history = History::default();
+        assert!(history.apply(&mut project, batch, false).is_err());
+        assert_eq!(name(&project), "A1");
+        assert!(history.info().undo.is_empty());
+    }
+
+    #[test]
+    fn forgets_edits_past_the_limit() {
+        let (mut project, id) = project();
+        let mut history = History::default();
+        for i in 0..LIMIT + 5 {
+            let edit = rename(id, &format!("R{i}"), &format!("R{}", i + 1));
+            history.apply(&mut project, edit, false).unwrap();
+        }
+        assert_eq!(history.info().undo.len(), LIMIT);
+        while history.undo(&mut project).unwrap().is_some() {}
+        assert_eq!(name(&project), "R5");
+        assert!(history.is_modified());
+    }
+
+    #[test]
+    fn undoing_back_to_the_saved_state_is_clean() {
+        let (mut project, id) = project();
+        let mut history = History::default();
+        assert!(!history.is_modified());
+        history
+            .apply(&mut project, rename(id, "A1", "B1"), false)
+            .unwrap();
+        history.mark_saved();
+        history
+            .apply(&mut project, rename(id, "B1", "C1"), false)
+            .unwrap();
+        assert!(history.is_modified());
+
+        history.undo(&mut project).unwrap();
+        assert!(!history.is_modified());
+        history.undo(&mut project).unwrap();
+        assert!(history.is_modified());
+        history.redo(&mut project).unwrap();
+        assert!(!history.is_modified());
+    }
+
+    #[test]
+    fn coalescing_into_the_saved_edit_is_a_change() {
+        let (mut project, rack_id) = project();
+        let device = Device::new("web-01", 1, 1, 500, Face::Front);
+        let device_id = device.id;
+        project.layout.racks[0].devices.push(device);
+        let mut history = History::default();
+        let edit = |from, to| Edit::MoveDevice {
+            device_id,
+            name: String::from("web-01"),
+            from: Placement {
+                rack_id,
+                position_u: from,
+                face: Face::Front,
+            },
+            to: Placement {
+                rack_id,
+                position_u: to,
+                face: Face::Front,
+            },
+        };
+        history.apply(&mut project, edit(1, 2), false).unwrap();
+        history.mark_saved();
+        history.apply(&mut project, edit(2, 3), true).unwrap();
+        assert!(history.is_modified());
+    }
+
+    #[test]
+    fn removing_a_cabled_port_is_undone_with_its_cable() {
+        let (mut project, _) = project();
+        let mut switch = Device::new("sw-01", 1, 1, 300, Face::Front);
+        switch.ports = vec![
+            Port::new("ge-0/0/0", PortKind::Rj45),
+            Port::new("ge-0/0/1", PortKind::Rj45),
+        ];
+        let device_id = switch.id;
+        project.layout.racks[0].devices.push(switch);
+        let a = PortRef {
+            device_id,
+            port: String::from("ge-0/0/0"),
+        };
+        let b = PortRef {
+            device_id,
+            port: String::from("ge-0/0/1"),
+        };
+        let cable = Cable::new(a, b);
+        project.layout.cables.push(cable.clone());
+
+        let mut history = History::default();
+        let edit = Edit::Batch {
+            label: String::from("Remove port ge-0/0/0"),
+            edits: vec![
+                Edit::Disconnect { cable },
+                Edit::RemovePort {
+                    device_id,
+                    index: 0,
+                    port: Port::new("ge-0/0/0", PortKind::Rj45),
+                },
+            ],
+        };
+        history.apply(&mut project, edit, false).unwrap();
+        assert_eq!(project.layout.racks[0].devices[0].ports.len(), 1);
+        assert!(project.layout.cables.is_empty());
+
+        history.undo(&mut project).unwrap();
+        let ports = &project.layout.racks[0].devices[0].ports;
+        assert_eq!(ports[0].name, "ge-0/0/0");
+        assert_eq!(ports.len(), 2);
+        assert_eq!(project.layout.cables.len(), 1);
+    }
+
+    #[test]
+    fn rejects_duplicate_port_names() {
+        let (mut project, _) = project();
+        let mut device = Device::new("custom", 1, 1, 300, Face::Front);
+        device.ports = vec![Port::new("eth0", PortKind::Rj45)];
+        let device_id = device.id;
+        project.layout.racks[0].devices.push(device);
+        let mut history = History::default();
+        let edit = |name| Edit::InsertPort {
+            device_id,
+            index: 1,
+            port: Port::new(name, PortKind::Rj45),
+        };
+        assert!(history.apply(&mut project, edit("eth0"), false).is_err());
+        history.apply(&mut project, edit("eth1"), false).unwrap();
+        assert_eq!(project.layout.racks[0].devices[0].ports.len(), 2);
+    }
+}
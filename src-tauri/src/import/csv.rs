@@ -0,0 +1,639 @@
+//! Imports devices from spreadsheets exported as CSV.
+//!
+//! The user maps their own column headers onto the fields the importer needs.
+//! Each row becomes one device: its model is matched against the device
+//! library, racks that do not exist yet are created, and every placement is
+//! checked against what is already in the layout and the rows before it.
+
+use serde::{Deserialize, Serialize};
+
+use super::ImportPlan;
+use crate::error::{Error, Result};
+use crate::library::{self, DeviceTemplate, ModelMatch};
+use crate::model::{Device, Face, Layout, Rack, RackId};
+use crate::validation::Conflict;
+
+/// One parsed record and the line it starts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+/// Which header names hold each field. Optional columns may be left unmapped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+    pub rack: String,
+    /// Lowest unit the device occupies.
+    pub position_u: String,
+    /// Needed for rows whose model does not match a library template.
+    pub height_u: Option<String>,
+    pub name: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub face: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Whether racks named in the file but missing from the layout are
+    /// created, rather than reported.
+    pub create_racks: bool,
+    /// Height of racks created by the import.
+    pub rack_height_u: u32,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            create_racks: true,
+            rack_height_u: 42,
+        }
+    }
+}
+
+/// A problem with one row. Rows with any blocking issue are skipped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Issue {
+    /// A required column is empty.
+    Missing {
+        column: &'static str,
+    },
+    InvalidNumber {
+        column: &'static str,
+        value: String,
+    },
+    InvalidFace {
+        value: String,
+    },
+    UnknownRack {
+        rack: String,
+    },
+    /// No template matches the model, so the device is imported without one.
+    UnknownModel {
+        model: String,
+    },
+    /// Several templates match the model, so the device is imported without
+    /// one.
+    AmbiguousModel {
+        model: String,
+        candidates: Vec<String>,
+    },
+    /// The device does not fit where the row puts it.
+    Placement {
+        conflicts: Vec<Conflict>,
+    },
+    Invalid {
+        message: String,
+    },
+}
+
+impl Issue {
+    pub fn is_blocking(&self) -> bool {
+        !matches!(
+            self,
+            Issue::UnknownModel { .. } | Issue::AmbiguousModel { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowReport {
+    pub line: usize,
+    pub rack: String,
+    pub name: String,
+    pub template_id: Option<String>,
+    pub imported: bool,
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub rows: Vec<RowReport>,
+    /// Racks the import creates.
+    pub new_racks: Vec<String>,
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Splits CSV text into records, following RFC 4180 but accepting any line
+/// ending and a leading byte order mark as written by Excel.
+pub fn parse(text: &str, delimiter: char) -> Result<Vec<Record>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut field_quoted = false;
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !field_quoted => {
+                in_quotes = true;
+                field_quoted = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                fields.push(std::mem::take(&mut field));
+                field_quoted = false;
+                records.push(Record {
+                    line: start,
+                    fields: std::mem::take(&mut fields),
+                });
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter => {
+                fields.push(std::mem::take(&mut field));
+                field_quoted = false;
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(Error::InvalidCsv {
+            line: start,
+            message: String::from("quoted field is never closed"),
+        });
+    }
+    if !field.is_empty() || field_quoted || !fields.is_empty() {
+        fields.push(field);
+        records.push(Record {
+            line: start,
+            fields,
+        });
+    }
+    Ok(records)
+}
+
+/// The header row, for offering columns to map.
+pub fn headers(text: &str, delimiter: char) -> Result<Vec<String>> {
+    Ok(parse(text, delimiter)?
+        .into_iter()
+        .next()
+        .map(|r| r.fields)
+        .unwrap_or_default())
+}
+
+/// Works out what importing `text` into `layout` would do without changing
+/// it. Fails only if the file cannot be read or the mapping names a column
+/// the file does not have; problems with individual rows are reported.
+pub fn plan(
+    layout: &Layout,
+    templates: &[DeviceTemplate],
+    text: &str,
+    mapping: &ColumnMapping,
+    options: &CsvOptions,
+) -> Result<(ImportPlan, ImportReport)> {
+    let mut records = parse(text, options.delimiter)?.into_iter();
+    let header = records.next().map(|r| r.fields).unwrap_or_default();
+    let columns = Columns::resolve(&header, mapping)?;
+
+    let mut importer = Importer {
+        layout: layout.clone(),
+        templates,
+        options,
+        new_racks: Vec::new(),
+        plan: ImportPlan::default(),
+    };
+    let rows: Vec<RowReport> = records
+        .filter(|r| r.fields.iter().any(|f| !f.trim().is_empty()))
+        .map(|r| importer.row(&r, &columns))
+        .collect();
+
+    let Importer {
+        new_racks,
+        mut plan,
+        ..
+    } = importer;
+    plan.racks = new_racks
+        .into_iter()
+        .filter(|rack| plan.devices.iter().any(|(id, _)| *id == rack.id))
+        .collect();
+    let imported = rows.iter().filter(|r| r.imported).count();
+    let report = ImportReport {
+        new_racks: plan.racks.iter().map(|r| r.name.clone()).collect(),
+        imported,
+        skipped: rows.len() - imported,
+        rows,
+    };
+    Ok((plan, report))
+}
+
+/// Column indexes for each mapped field.
+struct Columns {
+    rack: usize,
+    position_u: usize,
+    height_u: Option<usize>,
+    name: usize,
+    model: Option<usize>,
+    serial: Option<usize>,
+    face: Option<usize>,
+}
+
+impl Columns {
+    fn resolve(header: &[String], mapping: &ColumnMapping) -> Result<Self> {
+        let find = |column: &str| {
+            header
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(column.trim()))
+                .ok_or_else(|| Error::UnknownColumn(column.to_owned()))
+        };
+        let optional = |column: &Option<String>| column.as_deref().map(find).transpose();
+        Ok(Self {
+            rack: find(&mapping.rack)?,
+            position_u: find(&mapping.position_u)?,
+            height_u: optional(&mapping.height_u)?,
+            name: find(&mapping.name)?,
+            model: optional(&mapping.model)?,
+            serial: optional(&mapping.serial)?,
+            face: optional(&mapping.face)?,
+        })
+    }
+}
+
+struct Importer<'a> {
+    /// The layout as it would be after the rows so far.
+    layout: Layout,
+    templates: &'a [DeviceTemplate],
+    options: &'a CsvOptions,
+    new_racks: Vec<Rack>,
+    plan: ImportPlan,
+}
+
+impl Importer<'_> {
+    fn row(&mut self, record: &Record, columns: &Columns) -> RowReport {
+        let get = |index: Option<usize>| {
+            index
+                .and_then(|i| record.fields.get(i))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+        let rack = get(Some(columns.rack)).unwrap_or_default().to_owned();
+        let name = get(Some(columns.name)).unwrap_or_default().to_owned();
+        let mut report = RowReport {
+            line: record.line,
+            rack: rack.clone(),
+            name: name.clone(),
+            template_id: None,
+            imported: false,
+            issues: Vec::new(),
+        };
+        let issues = &mut report.issues;
+
+        if rack.is_empty() {
+            issues.push(Issue::Missing { column: "rack" });
+        }
+        if name.is_empty() {
+            issues.push(Issue::Missing { column: "name" });
+        }
+        let position_u = number(get(Some(columns.position_u)), "positionU", issues);
+        let height_value = get(columns.height_u);
+        let height_u = height_value.and_then(|v| number(Some(v), "heightU", issues));
+        let face = match get(columns.face) {
+            None => Face::Front,
+            Some(v) => parse_face(v).unwrap_or_else(|| {
+                issues.push(Issue::InvalidFace {
+                    value: v.to_owned(),
+                });
+                Face::Front
+            }),
+        };
+
+        let model = get(columns.model);
+        let template = model.and_then(|model| match library::match_model(self.templates, model) {
+            ModelMatch::Found(template) => Some(template),
+            ModelMatch::Ambiguous(candidates) => {
+                issues.push(Issue::AmbiguousModel {
+                    model: model.to_owned(),
+                    candidates: candidates.iter().map(|t| t.id.clone()).collect(),
+                });
+                None
+            }
+            ModelMatch::NotFound => {
+                issues.push(Issue::UnknownModel {
+                    model: model.to_owned(),
+                });
+                None
+            }
+        });
+        report.template_id = template.map(|t| t.id.clone());
+        if template.is_none() && height_value.is_none() {
+            issues.push(Issue::Missing { column: "heightU" });
+        }
+
+        let rack_id = (!rack.is_empty())
+            .then(|| self.rack(&rack, issues))
+            .flatten();
+        if issues.iter().any(Issue::is_blocking) {
+            return report;
+        }
+        let (Some(rack_id), Some(position_u)) = (rack_id, position_u) else {
+            return report;
+        };
+
+        let mut device = match template {
+            Some(template) => {
+                let mut device = template.instantiate(name, position_u, face);
+                if let Some(height_u) = height_u {
+                    device.height_u = height_u;
+                }
+                device
+            }
+            None => {
+                let depth_mm = self.layout.rack(rack_id).map_or(0, |r| r.depth_mm);
+                let mut device =
+                    Device::new(name, height_u.unwrap_or(1), position_u, depth_mm, face);
+                device.model = model.map(str::to_owned);
+                device
+            }
+        };
+        device.serial = get(columns.serial).map(str::to_owned);
+
+        match self.layout.add_device(rack_id, device.clone()) {
+            Ok(_) => {
+                self.plan.devices.push((rack_id, device));
+                report.imported = true;
+            }
+            Err(Error::InvalidPlacement(conflicts)) => {
+                issues.push(Issue::Placement { conflicts });
+            }
+            Err(e) => issues.push(Issue::Invalid {
+                message: e.to_string(),
+            }),
+        }
+        report
+    }
+
+    /// The rack a row goes in, creating it if allowed.
+    fn rack(&mut self, name: &str, issues: &mut Vec<Issue>) -> Option<RackId> {
+        if let Some(rack) = self.layout.racks.iter().find(|r| r.name == name) {
+            return Some(rack.id);
+        }
+        if !self.options.create_racks {
+            issues.push(Issue::UnknownRack {
+                rack: name.to_owned(),
+            });
+            return None;
+        }
+        let rack = Rack::new(name, self.options.rack_height_u);
+        let id = rack.id;
+        let index = self.layout.racks.len();
+        if let Err(e) = self.layout.insert_rack(index, rack.clone()) {
+            issues.push(Issue::Invalid {
+                message: e.to_string(),
+            });
+            return None;
+        }
+        self.new_racks.push(rack);
+        Some(id)
+    }
+}
+
+/// Parses a unit number, allowing a `U` before or after it as in "U12" or
+/// "2U".
+fn number(value: Option<&str>, column: &'static str, issues: &mut Vec<Issue>) -> Option<u32> {
+    let Some(value) = value else {
+        issues.push(Issue::Missing { column });
+        return None;
+    };
+    let digits = value.trim_matches(|c: char| c == 'U' || c == 'u' || c.is_whitespace());
+    match digits.parse() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            issues.push(Issue::InvalidNumber {
+                column,
+                value: value.to_owned(),
+            });
+            None
+        }
+    }
+}
+
+fn parse_face(value: &str) -> Option<Face> {
+    match value.to_ascii_lowercase().as_str() {
+        "front" | "f" => Some(Face::Front),
+        "rear" | "back" | "r" | "b" => Some(Face::Rear),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(records: &[Record]) -> Vec<Vec<&str>> {
+        records
+            .iter()
+            .map(|r| r.fields.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    fn mapping() -> ColumnMapping {
+        ColumnMapping {
+            rack: String::from("Rack"),
+            position_u: String::from("U"),
+            height_u: Some(String::from("Height")),
+            name: String::from("Name"),
+            model: None,
+            serial: None,
+            face: Some(String::from("Face")),
+        }
+    }
+
+    fn plan_rows(layout: &Layout, text: &str) -> (ImportPlan, ImportReport) {
+        plan(layout, &[], text, &mapping(), &CsvOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn reads_quoted_delimiters_quotes_and_line_breaks() {
+        let text =
+            "Name,Notes\n\"web-01, web-02\",\"19\"\" shelf\"\n\"multi\nline\",x\nlast,\"\"\n";
+        let records = parse(text, ',').unwrap();
+        assert_eq!(
+            fields(&records),
+            [
+                vec!["Name", "Notes"],
+                vec!["web-01, web-02", "19\" shelf"],
+                vec!["multi\nline", "x"],
+                vec!["last", ""],
+            ]
+        );
+        let lines: Vec<_> = records.iter().map(|r| r.line).collect();
+        assert_eq!(lines, [1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn accepts_crlf_and_a_byte_order_mark() {
+        let text = "\u{feff}Rack,U\r\nA1,1\r\nA2,2";
+        let records = parse(text, ',').unwrap();
+        assert_eq!(
+            fields(&records),
+            [vec!["Rack", "U"], vec!["A1", "1"], vec!["A2", "2"]]
+        );
+        assert_eq!(headers(text, ',').unwrap(), ["Rack", "U"]);
+    }
+
+    #[test]
+    fn splits_on_other_delimiters() {
+        let records = parse("a;\"b;c\"\n", ';').unwrap();
+        assert_eq!(fields(&records), [vec!["a", "b;c"]]);
+    }
+
+    #[test]
+    fn rejects_unclosed_quotes() {
+        let err = parse("Name\n\"web-01\nweb-02\n", ',').unwrap_err();
+        assert!(matches!(err, Error::InvalidCsv { line: 2, .. }));
+    }
+
+    #[test]
+    fn rejects_mappings_to_missing_columns() {
+        let text = "Rack,U,Name\nA1,1,web-01\n";
+        let err = plan(
+            &Layout::default(),
+            &[],
+            text,
+            &mapping(),
+            &CsvOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::UnknownColumn(c) if c == "Height"));
+
+        let mapping = ColumnMapping {
+            rack: String::from("Cabinet"),
+            ..mapping()
+        };
+        let err = plan(
+            &Layout::default(),
+            &[],
+            "Rack,U,Height,Name,Face\n",
+            &mapping,
+            &CsvOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::UnknownColumn(c) if c == "Cabinet"));
+    }
+
+    #[test]
+    fn matches_headers_loosely_and_ignores_blank_rows() {
+        let text = " rack ,u,HEIGHT,name,face\nA1,U1,2U,web-01,rear\n,,,,\n";
+        let (plan, report) = plan_rows(&Layout::default(), text);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.new_racks, ["A1"]);
+        let (_, device) = &plan.devices[0];
+        assert_eq!((device.position_u, device.height_u), (1, 2));
+        assert_eq!(device.face, Face::Rear);
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_values() {
+        let text = "Rack,U,Height,Name,Face\n,x,,,sideways\n";
+        let (plan, report) = plan_rows(&Layout::default(), text);
+        assert!(plan.devices.is_empty());
+        assert_eq!(report.skipped, 1);
+        let issues = &report.rows[0].issues;
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::Missing { column: "rack" })));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::Missing { column: "name" })));
+        assert!(issues.iter().any(|i| matches!(
+            i,
+            Issue::InvalidNumber {
+                column: "positionU",
+                ..
+            }
+        )));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::Missing { column: "heightU" })));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::InvalidFace { value } if value == "sideways")));
+    }
+
+    #[test]
+    fn skips_rows_that_collide_with_existing_devices() {
+        let mut rack = Rack::new("A1", 42);
+        rack.devices
+            .push(Device::new("db-01", 2, 10, 600, Face::Front));
+        let layout = Layout {
+            racks: vec![rack],
+            ..Layout::default()
+        };
+        let text = "Rack,U,Height,Name,Face\nA1,11,1,web-01,front\nA1,20,1,web-02,front\n";
+        let (plan, report) = plan_rows(&layout, text);
+        assert!(!report.rows[0].imported);
+        assert!(matches!(
+            report.rows[0].issues[..],
+            [Issue::Placement { .. }]
+        ));
+        assert!(report.rows[1].imported);
+        assert_eq!(plan.devices.len(), 1);
+        assert!(report.new_racks.is_empty());
+    }
+
+    #[test]
+    fn skips_rows_that_collide_with_earlier_rows() {
+        let text = "Rack,U,Height,Name,Face\nA1,1,2,web-01,\nA1,2,1,web-02,\nA1,5,1,web-03,\n";
+        let (plan, report) = plan_rows(&Layout::default(), text);
+        let imported: Vec<_> = report.rows.iter().map(|r| r.imported).collect();
+        assert_eq!(imported, [true, false, true]);
+        assert_eq!(report.rows[1].line, 3);
+        assert_eq!(plan.devices.len(), 2);
+        // Both rows go in the one rack created for the first.
+        assert_eq!(plan.racks.len(), 1);
+        assert!(plan.devices.iter().all(|(id, _)| *id == plan.racks[0].id));
+    }
+
+    #[test]
+    fn reports_unknown_racks_when_not_creating_them() {
+        let options = CsvOptions {
+            create_racks: false,
+            ..CsvOptions::default()
+        };
+        let text = "Rack,U,Height,Name,Face\nB9,1,1,web-01,\n";
+        let (plan, report) = plan(&Layout::default(), &[], text, &mapping(), &options).unwrap();
+        assert!(plan.racks.is_empty());
+        assert!(matches!(
+            &report.rows[0].issues[..],
+            [Issue::UnknownRack { rack }] if rack == "B9"
+        ));
+    }
+
+    #[test]
+    fn leaves_out_racks_whose_rows_all_failed() {
+        let text = "Rack,U,Height,Name,Face\nA1,41,4,too-tall,\n";
+        let (plan, report) = plan_rows(&Layout::default(), text);
+        assert!(plan.racks.is_empty());
+        assert!(report.new_racks.is_empty());
+        assert_eq!(report.skipped, 1);
+    }
+}
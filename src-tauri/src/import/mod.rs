@@ -0,0 +1,45 @@
+//! Bringing racks and devices in from other tools.
+//!
+//! Importers work out an [`ImportPlan`] against a copy of the layout and
+//! report any problems row by row, so the user can review a dry run before
+//! anything changes. Committing applies the plan as a single undoable edit.
+
+pub mod csv;
+pub mod netbox;
+
+use crate::history::Edit;
+use crate::model::{Device, Layout, Rack, RackId};
+
+/// Racks to create and devices to add, in order.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub racks: Vec<Rack>,
+    pub devices: Vec<(RackId, Device)>,
+}
+
+impl ImportPlan {
+    pub fn is_empty(&self) -> bool {
+        self.racks.is_empty() && self.devices.is_empty()
+    }
+
+    /// The plan as one edit, with new racks added after the existing ones.
+    pub fn into_edit(self, layout: &Layout, label: impl Into<String>) -> Edit {
+        let first = layout.racks.len();
+        let racks = self
+            .racks
+            .into_iter()
+            .enumerate()
+            .map(|(i, rack)| Edit::InsertRack {
+                index: first + i,
+                rack,
+            });
+        let devices = self
+            .devices
+            .into_iter()
+            .map(|(rack_id, device)| Edit::AddDevice { rack_id, device });
+        Edit::Batch {
+            label: label.into(),
+            edits: racks.chain(devices).collect(),
+        }
+    }
+}
@@ -0,0 +1,750 @@
+//! Imports racks and devices from NetBox REST API dumps.
+//!
+//! Each endpoint (`dcim/racks`, `dcim/devices` and `dcim/device-types`) is
+//! saved to its own file, either as the page of results the API returns or
+//! as a plain array of objects. Racks and devices keep their NetBox ids, so
+//! importing a newer dump skips what is already in the project.
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::ImportPlan;
+use crate::error::{Error, Result};
+use crate::library::{self, DeviceTemplate, ModelMatch};
+use crate::model::{Airflow, Category, Device, Face, Layout, Rack, RackId, DEFAULT_DEPTH_MM};
+use crate::validation::Conflict;
+
+pub const RACKS: &str = "dcim/racks";
+pub const DEVICES: &str = "dcim/devices";
+pub const DEVICE_TYPES: &str = "dcim/device-types";
+
+/// A reference to another object, as NetBox nests them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Nested {
+    pub id: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+/// A choice field such as a face or unit, e.g. `{"value": "rear", ...}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Choice {
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetboxRack {
+    pub id: u64,
+    pub name: String,
+    pub u_height: u32,
+    /// Number of the lowest unit, 1 unless configured otherwise.
+    #[serde(default)]
+    pub starting_unit: Option<u32>,
+    /// Whether units are numbered from the top down.
+    #[serde(default)]
+    pub desc_units: bool,
+    #[serde(default)]
+    pub outer_depth: Option<u32>,
+    #[serde(default)]
+    pub outer_unit: Option<Choice>,
+    #[serde(default, deserialize_with = "decimal")]
+    pub weight: Option<f64>,
+    #[serde(default, deserialize_with = "decimal")]
+    pub max_weight: Option<f64>,
+    #[serde(default)]
+    pub weight_unit: Option<Choice>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetboxDevice {
+    pub id: u64,
+    /// Devices may be left unnamed in NetBox.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub device_type: Nested,
+    #[serde(default)]
+    pub role: Option<Nested>,
+    /// What `role` was called before NetBox 3.6.
+    #[serde(default)]
+    pub device_role: Option<Nested>,
+    #[serde(default)]
+    pub rack: Option<Nested>,
+    /// Lowest unit the device occupies, in the rack's own numbering.
+    #[serde(default, deserialize_with = "decimal")]
+    pub position: Option<f64>,
+    #[serde(default)]
+    pub face: Option<Choice>,
+    #[serde(default)]
+    pub serial: String,
+    #[serde(default)]
+    pub airflow: Option<Choice>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetboxDeviceType {
+    pub id: u64,
+    pub manufacturer: Nested,
+    pub model: String,
+    #[serde(deserialize_with = "decimal_required")]
+    pub u_height: f64,
+    #[serde(default = "full_depth")]
+    pub is_full_depth: bool,
+    #[serde(default, deserialize_with = "decimal")]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub weight_unit: Option<Choice>,
+    #[serde(default)]
+    pub airflow: Option<Choice>,
+}
+
+fn full_depth() -> bool {
+    true
+}
+
+/// The three endpoints of one NetBox dump.
+#[derive(Debug, Clone, Default)]
+pub struct Dump {
+    pub racks: Vec<NetboxRack>,
+    pub devices: Vec<NetboxDevice>,
+    pub device_types: Vec<NetboxDeviceType>,
+}
+
+impl Dump {
+    pub fn parse(racks: &str, devices: &str, device_types: &str) -> Result<Self> {
+        Ok(Self {
+            racks: results(RACKS, racks)?,
+            devices: results(DEVICES, devices)?,
+            device_types: results(DEVICE_TYPES, device_types)?,
+        })
+    }
+
+    pub fn rack(&self, id: u64) -> Option<&NetboxRack> {
+        self.racks.iter().find(|r| r.id == id)
+    }
+
+    pub fn device(&self, id: u64) -> Option<&NetboxDevice> {
+        self.devices.iter().find(|d| d.id == id)
+    }
+
+    pub fn device_type(&self, id: u64) -> Option<&NetboxDeviceType> {
+        self.device_types.iter().find(|t| t.id == id)
+    }
+
+    /// The device's name, or the one the importer gives unnamed devices.
+    pub fn device_name(&self, device: &NetboxDevice) -> String {
+        match device.name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => {
+                let model = self
+                    .device_type(device.device_type.id)
+                    .map_or("Device", |t| t.model.as_str());
+                format!("{model} ({})", device.id)
+            }
+        }
+    }
+
+    /// The NetBox rack a device is mounted in and the unit its bottom edge
+    /// sits in, counted from 1 at the bottom as the project does. `None` for
+    /// devices the importer would not place.
+    pub fn placement(&self, device: &NetboxDevice) -> Option<(u64, u32)> {
+        let rack = device.rack.as_ref()?;
+        let height_u = whole(self.device_type(device.device_type.id)?.u_height)?;
+        let position_u = bottom_unit(self.rack(rack.id), whole(device.position?)?, height_u)?;
+        (height_u > 0).then_some((rack.id, position_u))
+    }
+}
+
+impl NetboxDevice {
+    pub fn face(&self) -> Face {
+        match value(&self.face) {
+            Some("rear") => Face::Rear,
+            _ => Face::Front,
+        }
+    }
+
+    pub fn role(&self) -> Option<&Nested> {
+        self.role.as_ref().or(self.device_role.as_ref())
+    }
+}
+
+/// The objects in one endpoint's dump. A page that links to another one is
+/// rejected, since importing it would silently leave devices out.
+fn results<T: DeserializeOwned>(endpoint: &'static str, text: &str) -> Result<Vec<T>> {
+    let invalid = |message: String| Error::InvalidNetbox { endpoint, message };
+    let value: Value = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(mut page) => {
+            if page.get("next").is_some_and(|next| !next.is_null()) {
+                return Err(invalid(String::from(
+                    "the dump is only one page of results; export with ?limit=0 or combine the pages into one array",
+                )));
+            }
+            match page.remove("results") {
+                Some(Value::Array(items)) => items,
+                _ => return Err(invalid(String::from("expected a list of results"))),
+            }
+        }
+        _ => return Err(invalid(String::from("expected a list of results"))),
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            serde_json::from_value(item).map_err(|e| invalid(format!("results[{i}]: {e}")))
+        })
+        .collect()
+}
+
+/// NetBox serializes decimal fields as numbers, but older versions and some
+/// export scripts write them as strings.
+fn decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Decimal {
+        Number(f64),
+        Text(String),
+    }
+    match Option::<Decimal>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Decimal::Number(n)) => Ok(Some(n)),
+        Some(Decimal::Text(s)) => s
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid decimal {s:?}"))),
+    }
+}
+
+fn decimal_required<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    decimal(deserializer)?.ok_or_else(|| serde::de::Error::custom("expected a decimal"))
+}
+
+/// A problem with one device. Devices with any blocking issue are skipped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Issue {
+    /// The device is not mounted in a rack, e.g. it sits in a chassis bay.
+    NotRacked,
+    #[serde(rename_all = "camelCase")]
+    UnknownRack {
+        rack_id: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    UnknownDeviceType {
+        device_type_id: u64,
+    },
+    /// Zero-height and fractional-unit devices cannot be placed in the
+    /// elevation.
+    #[serde(rename_all = "camelCase")]
+    UnsupportedHeight {
+        u_height: f64,
+    },
+    UnsupportedPosition {
+        position: f64,
+    },
+    /// A device with the same NetBox id is already in the project.
+    AlreadyImported,
+    /// No template matches the device type, so the device is imported
+    /// without one.
+    UnknownModel {
+        model: String,
+    },
+    /// Several templates match the device type, so the device is imported
+    /// without one.
+    AmbiguousModel {
+        model: String,
+        candidates: Vec<String>,
+    },
+    Placement {
+        conflicts: Vec<Conflict>,
+    },
+    Invalid {
+        message: String,
+    },
+}
+
+impl Issue {
+    pub fn is_blocking(&self) -> bool {
+        !matches!(
+            self,
+            Issue::UnknownModel { .. } | Issue::AmbiguousModel { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceReport {
+    pub netbox_id: u64,
+    pub name: String,
+    pub rack: Option<String>,
+    pub template_id: Option<String>,
+    pub imported: bool,
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetboxReport {
+    pub devices: Vec<DeviceReport>,
+    /// Racks the import creates.
+    pub new_racks: Vec<String>,
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Works out what importing `dump` into `layout` would do without changing
+/// it. Racks already imported from NetBox are reused; every other rack in
+/// the dump is created, even if it is empty.
+pub fn plan(
+    layout: &Layout,
+    templates: &[DeviceTemplate],
+    dump: &Dump,
+) -> (ImportPlan, NetboxReport) {
+    let mut layout = layout.clone();
+    let mut plan = ImportPlan::default();
+    let mut racks = HashMap::new();
+    for rack in &layout.racks {
+        if let Some(netbox_id) = rack.netbox_id {
+            racks.insert(netbox_id, rack.id);
+        }
+    }
+    for netbox_rack in &dump.racks {
+        if racks.contains_key(&netbox_rack.id) {
+            continue;
+        }
+        let rack = rack(netbox_rack);
+        if layout.insert_rack(layout.racks.len(), rack.clone()).is_ok() {
+            racks.insert(netbox_rack.id, rack.id);
+            plan.racks.push(rack);
+        }
+    }
+
+    let mut importer = Importer {
+        layout,
+        templates,
+        dump,
+        racks,
+        plan,
+    };
+    let devices: Vec<DeviceReport> = dump.devices.iter().map(|d| importer.device(d)).collect();
+    let plan = importer.plan;
+    let imported = devices.iter().filter(|d| d.imported).count();
+    let report = NetboxReport {
+        new_racks: plan.racks.iter().map(|r| r.name.clone()).collect(),
+        imported,
+        skipped: devices.len() - imported,
+        devices,
+    };
+    (plan, report)
+}
+
+fn rack(netbox: &NetboxRack) -> Rack {
+    let mut rack = Rack::new(&netbox.name, netbox.u_height);
+    rack.depth_mm = netbox.outer_depth.filter(|&depth| depth > 0).map_or(
+        DEFAULT_DEPTH_MM,
+        |depth| match value(&netbox.outer_unit) {
+            Some("in") => (f64::from(depth) * 25.4).round() as u32,
+            _ => depth,
+        },
+    );
+    let unit = value(&netbox.weight_unit);
+    rack.weight_kg = netbox.weight.map(|weight| kilograms(weight, unit));
+    rack.load_rating_kg = netbox.max_weight.map(|weight| kilograms(weight, unit));
+    rack.netbox_id = Some(netbox.id);
+    rack
+}
+
+struct Importer<'a> {
+    /// The layout as it would be after the devices so far.
+    layout: Layout,
+    templates: &'a [DeviceTemplate],
+    dump: &'a Dump,
+    /// Project rack for each NetBox rack id.
+    racks: HashMap<u64, RackId>,
+    plan: ImportPlan,
+}
+
+impl Importer<'_> {
+    fn device(&mut self, netbox: &NetboxDevice) -> DeviceReport {
+        let device_type = self.dump.device_type(netbox.device_type.id);
+        let name = self.dump.device_name(netbox);
+        let rack_id = netbox
+            .rack
+            .as_ref()
+            .and_then(|r| self.racks.get(&r.id).copied());
+        let mut report = DeviceReport {
+            netbox_id: netbox.id,
+            name: name.clone(),
+            rack: rack_id
+                .and_then(|id| self.layout.rack(id).ok())
+                .map(|r| r.name.clone()),
+            template_id: None,
+            imported: false,
+            issues: Vec::new(),
+        };
+        let issues = &mut report.issues;
+
+        let existing = self
+            .layout
+            .racks
+            .iter()
+            .flat_map(|r| &r.devices)
+            .any(|d| d.netbox_id == Some(netbox.id));
+        if existing {
+            issues.push(Issue::AlreadyImported);
+            return report;
+        }
+        let Some(device_type) = device_type else {
+            issues.push(Issue::UnknownDeviceType {
+                device_type_id: netbox.device_type.id,
+            });
+            return report;
+        };
+        let Some(height_u) = whole(device_type.u_height).filter(|&h| h > 0) else {
+            issues.push(Issue::UnsupportedHeight {
+                u_height: device_type.u_height,
+            });
+            return report;
+        };
+        let (Some(rack), Some(position)) = (&netbox.rack, netbox.position) else {
+            issues.push(Issue::NotRacked);
+            return report;
+        };
+        let Some(rack_id) = rack_id else {
+            issues.push(Issue::UnknownRack { rack_id: rack.id });
+            return report;
+        };
+        let Some(position_u) =
+            whole(position).and_then(|p| bottom_unit(self.dump.rack(rack.id), p, height_u))
+        else {
+            issues.push(Issue::UnsupportedPosition { position });
+            return report;
+        };
+        let face = netbox.face();
+
+        let model = format!("{} {}", device_type.manufacturer_name(), device_type.model);
+        let template = match library::match_model(self.templates, &model) {
+            ModelMatch::Found(template) => Some(template),
+            ModelMatch::Ambiguous(candidates) => {
+                issues.push(Issue::AmbiguousModel {
+                    model,
+                    candidates: candidates.iter().map(|t| t.id.clone()).collect(),
+                });
+                None
+            }
+            ModelMatch::NotFound => {
+                issues.push(Issue::UnknownModel { model });
+                None
+            }
+        };
+        report.template_id = template.map(|t| t.id.clone());
+
+        let mut device = match template {
+            Some(template) => template.instantiate(name, position_u, face),
+            None => {
+                let rack_depth = self.layout.rack(rack_id).map_or(0, |r| r.depth_mm);
+                let depth_mm = if device_type.is_full_depth {
+                    rack_depth
+                } else {
+                    rack_depth / 2
+                };
+                let mut device = Device::new(name, height_u, position_u, depth_mm, face);
+                device.category = netbox.role().map_or(Category::Other, category);
+                device.manufacturer = Some(device_type.manufacturer_name().to_owned());
+                device.model = Some(device_type.model.clone());
+                device
+            }
+        };
+        device.height_u = height_u;
+        if let Some(weight) = device_type.weight {
+            device.weight_kg = Some(kilograms(weight, value(&device_type.weight_unit)));
+        }
+        let airflow = value(&netbox.airflow)
+            .and_then(airflow)
+            .or_else(|| value(&device_type.airflow).and_then(airflow));
+        if let Some(airflow) = airflow {
+            device.airflow = Some(airflow);
+        }
+        device.serial = Some(netbox.serial.trim())
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+        device.netbox_id = Some(netbox.id);
+
+        match self.layout.add_device(rack_id, device.clone()) {
+            Ok(_) => {
+                self.plan.devices.push((rack_id, device));
+                report.imported = true;
+            }
+            Err(Error::InvalidPlacement(conflicts)) => {
+                issues.push(Issue::Placement { conflicts });
+            }
+            Err(e) => issues.push(Issue::Invalid {
+                message: e.to_string(),
+            }),
+        }
+        report
+    }
+}
+
+impl NetboxDeviceType {
+    pub fn manufacturer_name(&self) -> &str {
+        self.manufacturer
+            .name
+            .as_deref()
+            .or(self.manufacturer.slug.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+fn value(choice: &Option<Choice>) -> Option<&str> {
+    choice.as_ref().map(|c| c.value.as_str())
+}
+
+/// A whole number of units, rejecting half-unit heights and positions.
+fn whole(value: f64) -> Option<u32> {
+    (value.fract() == 0.0 && value >= 0.0 && value <= f64::from(u32::MAX)).then_some(value as u32)
+}
+
+/// Converts a NetBox position, the lowest-numbered unit the device takes in
+/// the rack's own numbering, into the unit its bottom edge sits in counted
+/// from 1 at the bottom.
+fn bottom_unit(rack: Option<&NetboxRack>, position: u32, height_u: u32) -> Option<u32> {
+    let Some(rack) = rack else {
+        return (position > 0).then_some(position);
+    };
+    let offset = position.checked_sub(rack.starting_unit.unwrap_or(1))?;
+    if rack.desc_units {
+        rack.u_height.checked_sub(offset + height_u - 1)
+    } else {
+        Some(offset + 1)
+    }
+    .filter(|&unit| unit > 0)
+}
+
+/// The inverse of [`bottom_unit`]: the position NetBox expects for a device
+/// whose bottom edge sits in `bottom_u`.
+pub(crate) fn netbox_position(rack: Option<&NetboxRack>, bottom_u: u32, height_u: u32) -> u32 {
+    let Some(rack) = rack else {
+        return bottom_u;
+    };
+    let offset = if rack.desc_units {
+        (rack.u_height + 1).saturating_sub(bottom_u + height_u.max(1) - 1)
+    } else {
+        bottom_u
+    };
+    rack.starting_unit.unwrap_or(1) + offset - 1
+}
+
+/// Converts a weight in one of NetBox's weight units to kilograms.
+pub(crate) fn kilograms(weight: f64, unit: Option<&str>) -> f64 {
+    let factor = match unit {
+        Some("g") => 0.001,
+        Some("lb") => 0.453_592_37,
+        Some("oz") => 0.028_349_523,
+        _ => 1.0,
+    };
+    weight * factor
+}
+
+/// Maps one of NetBox's airflow choices onto the directions the project
+/// models.
+pub(crate) fn airflow(value: &str) -> Option<Airflow> {
+    match value {
+        "front-to-rear" => Some(Airflow::FrontToBack),
+        "rear-to-front" => Some(Airflow::BackToFront),
+        "left-to-right" | "right-to-left" | "side-to-rear" => Some(Airflow::SideToSide),
+        _ => None,
+    }
+}
+
+/// Guesses a category from a device role, which NetBox leaves to each site
+/// to define.
+fn category(role: &Nested) -> Category {
+    let role = [role.slug.as_deref(), role.name.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    const ROLES: [(&str, Category); 9] = [
+        ("server", Category::Server),
+        ("switch", Category::Switch),
+        ("router", Category::Switch),
+        ("patch", Category::PatchPanel),
+        ("pdu", Category::Pdu),
+        ("ups", Category::Ups),
+        ("blank", Category::BlankingPanel),
+        ("shelf", Category::Shelf),
+        ("compute", Category::Server),
+    ];
+    ROLES
+        .iter()
+        .find(|(word, _)| role.contains(word))
+        .map_or(Category::Other, |&(_, category)| category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Project;
+
+    fn dump() -> Dump {
+        Dump::parse(
+            include_str!("../../tests/fixtures/netbox/racks.json"),
+            include_str!("../../tests/fixtures/netbox/devices.json"),
+            include_str!("../../tests/fixtures/netbox/device-types.json"),
+        )
+        .unwrap()
+    }
+
+    fn device_report(report: &NetboxReport, netbox_id: u64) -> &DeviceReport {
+        report
+            .devices
+            .iter()
+            .find(|d| d.netbox_id == netbox_id)
+            .unwrap()
+    }
+
+    fn imported(layout: &Layout, netbox_id: u64) -> (&Rack, &Device) {
+        layout
+            .racks
+            .iter()
+            .flat_map(|r| r.devices.iter().map(move |d| (r, d)))
+            .find(|(_, d)| d.netbox_id == Some(netbox_id))
+            .unwrap()
+    }
+
+    fn import(project: &mut Project) -> NetboxReport {
+        let (plan, report) = plan(&project.layout, library::builtin(), &dump());
+        plan.into_edit(&project.layout, "Import")
+            .apply(project)
+            .unwrap();
+        report
+    }
+
+    #[test]
+    fn accepts_pages_and_plain_arrays() {
+        let page = r#"{"count": 1, "next": null, "previous": null, "results": [{"id": 1, "name": "A1", "u_height": 42}]}"#;
+        let array = r#"[{"id": 1, "name": "A1", "u_height": 42}]"#;
+        for text in [page, array] {
+            let racks: Vec<NetboxRack> = results(RACKS, text).unwrap();
+            assert_eq!(racks[0].name, "A1");
+        }
+    }
+
+    #[test]
+    fn rejects_partial_pages() {
+        let text =
+            r#"{"count": 2, "next": "https://netbox/api/dcim/racks/?offset=1", "results": []}"#;
+        assert!(matches!(
+            results::<NetboxRack>(RACKS, text),
+            Err(Error::InvalidNetbox {
+                endpoint: RACKS,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_which_object_is_invalid() {
+        let text = r#"[{"id": 1, "name": "A1", "u_height": 42}, {"id": 2}]"#;
+        match results::<NetboxRack>(RACKS, text) {
+            Err(Error::InvalidNetbox { message, .. }) => assert!(message.starts_with("results[1]")),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn maps_racks() {
+        let mut project = Project::new("Cage 4");
+        let report = import(&mut project);
+        let layout = &project.layout;
+        assert_eq!(report.new_racks, ["A1", "A2", "B1"]);
+        let a1 = &layout.racks[0];
+        assert_eq!((a1.height_u, a1.depth_mm), (42, 1200));
+        assert_eq!(a1.load_rating_kg, Some(1000.0));
+        let b1 = &layout.racks[2];
+        assert_eq!((b1.height_u, b1.depth_mm), (24, 762));
+        assert!(b1.devices.is_empty());
+    }
+
+    #[test]
+    fn maps_devices_with_face_and_position() {
+        let mut project = Project::new("Cage 4");
+        let report = import(&mut project);
+        let layout = &project.layout;
+
+        let (rack, server) = imported(layout, 101);
+        assert_eq!(rack.name, "A1");
+        assert_eq!((server.position_u, server.height_u), (10, 1));
+        assert_eq!(server.face, Face::Front);
+        assert_eq!(server.template_id.as_deref(), Some("builtin/server-1u"));
+        assert_eq!(server.serial.as_deref(), Some("SN-0101"));
+
+        let (_, switch) = imported(layout, 102);
+        assert_eq!(switch.face, Face::Rear);
+        assert_eq!(switch.position_u, 42);
+        assert_eq!(switch.airflow, Some(Airflow::BackToFront));
+        assert!(device_report(&report, 102)
+            .issues
+            .iter()
+            .any(|i| matches!(i, Issue::UnknownModel { .. })));
+        assert_eq!(switch.category, Category::Switch);
+        assert_eq!(switch.manufacturer.as_deref(), Some("Arista"));
+        assert_eq!(switch.depth_mm, 600);
+        assert!((switch.weight_kg.unwrap() - 9.071_847_4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn converts_descending_units() {
+        let mut project = Project::new("Cage 4");
+        import(&mut project);
+        let layout = &project.layout;
+        // U1-U2 counted from the top of a 42U rack.
+        let (rack, device) = imported(layout, 103);
+        assert_eq!(rack.name, "A2");
+        assert_eq!((device.position_u, device.height_u), (41, 2));
+        assert_eq!(device.name, "PowerEdge R750 (103)");
+    }
+
+    #[test]
+    fn skips_devices_that_cannot_be_placed() {
+        let mut project = Project::new("Cage 4");
+        let report = import(&mut project);
+        assert!(matches!(
+            device_report(&report, 104).issues[..],
+            [Issue::NotRacked]
+        ));
+        assert!(matches!(
+            device_report(&report, 105).issues[..],
+            [Issue::UnsupportedHeight { .. }]
+        ));
+        assert!(matches!(
+            device_report(&report, 106).issues[..],
+            [.., Issue::Placement { .. }]
+        ));
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped, 3);
+    }
+
+    #[test]
+    fn reimporting_skips_what_is_already_there() {
+        let mut project = Project::new("Cage 4");
+        import(&mut project);
+        let layout = &project.layout;
+        let (plan, report) = plan(layout, library::builtin(), &dump());
+        assert!(plan.is_empty());
+        assert!(report.new_racks.is_empty());
+        assert!(matches!(
+            device_report(&report, 101).issues[..],
+            [Issue::AlreadyImported]
+        ));
+    }
+}
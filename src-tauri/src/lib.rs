@@ -1,14 +1,107 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-// #[tauri::command]
-// fn greet(name: &str) -> String {
-//     format!("Hello, {}! You've been greeted from Rust!", name)
-// }
+pub mod analysis;
+mod commands;
+pub mod error;
+pub mod export;
+pub mod history;
+pub mod import;
+pub mod library;
+pub mod model;
+pub mod project;
+pub mod state;
+pub mod sync;
+pub mod validation;
+
+use library::user::UserLibrary;
+use state::AppState;
+use tauri::{Manager, WindowEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // tauri::Builder::default()
-    //     .plugin(tauri_plugin_opener::init())
-    //     .invoke_handler(tauri::generate_handler![greet])
-    //     .run(tauri::generate_context!())
-    //     .expect("error while running tauri application");
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(AppState::new(UserLibrary::new(&data_dir)));
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                let detached = window.state::<AppState>().close_window(window.label());
+                state::close_windows(window, &detached);
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::layout::get_layout,
+            commands::layout::create_rack,
+            commands::layout::rename_rack,
+            commands::layout::set_rack_position,
+            commands::layout::delete_rack,
+            commands::layout::add_device,
+            commands::layout::move_device,
+            commands::layout::remove_device,
+            commands::layout::set_serial,
+            commands::layout::check_placement,
+            commands::cabling::connect_ports,
+            commands::cabling::disconnect_ports,
+            commands::cabling::set_cable_color,
+            commands::cabling::set_transceiver,
+            commands::cabling::add_port,
+            commands::cabling::remove_port,
+            commands::cabling::cable_report,
+            commands::export::export_elevation_svg,
+            commands::export::export_elevation_png,
+            commands::export::export_report_pdf,
+            commands::export::export_workbook_xlsx,
+            commands::export::export_devices_csv,
+            commands::export::export_cables_csv,
+            commands::export::export_outlets_csv,
+            commands::floor::set_room,
+            commands::floor::place_rack_in_row,
+            commands::floor::floor_report,
+            commands::history::undo,
+            commands::history::redo,
+            commands::history::history,
+            commands::import::csv_headers,
+            commands::import::preview_csv_import,
+            commands::import::import_csv,
+            commands::import::preview_netbox_import,
+            commands::import::import_netbox,
+            commands::netbox::export_netbox_json,
+            commands::netbox::netbox_diff,
+            commands::netbox::push_to_netbox,
+            commands::library::list_templates,
+            commands::library::search_templates,
+            commands::library::create_template,
+            commands::library::edit_template,
+            commands::library::delete_template,
+            commands::library::import_templates,
+            commands::library::import_devicetype_library,
+            commands::library::export_templates,
+            commands::library::add_device_from_template,
+            commands::power::add_pdu,
+            commands::power::remove_pdu,
+            commands::power::set_pdu_feed,
+            commands::power::connect_power,
+            commands::power::disconnect_power,
+            commands::power::power_report,
+            commands::power::failover_report,
+            commands::project::new_project,
+            commands::project::get_project,
+            commands::project::set_project_metadata,
+            commands::project::open_project,
+            commands::project::save_project,
+            commands::project::save_project_as,
+            commands::thermal::set_airflow,
+            commands::thermal::set_cold_aisle,
+            commands::thermal::thermal_report,
+            commands::weight::set_device_weight,
+            commands::weight::set_rack_weight,
+            commands::weight::set_load_rating,
+            commands::weight::weight_report,
+            commands::window::window_info,
+            commands::window::open_window,
+            commands::window::open_rack_window,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
 }
@@ -0,0 +1,420 @@
+//! Reads device definitions from the NetBox community devicetype-library.
+//!
+//! The library keeps one YAML file per model under
+//! `device-types/<Manufacturer>/`. Each file becomes a template: its
+//! interfaces, console ports, power ports and outlets are grouped into port
+//! groups where their names number on from each other, and components with
+//! no matching port kind, such as LAGs or CFP cages, are left out. The
+//! library only records whether a device is full depth, so templates get a
+//! typical depth for either case.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{DeviceTemplate, PortSpec};
+use crate::error::{Error, Result};
+use crate::import::netbox::{airflow, kilograms};
+use crate::model::{Category, PortKind, PowerDraw};
+
+/// Depth given to full-depth devices.
+const FULL_DEPTH_MM: u32 = 750;
+/// Depth given to everything else, leaving room for a device on the other
+/// face of a standard rack.
+const HALF_DEPTH_MM: u32 = 400;
+
+/// Devices with at least this many network interfaces are taken to be
+/// switches rather than servers.
+const SWITCH_INTERFACES: usize = 16;
+
+#[derive(Debug, Deserialize)]
+struct DeviceType {
+    manufacturer: String,
+    model: String,
+    #[serde(default)]
+    slug: Option<String>,
+    #[serde(default = "one")]
+    u_height: f64,
+    #[serde(default = "yes")]
+    is_full_depth: bool,
+    #[serde(default)]
+    airflow: Option<String>,
+    #[serde(default)]
+    weight: Option<f64>,
+    #[serde(default)]
+    weight_unit: Option<String>,
+    #[serde(default)]
+    interfaces: Vec<Component>,
+    #[serde(default, rename = "console-ports")]
+    console_ports: Vec<Component>,
+    #[serde(default, rename = "console-server-ports")]
+    console_server_ports: Vec<Component>,
+    #[serde(default, rename = "power-ports")]
+    power_ports: Vec<PowerPort>,
+    #[serde(default, rename = "power-outlets")]
+    power_outlets: Vec<Component>,
+    #[serde(default, rename = "front-ports")]
+    front_ports: Vec<Component>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct Component {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PowerPort {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    maximum_draw: Option<u32>,
+    #[serde(default)]
+    allocated_draw: Option<u32>,
+}
+
+/// Converts one device type definition into a template. The template's id
+/// is the device type's slug; the user library gives it a proper one when it
+/// is stored.
+pub fn parse(yaml: &str) -> Result<DeviceTemplate> {
+    let device_type: DeviceType =
+        serde_yaml_ng::from_str(yaml).map_err(|e| invalid(String::new(), e.to_string()))?;
+    let slug = device_type.slug.clone().unwrap_or_else(|| {
+        format!("{}-{}", device_type.manufacturer, device_type.model)
+            .to_lowercase()
+            .replace(' ', "-")
+    });
+    if device_type.u_height.fract() != 0.0 || device_type.u_height < 1.0 {
+        return Err(invalid(
+            String::from("u_height"),
+            format!("{}U devices cannot be racked", device_type.u_height),
+        ));
+    }
+
+    let interfaces: Vec<_> = device_type
+        .interfaces
+        .iter()
+        .filter_map(|i| interface_kind(&i.kind).map(|kind| (i.name.as_str(), kind)))
+        .collect();
+    let category = category(&device_type, interfaces.len());
+    let mut ports = group(interfaces);
+    ports.extend(group(
+        device_type
+            .console_ports
+            .iter()
+            .chain(&device_type.console_server_ports)
+            .map(|c| (c.name.as_str(), PortKind::Console)),
+    ));
+    ports.extend(group(
+        device_type
+            .front_ports
+            .iter()
+            .filter(|p| p.kind == "8p8c")
+            .map(|p| (p.name.as_str(), PortKind::Rj45)),
+    ));
+    ports.extend(group(device_type.power_ports.iter().filter_map(|p| {
+        power_kind(&p.kind).map(|kind| (p.name.as_str(), kind))
+    })));
+    ports.extend(group(device_type.power_outlets.iter().filter_map(|o| {
+        power_kind(&o.kind).map(|kind| (o.name.as_str(), kind))
+    })));
+
+    Ok(DeviceTemplate {
+        id: slug,
+        manufacturer: device_type.manufacturer.trim().to_owned(),
+        model: device_type.model.trim().to_owned(),
+        category,
+        height_u: device_type.u_height as u32,
+        depth_mm: if device_type.is_full_depth {
+            FULL_DEPTH_MM
+        } else {
+            HALF_DEPTH_MM
+        },
+        weight_kg: device_type.weight.map_or(0.0, |weight| {
+            kilograms(weight, device_type.weight_unit.as_deref())
+        }),
+        power: power(&device_type.power_ports),
+        airflow: device_type.airflow.as_deref().and_then(airflow),
+        ports,
+    })
+}
+
+fn invalid(path: String, message: String) -> Error {
+    Error::InvalidLibrary { path, message }
+}
+
+/// A file that could not be imported.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Templates read from a library checkout, in path order.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    pub templates: Vec<DeviceTemplate>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Reads every `.yaml` and `.yml` file under `dir`. Pointing at the root of a
+/// checkout only reads its `device-types` directory, leaving out module
+/// types and other definitions. Files that do not parse or describe devices
+/// that cannot be racked are skipped and reported.
+pub fn read_dir(dir: &Path) -> Result<Batch> {
+    let device_types = dir.join("device-types");
+    let root = if device_types.is_dir() {
+        device_types
+    } else {
+        dir.to_owned()
+    };
+    let mut files = Vec::new();
+    collect(&root, &mut files)?;
+    files.sort();
+
+    let mut batch = Batch::default();
+    for path in files {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| Error::io(&path, e))
+            .and_then(|yaml| parse(&yaml))
+            .and_then(|template| super::user::validate(&template).map(|_| template));
+        match parsed {
+            Ok(template) => batch.templates.push(template),
+            Err(e) => batch.skipped.push(SkippedFile {
+                message: e.to_string(),
+                path,
+            }),
+        }
+    }
+    Ok(batch)
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(|e| Error::io(dir, e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| Error::io(dir, e))?.path();
+        if path.is_dir() {
+            collect(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Groups ports into runs that share a kind and a name prefix and number on
+/// from each other, e.g. `Ethernet1` to `Ethernet48`. Names without a
+/// number become a group of one.
+fn group<'a>(ports: impl IntoIterator<Item = (&'a str, PortKind)>) -> Vec<PortSpec> {
+    let mut groups: Vec<PortSpec> = Vec::new();
+    for (name, kind) in ports {
+        let (prefix, number) = split_number(name.trim());
+        if let (Some(last), Some(number)) = (groups.last_mut(), number) {
+            if last.kind == kind && last.name == prefix && last.first + last.count == number {
+                last.count += 1;
+                continue;
+            }
+        }
+        groups.push(PortSpec {
+            name: prefix.to_owned(),
+            kind,
+            count: 1,
+            first: number.unwrap_or(1),
+        });
+    }
+    groups
+}
+
+/// Splits a trailing port number off a name. Numbers with leading zeros are
+/// left in the prefix, since numbering on from them would change the names.
+fn split_number(name: &str) -> (&str, Option<u32>) {
+    let digits = name.len() - name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let (prefix, number) = name.split_at(name.len() - digits);
+    if number.is_empty() || (number.len() > 1 && number.starts_with('0')) {
+        return (name, None);
+    }
+    match number.parse() {
+        Ok(n) => (prefix, Some(n)),
+        Err(_) => (name, None),
+    }
+}
+
+/// The port kind for a NetBox interface type, e.g. `10gbase-x-sfpp`.
+fn interface_kind(kind: &str) -> Option<PortKind> {
+    if kind.ends_with("base-t") || kind.ends_with("base-tx") {
+        Some(PortKind::Rj45)
+    } else if kind.ends_with("-sfp") {
+        Some(PortKind::Sfp)
+    } else if kind.ends_with("-sfpp") || kind.ends_with("-sfp28") || kind.ends_with("-sfp56") {
+        Some(PortKind::SfpPlus)
+    } else if kind.contains("-qsfp") {
+        Some(PortKind::Qsfp28)
+    } else {
+        None
+    }
+}
+
+fn power_kind(kind: &str) -> Option<PortKind> {
+    match kind {
+        "iec-60320-c13" => Some(PortKind::C13),
+        "iec-60320-c14" => Some(PortKind::C14),
+        "iec-60320-c19" => Some(PortKind::C19),
+        "iec-60320-c20" => Some(PortKind::C20),
+        _ => None,
+    }
+}
+
+/// Draw summed over the power ports. For redundant supplies this overstates
+/// what the device draws, which errs on the safe side when planning power.
+fn power(ports: &[PowerPort]) -> Option<PowerDraw> {
+    let nameplate_w: u32 = ports.iter().filter_map(|p| p.maximum_draw).sum();
+    if nameplate_w == 0 {
+        return None;
+    }
+    let allocated: u32 = ports.iter().filter_map(|p| p.allocated_draw).sum();
+    Some(PowerDraw {
+        nameplate_w,
+        typical_w: if allocated == 0 {
+            nameplate_w
+        } else {
+            allocated.min(nameplate_w)
+        },
+    })
+}
+
+/// Guesses a category, which the library does not record.
+fn category(device_type: &DeviceType, interfaces: usize) -> Category {
+    let model = device_type.model.to_lowercase();
+    if model.contains("ups") {
+        Category::Ups
+    } else if !device_type.power_outlets.is_empty() {
+        Category::Pdu
+    } else if !device_type.front_ports.is_empty() && interfaces == 0 {
+        Category::PatchPanel
+    } else if interfaces >= SWITCH_INTERFACES {
+        Category::Switch
+    } else if interfaces > 0 {
+        Category::Server
+    } else {
+        Category::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Airflow;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/devicetype-library")
+    }
+
+    fn read(path: &str) -> DeviceTemplate {
+        let yaml = fs::read_to_string(fixtures().join("device-types").join(path)).unwrap();
+        parse(&yaml).unwrap()
+    }
+
+    #[test]
+    fn reads_a_server() {
+        let template = read("Dell/poweredge-r650.yaml");
+        assert_eq!(template.id, "dell-poweredge-r650");
+        assert_eq!(
+            (template.manufacturer.as_str(), template.model.as_str()),
+            ("Dell", "PowerEdge R650")
+        );
+        assert_eq!(template.category, Category::Server);
+        assert_eq!((template.height_u, template.depth_mm), (1, FULL_DEPTH_MM));
+        assert_eq!(template.airflow, Some(Airflow::FrontToBack));
+        assert_eq!(
+            template.power,
+            Some(PowerDraw {
+                nameplate_w: 2800,
+                typical_w: 700
+            })
+        );
+        let names: Vec<_> = template
+            .ports
+            .iter()
+            .flat_map(PortSpec::expand)
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["iDRAC1", "eno1", "eno2", "ens0", "ens1", "Serial1", "PSU1", "PSU2"]
+        );
+    }
+
+    #[test]
+    fn groups_numbered_ports() {
+        let template = read("Arista/dcs-7050sx3-48yc8.yaml");
+        assert_eq!(template.category, Category::Switch);
+        assert_eq!(template.depth_mm, HALF_DEPTH_MM);
+        assert_eq!(template.airflow, Some(Airflow::BackToFront));
+        assert!((template.weight_kg - 9.071_847_4).abs() < 1e-6);
+        let groups: Vec<_> = template
+            .ports
+            .iter()
+            .map(|p| (p.name.as_str(), p.kind, p.first, p.count))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("Ethernet", PortKind::SfpPlus, 1, 48),
+                ("Ethernet", PortKind::Qsfp28, 49, 8),
+                ("Management", PortKind::Rj45, 1, 1),
+                ("Console", PortKind::Console, 1, 1),
+                ("PSU", PortKind::C14, 1, 2),
+            ]
+        );
+        assert!(super::super::user::validate(&template).is_ok());
+    }
+
+    #[test]
+    fn reads_pdu_outlets() {
+        let template = read("APC/ap7900b.yaml");
+        assert_eq!(template.category, Category::Pdu);
+        assert_eq!(template.power, None);
+        let kinds: Vec<_> = template.ports.iter().map(|p| (p.kind, p.count)).collect();
+        assert_eq!(kinds, [(PortKind::Rj45, 1), (PortKind::C13, 8)]);
+    }
+
+    #[test]
+    fn keeps_leading_zeros_in_names() {
+        assert_eq!(split_number("Gi1/0/24"), ("Gi1/0/", Some(24)));
+        assert_eq!(split_number("eth0"), ("eth", Some(0)));
+        assert_eq!(split_number("port01"), ("port01", None));
+        assert_eq!(split_number("iDRAC"), ("iDRAC", None));
+    }
+
+    #[test]
+    fn reads_a_checkout_and_skips_what_cannot_be_racked() {
+        let batch = read_dir(&fixtures()).unwrap();
+        let models: Vec<_> = batch.templates.iter().map(|t| t.model.as_str()).collect();
+        assert_eq!(models, ["AP7900B", "DCS-7050SX3-48YC8", "PowerEdge R650"]);
+        assert_eq!(batch.skipped.len(), 1);
+        assert!(batch.skipped[0].path.ends_with("APC/ap8868.yaml"));
+    }
+
+    #[test]
+    fn rejects_invalid_yaml() {
+        assert!(matches!(
+            parse("manufacturer: [unclosed"),
+            Err(Error::InvalidLibrary { .. })
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+//! Device templates that devices are instantiated from.
+//!
+//! Templates come from the built-in catalog or the user's own library; user
+//! template ids start with [`user::ID_PREFIX`]. The user's library can be
+//! filled from a checkout of the NetBox community devicetype-library, see
+//! [`devicetype`].
+
+pub mod devicetype;
+pub mod user;
+
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::model::{Airflow, Category, Device, Face, Port, PortKind, PowerDraw};
+
+use self::user::UserLibrary;
+
+/// The catalog shipped with the app, embedded at compile time.
+static BUILTIN: LazyLock<Vec<DeviceTemplate>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("catalog.json")).expect("built-in catalog is valid")
+});
+
+/// A group of identical ports, e.g. 48 RJ45 ports named `1` to `48`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortSpec {
+    /// Name prefix; ports are numbered after it.
+    pub name: String,
+    pub kind: PortKind,
+    pub count: u32,
+    /// Number of the first port, for groups that start at 0 or continue
+    /// another group with the same prefix.
+    #[serde(default = "first_port", skip_serializing_if = "is_first_port")]
+    pub first: u32,
+}
+
+fn first_port() -> u32 {
+    1
+}
+
+fn is_first_port(first: &u32) -> bool {
+    *first == 1
+}
+
+impl PortSpec {
+    /// The individual ports in the group.
+    pub fn expand(&self) -> impl Iterator<Item = Port> + '_ {
+        (self.first..self.first.saturating_add(self.count))
+            .map(|i| Port::new(format!("{}{i}", self.name), self.kind))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceTemplate {
+    pub id: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub category: Category,
+    pub height_u: u32,
+    pub depth_mm: u32,
+    pub weight_kg: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerDraw>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airflow: Option<Airflow>,
+    #[serde(default)]
+    pub ports: Vec<PortSpec>,
+}
+
+impl DeviceTemplate {
+    /// Whether every word of `query` appears in the template's manufacturer,
+    /// model or id, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = format!("{} {} {}", self.manufacturer, self.model, self.id).to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+
+    pub fn instantiate(&self, name: impl Into<String>, position_u: u32, face: Face) -> Device {
+        let mut device = Device::new(name, self.height_u, position_u, self.depth_mm, face);
+        device.category = self.category;
+        device.manufacturer = Some(self.manufacturer.clone());
+        device.model = Some(self.model.clone());
+        device.power = self.power.clone();
+        device.weight_kg = Some(self.weight_kg);
+        device.airflow = self.airflow;
+        device.ports = self.ports.iter().flat_map(PortSpec::expand).collect();
+        device.template_id = Some(self.id.clone());
+        device
+    }
+}
+
+pub fn builtin() -> &'static [DeviceTemplate] {
+    &BUILTIN
+}
+
+/// Templates in `templates` matching `query`, optionally limited to one
+/// category. An empty query matches everything.
+pub fn search<'a>(
+    templates: impl IntoIterator<Item = &'a DeviceTemplate>,
+    query: &str,
+    category: Option<Category>,
+) -> Vec<&'a DeviceTemplate> {
+    templates
+        .into_iter()
+        .filter(|t| category.is_none_or(|c| t.category == c))
+        .filter(|t| t.matches(query))
+        .collect()
+}
+
+/// The result of looking up a template by a free-form model name.
+#[derive(Debug)]
+pub enum ModelMatch<'a> {
+    Found(&'a DeviceTemplate),
+    Ambiguous(Vec<&'a DeviceTemplate>),
+    NotFound,
+}
+
+/// Finds the template a model name from another system refers to.
+///
+/// An exact id, model or "manufacturer model" match wins, ignoring case;
+/// otherwise the name is used as a search query and must match exactly one
+/// template.
+pub fn match_model<'a>(templates: &'a [DeviceTemplate], model: &str) -> ModelMatch<'a> {
+    let model = model.trim();
+    if model.is_empty() {
+        return ModelMatch::NotFound;
+    }
+    let exact: Vec<_> = templates
+        .iter()
+        .filter(|t| {
+            t.id.eq_ignore_ascii_case(model)
+                || t.model.eq_ignore_ascii_case(model)
+                || format!("{} {}", t.manufacturer, t.model).eq_ignore_ascii_case(model)
+        })
+        .collect();
+    let candidates = if exact.is_empty() {
+        search(templates, model, None)
+    } else {
+        exact
+    };
+    match candidates.as_slice() {
+        [] => ModelMatch::NotFound,
+        [template] => ModelMatch::Found(template),
+        _ => ModelMatch::Ambiguous(candidates),
+    }
+}
+
+/// The built-in catalog followed by the user's library.
+pub fn all(user: &UserLibrary) -> Result<Vec<DeviceTemplate>> {
+    let mut templates = builtin().to_vec();
+    templates.extend(user.load()?);
+    Ok(templates)
+}
+
+pub fn find(user: &UserLibrary, id: &str) -> Result<DeviceTemplate> {
+    if id.starts_with(user::ID_PREFIX) {
+        return user.get(id);
+    }
+    builtin()
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .ok_or_else(|| Error::TemplateNotFound(id.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn builtin_catalog_is_valid() {
+        let templates = builtin();
+        assert!(!templates.is_empty());
+        let mut ids = HashSet::new();
+        for template in templates {
+            user::validate(template).unwrap();
+            assert!(!template.id.starts_with(user::ID_PREFIX), "{}", template.id);
+            assert!(ids.insert(&template.id), "duplicate id {}", template.id);
+        }
+    }
+}
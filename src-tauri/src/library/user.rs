@@ -0,0 +1,431 @@
+//! Custom device templates stored in the app data directory.
+//!
+//! The library lives in a single JSON file shared by every window and every
+//! running instance of the app. Reads take a shared lock and writes an
+//! exclusive one on a sidecar lock file, and each write re-reads the file
+//! under the lock, so concurrent edits are applied one after another instead
+//! of overwriting each other.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use super::DeviceTemplate;
+use crate::error::{Error, Result};
+
+const FILE_NAME: &str = "device-library.json";
+/// Prefix of every user template id, keeping them apart from built-in ids.
+pub const ID_PREFIX: &str = "user/";
+/// Most ports a single port group may expand to. Every port becomes an entry
+/// on each device placed from the template, so a typo like `count: 4800000`
+/// would otherwise stall the app.
+pub const MAX_PORT_COUNT: u32 = 1024;
+
+#[derive(Debug)]
+pub struct UserLibrary {
+    path: PathBuf,
+}
+
+impl UserLibrary {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(FILE_NAME),
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<DeviceTemplate>> {
+        let _lock = self.lock(false)?;
+        self.read()
+    }
+
+    pub fn get(&self, id: &str) -> Result<DeviceTemplate> {
+        self.load()?
+            .into_iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| Error::TemplateNotFound(id.to_owned()))
+    }
+
+    /// Adds a new template, giving it a fresh id.
+    pub fn create(&self, mut template: DeviceTemplate) -> Result<DeviceTemplate> {
+        template.id = new_id();
+        validate(&template)?;
+        self.update(|templates| {
+            templates.push(template.clone());
+            Ok(template)
+        })
+    }
+
+    /// Replaces the template with the same id.
+    pub fn edit(&self, template: DeviceTemplate) -> Result<DeviceTemplate> {
+        validate(&template)?;
+        self.update(|templates| {
+            let existing = templates
+                .iter_mut()
+                .find(|t| t.id == template.id)
+                .ok_or_else(|| Error::TemplateNotFound(template.id.clone()))?;
+            *existing = template.clone();
+            Ok(template)
+        })
+    }
+
+    pub fn delete(&self, id: &str) -> Result<()> {
+        self.update(|templates| {
+            let index = templates
+                .iter()
+                .position(|t| t.id == id)
+                .ok_or_else(|| Error::TemplateNotFound(id.to_owned()))?;
+            templates.remove(index);
+            Ok(())
+        })
+    }
+
+    /// Merges templates from an exported library file.
+    ///
+    /// User template ids are kept, so projects referring to them still resolve
+    /// on another machine, and replace any existing entry with the same id.
+    /// Templates with any other id, such as copies of built-in ones, are added
+    /// under a fresh id. Nothing is imported unless every template is valid.
+    pub fn import(&self, path: &Path) -> Result<Vec<DeviceTemplate>> {
+        let mut imported = read_templates(path)?;
+        for template in &imported {
+            validate(template)?;
+        }
+        self.update(|templates| {
+            for template in &mut imported {
+                if !template.id.starts_with(ID_PREFIX) {
+                    template.id = new_id();
+                }
+                match templates.iter_mut().find(|t| t.id == template.id) {
+                    Some(existing) => *existing = template.clone(),
+                    None => templates.push(template.clone()),
+                }
+            }
+            Ok(imported)
+        })
+    }
+
+    /// Adds templates read from another catalog, such as the devicetype
+    /// library, replacing any with the same manufacturer and model so that
+    /// importing a newer copy updates them in place. Returns how many were
+    /// added and how many replaced.
+    pub fn merge(&self, incoming: Vec<DeviceTemplate>) -> Result<(usize, usize)> {
+        for template in &incoming {
+            validate(template)?;
+        }
+        self.update(|templates| {
+            let (mut added, mut replaced) = (0, 0);
+            for mut template in incoming {
+                let existing = templates.iter_mut().find(|t| {
+                    t.manufacturer.eq_ignore_ascii_case(&template.manufacturer)
+                        && t.model.eq_ignore_ascii_case(&template.model)
+                });
+                match existing {
+                    Some(existing) => {
+                        template.id = existing.id.clone();
+                        *existing = template;
+                        replaced += 1;
+                    }
+                    None => {
+                        template.id = new_id();
+                        templates.push(template);
+                        added += 1;
+                    }
+                }
+            }
+            Ok((added, replaced))
+        })
+    }
+
+    /// Writes the templates with the given ids, or the whole library, to
+    /// `path` in the format [`import`](Self::import) reads.
+    pub fn export(&self, path: &Path, ids: Option<&[String]>) -> Result<usize> {
+        let templates: Vec<_> = self
+            .load()?
+            .into_iter()
+            .filter(|t| ids.is_none_or(|ids| ids.contains(&t.id)))
+            .collect();
+        write_templates(path, &templates)?;
+        Ok(templates.len())
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut Vec<DeviceTemplate>) -> Result<T>) -> Result<T> {
+        let _lock = self.lock(true)?;
+        let mut templates = self.read()?;
+        let result = f(&mut templates)?;
+        write_templates(&self.path, &templates)?;
+        Ok(result)
+    }
+
+    fn read(&self) -> Result<Vec<DeviceTemplate>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        read_templates(&self.path)
+    }
+
+    /// Locks the library until the returned file is dropped.
+    fn lock(&self, exclusive: bool) -> Result<File> {
+        let path = self.path.with_extension("lock");
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+        }
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| Error::io(&path, e))?;
+        if exclusive {
+            file.lock()
+        } else {
+            file.lock_shared()
+        }
+        .map_err(|e| Error::io(&path, e))?;
+        Ok(file)
+    }
+}
+
+fn new_id() -> String {
+    format!("{ID_PREFIX}{}", Uuid::new_v4())
+}
+
+fn read_templates(path: &Path) -> Result<Vec<DeviceTemplate>> {
+    let json = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let deserializer = &mut serde_json::Deserializer::from_str(&json);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| Error::InvalidLibrary {
+        path: e.path().to_string(),
+        message: e.into_inner().to_string(),
+    })
+}
+
+fn write_templates(path: &Path, templates: &[DeviceTemplate]) -> Result<()> {
+    let json = serde_json::to_string_pretty(templates).expect("templates are always serializable");
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| Error::io(&tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| Error::io(path, e))
+}
+
+/// Checks a template's dimensions and ports before it is stored.
+pub fn validate(template: &DeviceTemplate) -> Result<()> {
+    let invalid = |field: &str, message: &str| {
+        Err(Error::InvalidTemplate {
+            id: template.id.clone(),
+            field: field.to_owned(),
+            message: message.to_owned(),
+        })
+    };
+    if template.model.trim().is_empty() {
+        return invalid("model", "must not be empty");
+    }
+    if template.height_u == 0 {
+        return invalid("heightU", "must be at least 1U");
+    }
+    if template.depth_mm == 0 {
+        return invalid("depthMm", "must be greater than zero");
+    }
+    if !template.weight_kg.is_finite() || template.weight_kg < 0.0 {
+        return invalid("weightKg", "must be a non-negative number");
+    }
+    if let Some(power) = &template.power {
+        if power.typical_w > power.nameplate_w {
+            return invalid("power.typicalW", "must not exceed the nameplate rating");
+        }
+    }
+    let mut names = HashSet::new();
+    for (i, group) in template.ports.iter().enumerate() {
+        if group.count == 0 {
+            return invalid(&format!("ports[{i}].count"), "must be at least 1");
+        }
+        if group.count > MAX_PORT_COUNT {
+            return invalid(
+                &format!("ports[{i}].count"),
+                &format!("must be at most {MAX_PORT_COUNT}"),
+            );
+        }
+        if group.expand().any(|port| !names.insert(port.name)) {
+            return invalid(&format!("ports[{i}].name"), "duplicates another port group");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::PortSpec;
+    use crate::model::{Category, PortKind, PowerDraw};
+
+    fn temp_library() -> (PathBuf, UserLibrary) {
+        let dir = std::env::temp_dir().join(format!("rack-designer-{}", Uuid::new_v4()));
+        let library = UserLibrary::new(&dir);
+        (dir, library)
+    }
+
+    fn template(model: &str) -> DeviceTemplate {
+        DeviceTemplate {
+            id: String::new(),
+            manufacturer: String::from("Acme"),
+            model: model.to_owned(),
+            category: Category::Switch,
+            height_u: 1,
+            depth_mm: 300,
+            weight_kg: 4.5,
+            power: None,
+            airflow: None,
+            ports: vec![PortSpec {
+                name: String::from("eth"),
+                kind: PortKind::Rj45,
+                count: 48,
+                first: 1,
+            }],
+        }
+    }
+
+    fn invalid_field(result: Result<()>) -> String {
+        match result {
+            Err(Error::InvalidTemplate { field, .. }) => field,
+            other => panic!("expected an invalid template, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn creates_templates_under_user_ids() {
+        let (dir, library) = temp_library();
+        let created = library.create(template("SW-48")).unwrap();
+        assert!(created.id.starts_with(ID_PREFIX));
+        assert_eq!(library.get(&created.id).unwrap(), created);
+        assert!(!dir.join("device-library.json.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn edits_and_deletes_templates() {
+        let (dir, library) = temp_library();
+        let mut created = library.create(template("SW-48")).unwrap();
+        created.weight_kg = 5.0;
+        library.edit(created.clone()).unwrap();
+        assert_eq!(library.get(&created.id).unwrap().weight_kg, 5.0);
+        library.delete(&created.id).unwrap();
+        assert!(library.load().unwrap().is_empty());
+        assert!(matches!(
+            library.delete(&created.id),
+            Err(Error::TemplateNotFound(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_store_invalid_templates() {
+        let (dir, library) = temp_library();
+        let mut invalid = template("SW-48");
+        invalid.height_u = 0;
+        assert!(matches!(
+            library.create(invalid),
+            Err(Error::InvalidTemplate { .. })
+        ));
+        assert!(library.load().unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn exports_and_imports_between_libraries() {
+        let (dir, library) = temp_library();
+        let kept = library.create(template("SW-48")).unwrap();
+        library.create(template("SW-24")).unwrap();
+        let file = dir.join("export.json");
+        let ids = [kept.id.clone()];
+        assert_eq!(library.export(&file, Some(&ids)).unwrap(), 1);
+
+        let (other_dir, other) = temp_library();
+        assert_eq!(other.import(&file).unwrap(), vec![kept.clone()]);
+        assert_eq!(other.load().unwrap(), vec![kept]);
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(other_dir).unwrap();
+    }
+
+    #[test]
+    fn imports_other_ids_as_new_templates() {
+        let (dir, library) = temp_library();
+        let mut builtin = template("SW-48");
+        builtin.id = String::from("acme/sw-48");
+        let file = dir.join("export.json");
+        fs::create_dir_all(&dir).unwrap();
+        write_templates(&file, &[builtin]).unwrap();
+        let imported = library.import(&file).unwrap();
+        assert!(imported[0].id.starts_with(ID_PREFIX));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn imports_nothing_unless_every_template_is_valid() {
+        let (dir, library) = temp_library();
+        let mut invalid = template("SW-24");
+        invalid.depth_mm = 0;
+        let file = dir.join("export.json");
+        fs::create_dir_all(&dir).unwrap();
+        write_templates(&file, &[template("SW-48"), invalid]).unwrap();
+        assert!(library.import(&file).is_err());
+        assert!(library.load().unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn merges_by_manufacturer_and_model() {
+        let (dir, library) = temp_library();
+        let existing = library.create(template("SW-48")).unwrap();
+        let mut newer = template("sw-48");
+        newer.weight_kg = 5.0;
+        assert_eq!(
+            library.merge(vec![newer, template("SW-24")]).unwrap(),
+            (1, 1)
+        );
+        let updated = library.get(&existing.id).unwrap();
+        assert_eq!(updated.weight_kg, 5.0);
+        assert_eq!(library.load().unwrap().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_empty_models_and_sizes() {
+        let mut t = template(" ");
+        assert_eq!(invalid_field(validate(&t)), "model");
+        t = template("SW-48");
+        t.depth_mm = 0;
+        assert_eq!(invalid_field(validate(&t)), "depthMm");
+        t = template("SW-48");
+        t.weight_kg = f64::NAN;
+        assert_eq!(invalid_field(validate(&t)), "weightKg");
+    }
+
+    #[test]
+    fn rejects_typical_power_above_nameplate() {
+        let mut t = template("SW-48");
+        t.power = Some(PowerDraw {
+            nameplate_w: 150,
+            typical_w: 200,
+        });
+        assert_eq!(invalid_field(validate(&t)), "power.typicalW");
+    }
+
+    #[test]
+    fn rejects_port_counts_out_of_range() {
+        let mut t = template("SW-48");
+        t.ports[0].count = 0;
+        assert_eq!(invalid_field(validate(&t)), "ports[0].count");
+        t.ports[0].count = MAX_PORT_COUNT + 1;
+        assert_eq!(invalid_field(validate(&t)), "ports[0].count");
+        t.ports[0].count = MAX_PORT_COUNT;
+        assert!(validate(&t).is_ok());
+    }
+
+    #[test]
+    fn rejects_overlapping_port_groups() {
+        let mut t = template("SW-48");
+        let mut uplinks = t.ports[0].clone();
+        uplinks.first = 48;
+        t.ports.push(uplinks);
+        assert_eq!(invalid_field(validate(&t)), "ports[1].name");
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Category {
+    Server,
+    Switch,
+    PatchPanel,
+    Pdu,
+    Ups,
+    BlankingPanel,
+    Shelf,
+    #[default]
+    Other,
+}
+
+impl Category {
+    /// Human-readable name for printed output.
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Server => "Server",
+            Category::Switch => "Switch",
+            Category::PatchPanel => "Patch panel",
+            Category::Pdu => "PDU",
+            Category::Ups => "UPS",
+            Category::BlankingPanel => "Blanking panel",
+            Category::Shelf => "Shelf",
+            Category::Other => "Other",
+        }
+    }
+
+    /// Whether devices of this kind draw no power of their own.
+    pub fn is_passive(self) -> bool {
+        matches!(
+            self,
+            Category::BlankingPanel | Category::PatchPanel | Category::Pdu | Category::Shelf
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Category, Face, Port, PowerConnection, UnitRange};
+
+pub type DeviceId = Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerDraw {
+    /// Rated maximum from the power supply label.
+    pub nameplate_w: u32,
+    /// Expected draw under normal load.
+    pub typical_w: u32,
+}
+
+/// Direction cooling air moves through a device, relative to its own front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Airflow {
+    FrontToBack,
+    BackToFront,
+    SideToSide,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Device {
+    pub id: DeviceId,
+    pub name: String,
+    pub height_u: u32,
+    /// Lowest rack unit the device occupies.
+    pub position_u: u32,
+    pub depth_mm: u32,
+    pub face: Face,
+    #[serde(default)]
+    pub category: Category,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerDraw>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airflow: Option<Airflow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<Port>,
+    /// PDU outlets the device's power supplies are plugged into. Its draw is
+    /// shared evenly between them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub power_connections: Vec<PowerConnection>,
+    /// Id of the library template the device was created from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Id of the device in NetBox, for devices imported from there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netbox_id: Option<u64>,
+}
+
+impl Device {
+    pub fn new(
+        name: impl Into<String>,
+        height_u: u32,
+        position_u: u32,
+        depth_mm: u32,
+        face: Face,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            height_u,
+            position_u,
+            depth_mm,
+            face,
+            category: Category::default(),
+            manufacturer: None,
+            model: None,
+            serial: None,
+            power: None,
+            weight_kg: None,
+            airflow: None,
+            ports: Vec::new(),
+            power_connections: Vec::new(),
+            template_id: None,
+            netbox_id: None,
+        }
+    }
+
+    pub fn units(&self) -> UnitRange {
+        UnitRange::new(self.position_u, self.height_u)
+    }
+
+    pub fn port(&self, name: &str) -> Option<&Port> {
+        self.ports.iter().find(|p| p.name == name)
+    }
+
+    /// The side of the rack the device blows its exhaust out of, taking into
+    /// account which face it is mounted on. `None` for side-to-side airflow or
+    /// when the airflow is unknown.
+    pub fn exhaust(&self) -> Option<Face> {
+        match self.airflow? {
+            Airflow::FrontToBack => Some(self.face.opposite()),
+            Airflow::BackToFront => Some(self.face),
+            Airflow::SideToSide => None,
+        }
+    }
+}
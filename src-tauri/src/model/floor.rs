@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::FloorPosition;
+
+pub type RowId = Uuid;
+
+/// Width of a rack's footprint. Racks are taken to be the standard 600 mm
+/// wide, so a row of them lines up with the floor tiles.
+pub const RACK_WIDTH_MM: u32 = 600;
+
+/// A compass direction on the floor plan. North is the top of the plan, so
+/// it points towards smaller y.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Heading {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    /// A unit step in this direction, as `(x, y)`.
+    pub fn vector(self) -> (f64, f64) {
+        match self {
+            Heading::North => (0.0, -1.0),
+            Heading::East => (1.0, 0.0),
+            Heading::South => (0.0, 1.0),
+            Heading::West => (-1.0, 0.0),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Heading::North => Heading::South,
+            Heading::East => Heading::West,
+            Heading::South => Heading::North,
+            Heading::West => Heading::East,
+        }
+    }
+
+    /// The direction to the right of someone standing in front of a rack
+    /// facing this way.
+    pub fn right(self) -> Self {
+        match self {
+            Heading::North => Heading::West,
+            Heading::East => Heading::North,
+            Heading::South => Heading::East,
+            Heading::West => Heading::South,
+        }
+    }
+}
+
+/// An axis-aligned rectangle on the floor, in millimeters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Rect {
+    /// The rectangle `width` across and `depth` along `facing`, centered on
+    /// `(x, y)`.
+    pub fn centered(x: f64, y: f64, width: f64, depth: f64, facing: Heading) -> Self {
+        let (dx, dy) = match facing {
+            Heading::North | Heading::South => (width, depth),
+            Heading::East | Heading::West => (depth, width),
+        };
+        Self {
+            min_x: x - dx / 2.0,
+            min_y: y - dy / 2.0,
+            max_x: x + dx / 2.0,
+            max_y: y + dy / 2.0,
+        }
+    }
+
+    /// Whether the rectangles share any area. Touching edges do not count.
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.min_x < other.max_x
+            && other.min_x < self.max_x
+            && self.min_y < other.max_y
+            && other.min_y < self.max_y
+    }
+
+    pub fn contains(&self, other: &Rect) -> bool {
+        self.min_x <= other.min_x
+            && other.max_x <= self.max_x
+            && self.min_y <= other.min_y
+            && other.max_y <= self.max_y
+    }
+}
+
+/// The floor space a rack at `position` takes up.
+pub fn footprint(position: FloorPosition, depth_mm: u32) -> Rect {
+    Rect::centered(
+        position.x_mm,
+        position.y_mm,
+        f64::from(RACK_WIDTH_MM),
+        f64::from(depth_mm),
+        position.facing,
+    )
+}
+
+/// Free space required in front of and behind every rack, for opening doors
+/// and sliding equipment out on its rails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clearance {
+    pub front_mm: u32,
+    pub rear_mm: u32,
+}
+
+impl Default for Clearance {
+    fn default() -> Self {
+        Self {
+            front_mm: 1200,
+            rear_mm: 900,
+        }
+    }
+}
+
+/// A row of racks standing side by side with their fronts lined up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RackRow {
+    pub id: RowId,
+    pub name: String,
+    /// The middle of the front edge of the row's first slot.
+    pub x_mm: f64,
+    pub y_mm: f64,
+    /// The direction the fronts of the racks in the row face.
+    pub facing: Heading,
+    /// Number of rack positions in the row, numbered from 1. Slots run to
+    /// the right as seen from in front of the row.
+    pub slots: u32,
+    #[serde(default = "default_slot")]
+    pub slot_mm: u32,
+}
+
+fn default_slot() -> u32 {
+    RACK_WIDTH_MM
+}
+
+impl RackRow {
+    /// Where a rack `depth_mm` deep stands in `slot`, with its front on the
+    /// row's front line.
+    pub fn slot_position(&self, slot: u32, depth_mm: u32) -> FloorPosition {
+        let (ax, ay) = self.facing.right().vector();
+        let (fx, fy) = self.facing.vector();
+        let along = f64::from(slot.saturating_sub(1)) * f64::from(self.slot_mm);
+        let back = f64::from(depth_mm) / 2.0;
+        FloorPosition {
+            x_mm: self.x_mm + ax * along - fx * back,
+            y_mm: self.y_mm + ay * along - fy * back,
+            facing: self.facing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AisleKind {
+    Cold,
+    Hot,
+}
+
+/// A strip of floor kept free between rows, measured from its north-west
+/// corner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Aisle {
+    pub name: String,
+    pub kind: AisleKind,
+    pub x_mm: f64,
+    pub y_mm: f64,
+    pub width_mm: f64,
+    pub depth_mm: f64,
+}
+
+impl Aisle {
+    pub fn rect(&self) -> Rect {
+        Rect {
+            min_x: self.x_mm,
+            min_y: self.y_mm,
+            max_x: self.x_mm + self.width_mm,
+            max_y: self.y_mm + self.depth_mm,
+        }
+    }
+}
+
+/// The room or cage a layout is built in: a grid of floor tiles with rows of
+/// racks and the aisles between them.
+///
+/// Positions are in millimeters from the north-west corner of the room, with
+/// x running east and y running south.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Room {
+    pub name: String,
+    /// Edge length of one floor tile; 600 mm on most raised floors.
+    #[serde(default = "default_tile")]
+    pub tile_mm: u32,
+    /// Size of the floor in whole tiles.
+    pub width_tiles: u32,
+    pub depth_tiles: u32,
+    #[serde(default)]
+    pub clearance: Clearance,
+    #[serde(default)]
+    pub rows: Vec<RackRow>,
+    #[serde(default)]
+    pub aisles: Vec<Aisle>,
+}
+
+fn default_tile() -> u32 {
+    600
+}
+
+impl Room {
+    pub fn bounds(&self) -> Rect {
+        Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: f64::from(self.width_tiles) * f64::from(self.tile_mm),
+            max_y: f64::from(self.depth_tiles) * f64::from(self.tile_mm),
+        }
+    }
+
+    pub fn row(&self, id: RowId) -> Option<&RackRow> {
+        self.rows.iter().find(|r| r.id == id)
+    }
+
+    /// Moves `position` so the footprint of a rack `depth_mm` deep starts on
+    /// tile edges.
+    pub fn snap(&self, position: FloorPosition, depth_mm: u32) -> FloorPosition {
+        let tile = f64::from(self.tile_mm);
+        let rect = footprint(position, depth_mm);
+        let dx = (rect.min_x / tile).round() * tile - rect.min_x;
+        let dy = (rect.min_y / tile).round() * tile - rect.min_y;
+        FloorPosition {
+            x_mm: position.x_mm + dx,
+            y_mm: position.y_mm + dy,
+            facing: position.facing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(x_mm: f64, y_mm: f64, facing: Heading) -> RackRow {
+        RackRow {
+            id: Uuid::new_v4(),
+            name: String::from("A"),
+            x_mm,
+            y_mm,
+            facing,
+            slots: 4,
+            slot_mm: 600,
+        }
+    }
+
+    fn at(position: FloorPosition) -> (f64, f64) {
+        (position.x_mm, position.y_mm)
+    }
+
+    #[test]
+    fn turns_right_as_seen_from_the_front() {
+        assert_eq!(Heading::North.right(), Heading::West);
+        assert_eq!(Heading::East.right(), Heading::North);
+        assert_eq!(Heading::South.right(), Heading::East);
+        assert_eq!(Heading::West.right(), Heading::South);
+        for heading in [Heading::North, Heading::East, Heading::South, Heading::West] {
+            assert_eq!(heading.right().right(), heading.opposite());
+        }
+    }
+
+    #[test]
+    fn places_slots_of_a_north_facing_row_to_the_west() {
+        let row = row(1200.0, 3000.0, Heading::North);
+        assert_eq!(at(row.slot_position(1, 1000)), (1200.0, 3500.0));
+        assert_eq!(at(row.slot_position(2, 1000)), (600.0, 3500.0));
+        assert_eq!(row.slot_position(2, 1000).facing, Heading::North);
+    }
+
+    #[test]
+    fn places_slots_of_rotated_rows() {
+        let east = row(1000.0, 3000.0, Heading::East);
+        assert_eq!(at(east.slot_position(1, 800)), (600.0, 3000.0));
+        assert_eq!(at(east.slot_position(3, 800)), (600.0, 1800.0));
+
+        let south = row(1200.0, 1200.0, Heading::South);
+        assert_eq!(at(south.slot_position(2, 1000)), (1800.0, 700.0));
+
+        let west = row(3000.0, 1200.0, Heading::West);
+        assert_eq!(at(west.slot_position(2, 1000)), (3500.0, 1800.0));
+        assert_eq!(west.slot_position(2, 1000).facing, Heading::West);
+    }
+
+    #[test]
+    fn puts_the_front_of_every_slot_on_the_front_line() {
+        for facing in [Heading::North, Heading::East, Heading::South, Heading::West] {
+            let row = row(3000.0, 3000.0, facing);
+            for slot in 1..=row.slots {
+                let rect = footprint(row.slot_position(slot, 1200), 1200);
+                let front = match facing {
+                    Heading::North => rect.min_y,
+                    Heading::East => rect.max_x,
+                    Heading::South => rect.max_y,
+                    Heading::West => rect.min_x,
+                };
+                let line = match facing {
+                    Heading::North | Heading::South => row.y_mm,
+                    Heading::East | Heading::West => row.x_mm,
+                };
+                assert_eq!(front, line, "{facing:?} slot {slot}");
+            }
+        }
+    }
+
+    #[test]
+    fn swaps_width_and_depth_for_racks_facing_sideways() {
+        let north = Rect::centered(0.0, 0.0, 600.0, 1000.0, Heading::North);
+        assert_eq!(
+            (north.max_x - north.min_x, north.max_y - north.min_y),
+            (600.0, 1000.0)
+        );
+        let east = Rect::centered(0.0, 0.0, 600.0, 1000.0, Heading::East);
+        assert_eq!(
+            (east.max_x - east.min_x, east.max_y - east.min_y),
+            (1000.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn touching_rectangles_do_not_overlap() {
+        let a = Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 600.0,
+            max_y: 1000.0,
+        };
+        let beside = Rect {
+            min_x: 600.0,
+            max_x: 1200.0,
+            ..a
+        };
+        let into = Rect {
+            min_x: 599.0,
+            max_x: 1199.0,
+            ..a
+        };
+        assert!(!a.overlaps(&beside));
+        assert!(a.overlaps(&into) && into.overlaps(&a));
+        assert!(a.contains(&a));
+        assert!(!a.contains(&into));
+    }
+
+    #[test]
+    fn snaps_footprints_to_tile_edges() {
+        let room = Room {
+            name: String::from("Hall 1"),
+            tile_mm: 600,
+            width_tiles: 10,
+            depth_tiles: 10,
+            clearance: Clearance::default(),
+            rows: Vec::new(),
+            aisles: Vec::new(),
+        };
+        let position = FloorPosition {
+            x_mm: 1250.0,
+            y_mm: 1190.0,
+            facing: Heading::East,
+        };
+        let snapped = room.snap(position, 1000);
+        assert_eq!(at(snapped), (1100.0, 900.0));
+        let rect = footprint(snapped, 1000);
+        assert_eq!((rect.min_x, rect.min_y), (600.0, 600.0));
+        assert_eq!(snapped.facing, Heading::East);
+    }
+}
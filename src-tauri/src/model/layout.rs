@@ -0,0 +1,873 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Airflow, Cable, CableId, Device, DeviceId, Face, Feed, FloorPosition, Pdu, PduId, Port,
+    PortRef, PowerConnection, Rack, RackId, Room, Transceiver,
+};
+use crate::error::{Error, Result};
+use crate::validation;
+
+/// Every rack in a design, in display order, and the cabling between them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Layout {
+    #[serde(default)]
+    pub racks: Vec<Rack>,
+    #[serde(default)]
+    pub cables: Vec<Cable>,
+    /// The floor plan the racks stand on, if one has been drawn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room: Option<Room>,
+}
+
+impl Layout {
+    pub fn rack(&self, id: RackId) -> Result<&Rack> {
+        self.racks
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or(Error::RackNotFound(id))
+    }
+
+    pub fn rack_mut(&mut self, id: RackId) -> Result<&mut Rack> {
+        self.racks
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or(Error::RackNotFound(id))
+    }
+
+    /// Finds a device anywhere in the layout along with the rack holding it.
+    pub fn find_device(&self, id: DeviceId) -> Result<(&Rack, &Device)> {
+        self.racks
+            .iter()
+            .find_map(|r| r.device(id).map(|d| (r, d)))
+            .ok_or(Error::DeviceNotFound(id))
+    }
+
+    fn device_mut(&mut self, id: DeviceId) -> Result<&mut Device> {
+        self.racks
+            .iter_mut()
+            .find_map(|r| r.device_mut(id))
+            .ok_or(Error::DeviceNotFound(id))
+    }
+
+    pub fn devices(&self) -> impl Iterator<Item = (&Rack, &Device)> {
+        self.racks
+            .iter()
+            .flat_map(|r| r.devices.iter().map(move |d| (r, d)))
+    }
+
+    pub fn find_pdu(&self, id: PduId) -> Result<(&Rack, &Pdu)> {
+        self.racks
+            .iter()
+            .find_map(|r| r.pdu(id).map(|p| (r, p)))
+            .ok_or(Error::PduNotFound(id))
+    }
+
+    /// The device plugged into an outlet, if any.
+    pub fn outlet_user(&self, connection: &PowerConnection) -> Option<&Device> {
+        self.devices()
+            .map(|(_, d)| d)
+            .find(|d| d.power_connections.contains(connection))
+    }
+
+    /// Inserts `rack` at `index`, or at the end when `index` is out of range.
+    pub fn insert_rack(&mut self, index: usize, rack: Rack) -> Result<&Rack> {
+        check_name("rack name", &rack.name)?;
+        check_size("rack height", rack.height_u)?;
+        check_size("rack depth", rack.depth_mm)?;
+        let index = index.min(self.racks.len());
+        self.racks.insert(index, rack);
+        Ok(&self.racks[index])
+    }
+
+    pub fn rename_rack(&mut self, id: RackId, name: String) -> Result<&Rack> {
+        check_name("rack name", &name)?;
+        let rack = self.rack_mut(id)?;
+        rack.name = name;
+        Ok(rack)
+    }
+
+    pub fn set_load_rating(&mut self, id: RackId, rating_kg: Option<f64>) -> Result<&Rack> {
+        if let Some(rating) = rating_kg {
+            check_weight("load rating", rating)?;
+        }
+        let rack = self.rack_mut(id)?;
+        rack.load_rating_kg = rating_kg;
+        Ok(rack)
+    }
+
+    pub fn set_rack_weight(&mut self, id: RackId, weight_kg: Option<f64>) -> Result<&Rack> {
+        if let Some(kg) = weight_kg {
+            check_weight("rack weight", kg)?;
+        }
+        let rack = self.rack_mut(id)?;
+        rack.weight_kg = weight_kg;
+        Ok(rack)
+    }
+
+    pub fn set_rack_netbox_id(&mut self, id: RackId, netbox_id: Option<u64>) -> Result<&Rack> {
+        let rack = self.rack_mut(id)?;
+        rack.netbox_id = netbox_id;
+        Ok(rack)
+    }
+
+    pub fn set_cold_aisle(&mut self, id: RackId, cold_aisle: Face) -> Result<&Rack> {
+        let rack = self.rack_mut(id)?;
+        rack.cold_aisle = cold_aisle;
+        Ok(rack)
+    }
+
+    pub fn set_rack_position(
+        &mut self,
+        id: RackId,
+        position: Option<FloorPosition>,
+    ) -> Result<&Rack> {
+        if position.is_some_and(|p| !p.x_mm.is_finite() || !p.y_mm.is_finite()) {
+            return Err(Error::InvalidPosition);
+        }
+        let rack = self.rack_mut(id)?;
+        rack.position = position;
+        Ok(rack)
+    }
+
+    /// Replaces the floor plan, or removes it with `None`. Racks keep their
+    /// positions either way.
+    pub fn set_room(&mut self, room: Option<Room>) -> Result<Option<&Room>> {
+        if let Some(room) = &room {
+            check_room(room)?;
+        }
+        self.room = room;
+        Ok(self.room.as_ref())
+    }
+
+    /// Removes a rack and everything in it, returning it with its former index.
+    pub fn remove_rack(&mut self, id: RackId) -> Result<(usize, Rack)> {
+        let index = self
+            .racks
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or(Error::RackNotFound(id))?;
+        Ok((index, self.racks.remove(index)))
+    }
+
+    pub fn add_device(&mut self, rack_id: RackId, device: Device) -> Result<&Device> {
+        check_name("device name", &device.name)?;
+        check_size("device height", device.height_u)?;
+        check_size("device position", device.position_u)?;
+        check_size("device depth", device.depth_mm)?;
+        if let Some(weight) = device.weight_kg {
+            check_weight("device weight", weight)?;
+        }
+        let rack = self.rack_mut(rack_id)?;
+        check_placement(rack, &device)?;
+        rack.devices.push(device);
+        Ok(rack.devices.last().unwrap())
+    }
+
+    /// Moves a device to a new position, possibly in a different rack. A
+    /// device staying in its rack keeps its place in the rack's device list.
+    pub fn move_device(
+        &mut self,
+        id: DeviceId,
+        rack_id: RackId,
+        position_u: u32,
+        face: Face,
+    ) -> Result<&Device> {
+        check_size("device position", position_u)?;
+        let (from, device) = self.find_device(id)?;
+        let from = from.id;
+        let mut device = device.clone();
+        device.position_u = position_u;
+        device.face = face;
+        check_placement(self.rack(rack_id)?, &device)?;
+        if from == rack_id {
+            let device = self.device_mut(id)?;
+            device.position_u = position_u;
+            device.face = face;
+            return Ok(device);
+        }
+        self.remove_device(id)?;
+        let rack = self.rack_mut(rack_id)?;
+        rack.devices.push(device);
+        Ok(rack.devices.last().unwrap())
+    }
+
+    /// Removes a device, returning it along with the rack it was in.
+    pub fn remove_device(&mut self, id: DeviceId) -> Result<(RackId, Device)> {
+        for rack in &mut self.racks {
+            if let Some(index) = rack.devices.iter().position(|d| d.id == id) {
+                return Ok((rack.id, rack.devices.remove(index)));
+            }
+        }
+        Err(Error::DeviceNotFound(id))
+    }
+
+    pub fn set_device_weight(&mut self, id: DeviceId, weight_kg: Option<f64>) -> Result<&Device> {
+        if let Some(weight) = weight_kg {
+            check_weight("device weight", weight)?;
+        }
+        let device = self.device_mut(id)?;
+        device.weight_kg = weight_kg;
+        Ok(device)
+    }
+
+    pub fn set_serial(&mut self, id: DeviceId, serial: Option<String>) -> Result<&Device> {
+        let device = self.device_mut(id)?;
+        device.serial = serial;
+        Ok(device)
+    }
+
+    pub fn set_airflow(&mut self, id: DeviceId, airflow: Option<Airflow>) -> Result<&Device> {
+        let device = self.device_mut(id)?;
+        device.airflow = airflow;
+        Ok(device)
+    }
+
+    pub fn set_device_netbox_id(
+        &mut self,
+        id: DeviceId,
+        netbox_id: Option<u64>,
+    ) -> Result<&Device> {
+        let device = self.device_mut(id)?;
+        device.netbox_id = netbox_id;
+        Ok(device)
+    }
+
+    /// Inserts a PDU into a rack at `index`, or at the end when `index` is out
+    /// of range, then plugs `connections` back in. Used to restore a removed
+    /// PDU along with everything that was connected to it.
+    pub fn insert_pdu(
+        &mut self,
+        rack_id: RackId,
+        index: usize,
+        pdu: Pdu,
+        connections: &[(DeviceId, PowerConnection)],
+    ) -> Result<&Pdu> {
+        check_name("PDU name", &pdu.name)?;
+        check_pdu(&pdu)?;
+        let pdu_id = pdu.id;
+        let rack = self.rack_mut(rack_id)?;
+        let index = index.min(rack.pdus.len());
+        rack.pdus.insert(index, pdu);
+        for (device_id, connection) in connections {
+            self.connect_power(*device_id, connection.clone())?;
+        }
+        let (_, pdu) = self.find_pdu(pdu_id)?;
+        Ok(pdu)
+    }
+
+    pub fn set_pdu_feed(&mut self, id: PduId, feed: Option<Feed>) -> Result<&Pdu> {
+        let pdu = self
+            .racks
+            .iter_mut()
+            .find_map(|r| r.pdus.iter_mut().find(|p| p.id == id))
+            .ok_or(Error::PduNotFound(id))?;
+        pdu.feed = feed;
+        Ok(pdu)
+    }
+
+    /// Removes a PDU and unplugs everything from it, returning where it was
+    /// and what was connected so the removal can be undone.
+    pub fn remove_pdu(&mut self, id: PduId) -> Result<RemovedPdu> {
+        let mut connections = Vec::new();
+        for rack in &mut self.racks {
+            for device in &mut rack.devices {
+                device.power_connections.retain(|c| {
+                    let keep = c.pdu_id != id;
+                    if !keep {
+                        connections.push((device.id, c.clone()));
+                    }
+                    keep
+                });
+            }
+        }
+        for rack in &mut self.racks {
+            if let Some(index) = rack.pdus.iter().position(|p| p.id == id) {
+                return Ok(RemovedPdu {
+                    rack_id: rack.id,
+                    index,
+                    pdu: rack.pdus.remove(index),
+                    connections,
+                });
+            }
+        }
+        Err(Error::PduNotFound(id))
+    }
+
+    pub fn connect_power(
+        &mut self,
+        device_id: DeviceId,
+        connection: PowerConnection,
+    ) -> Result<()> {
+        let (_, pdu) = self.find_pdu(connection.pdu_id)?;
+        if pdu.circuit_of(&connection.outlet).is_none() {
+            return Err(Error::OutletNotFound {
+                pdu_id: connection.pdu_id,
+                outlet: connection.outlet,
+            });
+        }
+        if let Some(user) = self.outlet_user(&connection) {
+            return Err(Error::OutletInUse {
+                outlet: connection.outlet,
+                device_id: user.id,
+            });
+        }
+        let rack = self
+            .racks
+            .iter_mut()
+            .find(|r| r.device(device_id).is_some())
+            .ok_or(Error::DeviceNotFound(device_id))?;
+        let device = rack.device_mut(device_id).unwrap();
+        device.power_connections.push(connection);
+        Ok(())
+    }
+
+    pub fn disconnect_power(
+        &mut self,
+        device_id: DeviceId,
+        connection: &PowerConnection,
+    ) -> Result<()> {
+        let rack = self
+            .racks
+            .iter_mut()
+            .find(|r| r.device(device_id).is_some())
+            .ok_or(Error::DeviceNotFound(device_id))?;
+        let device = rack.device_mut(device_id).unwrap();
+        let index = device
+            .power_connections
+            .iter()
+            .position(|c| c == connection)
+            .ok_or_else(|| Error::OutletNotFound {
+                pdu_id: connection.pdu_id,
+                outlet: connection.outlet.clone(),
+            })?;
+        device.power_connections.remove(index);
+        Ok(())
+    }
+
+    pub fn port(&self, port: &PortRef) -> Result<&Port> {
+        let (_, device) = self.find_device(port.device_id)?;
+        device.port(&port.port).ok_or_else(|| Error::PortNotFound {
+            device_id: port.device_id,
+            port: port.port.clone(),
+        })
+    }
+
+    /// Adds a port to a device at `index`, or at the end when `index` is out
+    /// of range.
+    pub fn insert_port(&mut self, device_id: DeviceId, index: usize, port: Port) -> Result<&Port> {
+        check_name("port name", &port.name)?;
+        if let Some(t) = port.transceiver {
+            if !port.kind.takes(t) {
+                return Err(Error::InvalidTransceiver {
+                    port: port.name,
+                    kind: port.kind,
+                });
+            }
+        }
+        let device = self.device_mut(device_id)?;
+        if device.port(&port.name).is_some() {
+            return Err(Error::DuplicatePort {
+                device_id,
+                port: port.name,
+            });
+        }
+        let index = index.min(device.ports.len());
+        device.ports.insert(index, port);
+        Ok(&device.ports[index])
+    }
+
+    /// Removes a port with nothing plugged into it, returning where it was so
+    /// the removal can be undone.
+    pub fn remove_port(&mut self, port: &PortRef) -> Result<(usize, Port)> {
+        if let Some(cable) = self.cable_at(port) {
+            return Err(Error::PortInUse {
+                device_id: port.device_id,
+                port: port.port.clone(),
+                cable_id: cable.id,
+            });
+        }
+        let device = self.device_mut(port.device_id)?;
+        let index = device
+            .ports
+            .iter()
+            .position(|p| p.name == port.port)
+            .ok_or_else(|| Error::PortNotFound {
+                device_id: port.device_id,
+                port: port.port.clone(),
+            })?;
+        Ok((index, device.ports.remove(index)))
+    }
+
+    /// The cable plugged into a port, if any.
+    pub fn cable_at(&self, port: &PortRef) -> Option<&Cable> {
+        self.cables.iter().find(|c| c.ends().contains(&port))
+    }
+
+    /// Cables with at least one end on any of `devices`.
+    pub fn cables_of(&self, devices: &[DeviceId]) -> Vec<&Cable> {
+        self.cables
+            .iter()
+            .filter(|c| devices.iter().any(|d| c.touches(*d)))
+            .collect()
+    }
+
+    /// Adds a cable after checking both ends exist, are free and can be
+    /// joined.
+    pub fn connect(&mut self, cable: Cable) -> Result<&Cable> {
+        if cable.a == cable.b {
+            return Err(Error::IncompatiblePorts {
+                a: cable.a.port,
+                b: cable.b.port,
+                reason: "a port cannot be connected to itself",
+            });
+        }
+        for end in cable.ends() {
+            if let Some(existing) = self.cable_at(end) {
+                return Err(Error::PortInUse {
+                    device_id: end.device_id,
+                    port: end.port.clone(),
+                    cable_id: existing.id,
+                });
+            }
+        }
+        check_ports(self.port(&cable.a)?, self.port(&cable.b)?)?;
+        self.cables.push(cable);
+        Ok(self.cables.last().unwrap())
+    }
+
+    pub fn disconnect(&mut self, id: CableId) -> Result<Cable> {
+        let index = self
+            .cables
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or(Error::CableNotFound(id))?;
+        Ok(self.cables.remove(index))
+    }
+
+    pub fn set_cable_color(&mut self, id: CableId, color: Option<String>) -> Result<&Cable> {
+        let cable = self
+            .cables
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(Error::CableNotFound(id))?;
+        cable.color = color;
+        Ok(cable)
+    }
+
+    /// Fits or removes a transceiver. A port that is already cabled keeps its
+    /// cable only if the other end is still compatible.
+    pub fn set_transceiver(
+        &mut self,
+        port: &PortRef,
+        transceiver: Option<Transceiver>,
+    ) -> Result<&Port> {
+        let mut updated = self.port(port)?.clone();
+        if let Some(t) = transceiver {
+            if !updated.kind.takes(t) {
+                return Err(Error::InvalidTransceiver {
+                    port: updated.name,
+                    kind: updated.kind,
+                });
+            }
+        }
+        updated.transceiver = transceiver;
+        if let Some(cable) = self.cable_at(port) {
+            let other = if &cable.a == port { &cable.b } else { &cable.a };
+            check_ports(&updated, self.port(other)?)?;
+        }
+        let device = self.device_mut(port.device_id)?;
+        let slot = device
+            .ports
+            .iter_mut()
+            .find(|p| p.name == port.port)
+            .unwrap();
+        *slot = updated;
+        Ok(slot)
+    }
+}
+
+/// A PDU taken out of a layout by [`Layout::remove_pdu`].
+#[derive(Debug, Clone)]
+pub struct RemovedPdu {
+    pub rack_id: RackId,
+    pub index: usize,
+    pub pdu: Pdu,
+    pub connections: Vec<(DeviceId, PowerConnection)>,
+}
+
+fn check_pdu(pdu: &Pdu) -> Result<()> {
+    if !pdu.voltage_v.is_finite() || pdu.voltage_v <= 0.0 {
+        return Err(Error::InvalidPdu(String::from(
+            "voltage must be greater than zero",
+        )));
+    }
+    let mut names = std::collections::HashSet::new();
+    for circuit in &pdu.circuits {
+        if !circuit.breaker_a.is_finite() || circuit.breaker_a <= 0.0 {
+            return Err(Error::InvalidPdu(format!(
+                "circuit {} needs a breaker rating",
+                circuit.name
+            )));
+        }
+        for outlet in &circuit.outlets {
+            if !names.insert(&outlet.name) {
+                return Err(Error::InvalidPdu(format!(
+                    "outlet {} appears more than once",
+                    outlet.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_placement(rack: &Rack, device: &Device) -> Result<()> {
+    let conflicts = validation::check_placement(rack, device);
+    if !conflicts.is_empty() {
+        return Err(Error::InvalidPlacement(conflicts));
+    }
+    Ok(())
+}
+
+fn check_ports(a: &Port, b: &Port) -> Result<()> {
+    match a.incompatibility(b) {
+        Some(reason) => Err(Error::IncompatiblePorts {
+            a: format!("{} ({})", a.name, a.kind.label()),
+            b: format!("{} ({})", b.name, b.kind.label()),
+            reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_weight(field: &'static str, kg: f64) -> Result<()> {
+    if !kg.is_finite() || kg < 0.0 {
+        return Err(Error::InvalidWeight { field });
+    }
+    Ok(())
+}
+
+fn check_name(field: &'static str, name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::EmptyName(field));
+    }
+    Ok(())
+}
+
+fn check_room(room: &Room) -> Result<()> {
+    check_name("room name", &room.name)?;
+    check_size("tile size", room.tile_mm)?;
+    check_size("room width", room.width_tiles)?;
+    check_size("room depth", room.depth_tiles)?;
+    for row in &room.rows {
+        check_name("row name", &row.name)?;
+        check_size("row length", row.slots)?;
+        check_size("slot width", row.slot_mm)?;
+        if !row.x_mm.is_finite() || !row.y_mm.is_finite() {
+            return Err(Error::InvalidPosition);
+        }
+    }
+    for aisle in &room.aisles {
+        check_name("aisle name", &aisle.name)?;
+        if !aisle.x_mm.is_finite() || !aisle.y_mm.is_finite() {
+            return Err(Error::InvalidPosition);
+        }
+        if !(aisle.width_mm > 0.0 && aisle.width_mm.is_finite()) {
+            return Err(Error::ZeroSize {
+                field: "aisle width",
+            });
+        }
+        if !(aisle.depth_mm > 0.0 && aisle.depth_mm.is_finite()) {
+            return Err(Error::ZeroSize {
+                field: "aisle depth",
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_size(field: &'static str, value: u32) -> Result<()> {
+    if value == 0 {
+        return Err(Error::ZeroSize { field });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::model::{Circuit, Outlet, Phase, PortKind};
+
+    fn layout() -> Layout {
+        let mut layout = Layout::default();
+        for name in ["A1", "A2"] {
+            layout.insert_rack(usize::MAX, Rack::new(name, 42)).unwrap();
+        }
+        layout
+    }
+
+    fn names(rack: &Rack) -> Vec<&str> {
+        rack.devices.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    fn switch(name: &str, position_u: u32) -> Device {
+        let mut device = Device::new(name, 1, position_u, 300, Face::Front);
+        device.ports = vec![
+            Port::new("eth0", PortKind::Rj45),
+            Port::new("eth1", PortKind::Rj45),
+            Port::new("sfp0", PortKind::SfpPlus),
+        ];
+        device
+    }
+
+    fn end(device: &Device, port: &str) -> PortRef {
+        PortRef {
+            device_id: device.id,
+            port: port.to_owned(),
+        }
+    }
+
+    fn pdu() -> Pdu {
+        let outlets = ["1", "2"].map(|name| Outlet {
+            name: name.to_owned(),
+            kind: PortKind::C13,
+        });
+        Pdu::new(
+            "PDU-A",
+            230.0,
+            vec![Circuit {
+                name: String::from("C1"),
+                phase: Phase::L1,
+                breaker_a: 16.0,
+                outlets: outlets.into(),
+            }],
+        )
+    }
+
+    #[test]
+    fn inserts_racks_at_an_index_or_the_end() {
+        let mut layout = layout();
+        layout.insert_rack(1, Rack::new("B1", 42)).unwrap();
+        layout.insert_rack(99, Rack::new("C1", 42)).unwrap();
+        let names: Vec<_> = layout.racks.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["A1", "B1", "A2", "C1"]);
+        assert!(matches!(
+            layout.insert_rack(0, Rack::new(" ", 42)),
+            Err(Error::EmptyName(_))
+        ));
+        assert!(matches!(
+            layout.insert_rack(0, Rack::new("D1", 0)),
+            Err(Error::ZeroSize { .. })
+        ));
+        assert_eq!(layout.racks.len(), 4);
+    }
+
+    #[test]
+    fn adds_devices_that_fit() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        layout.add_device(rack_id, switch("sw-01", 10)).unwrap();
+        assert!(matches!(
+            layout.add_device(rack_id, switch("sw-02", 10)),
+            Err(Error::InvalidPlacement(_))
+        ));
+        assert!(matches!(
+            layout.add_device(rack_id, switch("sw-02", 43)),
+            Err(Error::InvalidPlacement(_))
+        ));
+        assert!(matches!(
+            layout.add_device(rack_id, switch("sw-02", 0)),
+            Err(Error::ZeroSize { .. })
+        ));
+        assert!(matches!(
+            layout.add_device(Uuid::new_v4(), switch("sw-02", 1)),
+            Err(Error::RackNotFound(_))
+        ));
+        assert_eq!(names(&layout.racks[0]), ["sw-01"]);
+    }
+
+    #[test]
+    fn moves_devices_in_place_within_a_rack() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        for (name, u) in [("sw-01", 1), ("sw-02", 2), ("sw-03", 3)] {
+            layout.add_device(rack_id, switch(name, u)).unwrap();
+        }
+        let id = layout.racks[0].devices[1].id;
+        let moved = layout.move_device(id, rack_id, 20, Face::Rear).unwrap();
+        assert_eq!((moved.position_u, moved.face), (20, Face::Rear));
+        assert_eq!(names(&layout.racks[0]), ["sw-01", "sw-02", "sw-03"]);
+    }
+
+    #[test]
+    fn moves_devices_between_racks() {
+        let mut layout = layout();
+        let (a1, a2) = (layout.racks[0].id, layout.racks[1].id);
+        layout.add_device(a1, switch("sw-01", 1)).unwrap();
+        layout.add_device(a1, switch("sw-02", 2)).unwrap();
+        let id = layout.racks[0].devices[0].id;
+        layout.move_device(id, a2, 5, Face::Front).unwrap();
+        assert_eq!(names(&layout.racks[0]), ["sw-02"]);
+        assert_eq!(names(&layout.racks[1]), ["sw-01"]);
+        assert_eq!(layout.find_device(id).unwrap().0.id, a2);
+    }
+
+    #[test]
+    fn leaves_devices_alone_when_a_move_does_not_fit() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        layout.add_device(rack_id, switch("sw-01", 1)).unwrap();
+        layout.add_device(rack_id, switch("sw-02", 2)).unwrap();
+        let id = layout.racks[0].devices[0].id;
+        assert!(matches!(
+            layout.move_device(id, rack_id, 2, Face::Front),
+            Err(Error::InvalidPlacement(_))
+        ));
+        let other = layout.racks[1].id;
+        assert!(layout.move_device(id, other, 0, Face::Front).is_err());
+        let (rack, device) = layout.find_device(id).unwrap();
+        assert_eq!((rack.id, device.position_u), (rack_id, 1));
+    }
+
+    #[test]
+    fn removes_devices() {
+        let mut layout = layout();
+        let rack_id = layout.racks[1].id;
+        layout.add_device(rack_id, switch("sw-01", 1)).unwrap();
+        let id = layout.racks[1].devices[0].id;
+        let (from, device) = layout.remove_device(id).unwrap();
+        assert_eq!((from, device.name.as_str()), (rack_id, "sw-01"));
+        assert!(matches!(
+            layout.remove_device(id),
+            Err(Error::DeviceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reinserts_removed_pdus_with_their_connections() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        layout.add_device(rack_id, switch("sw-01", 1)).unwrap();
+        let device_id = layout.racks[0].devices[0].id;
+        let first = pdu();
+        let pdu_id = first.id;
+        layout.insert_pdu(rack_id, 0, first, &[]).unwrap();
+        layout.insert_pdu(rack_id, 0, pdu(), &[]).unwrap();
+        let connection = PowerConnection {
+            pdu_id,
+            outlet: String::from("2"),
+        };
+        layout.connect_power(device_id, connection.clone()).unwrap();
+
+        let removed = layout.remove_pdu(pdu_id).unwrap();
+        assert_eq!(removed.index, 1);
+        assert_eq!(removed.connections, [(device_id, connection.clone())]);
+        assert!(layout.racks[0].devices[0].power_connections.is_empty());
+
+        layout
+            .insert_pdu(
+                removed.rack_id,
+                removed.index,
+                removed.pdu,
+                &removed.connections,
+            )
+            .unwrap();
+        assert_eq!(layout.racks[0].pdus[1].id, pdu_id);
+        assert_eq!(layout.outlet_user(&connection).unwrap().id, device_id);
+    }
+
+    #[test]
+    fn rejects_invalid_pdus() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        let mut dead = pdu();
+        dead.voltage_v = 0.0;
+        assert!(matches!(
+            layout.insert_pdu(rack_id, 0, dead, &[]),
+            Err(Error::InvalidPdu(_))
+        ));
+        let mut doubled = pdu();
+        let outlet = doubled.circuits[0].outlets[0].clone();
+        doubled.circuits[0].outlets.push(outlet);
+        assert!(matches!(
+            layout.insert_pdu(rack_id, 0, doubled, &[]),
+            Err(Error::InvalidPdu(_))
+        ));
+        assert!(layout.racks[0].pdus.is_empty());
+    }
+
+    #[test]
+    fn connects_free_compatible_ports() {
+        let mut layout = layout();
+        let (a1, a2) = (layout.racks[0].id, layout.racks[1].id);
+        let (a, b) = (switch("sw-01", 1), switch("sw-02", 1));
+        layout.add_device(a1, a.clone()).unwrap();
+        layout.add_device(a2, b.clone()).unwrap();
+
+        let cable = layout
+            .connect(Cable::new(end(&a, "eth0"), end(&b, "eth0")))
+            .unwrap()
+            .clone();
+        assert_eq!(layout.cable_at(&end(&b, "eth0")), Some(&cable));
+        assert_eq!(layout.cable_at(&end(&b, "eth1")), None);
+        assert_eq!(layout.cables_of(&[a.id]), [&cable]);
+        assert!(layout.cables_of(&[Uuid::new_v4()]).is_empty());
+
+        assert!(matches!(
+            layout.connect(Cable::new(end(&a, "eth1"), end(&b, "eth0"))),
+            Err(Error::PortInUse { cable_id, .. }) if cable_id == cable.id
+        ));
+        assert!(matches!(
+            layout.connect(Cable::new(end(&a, "eth1"), end(&a, "eth1"))),
+            Err(Error::IncompatiblePorts { .. })
+        ));
+        assert!(matches!(
+            layout.connect(Cable::new(end(&a, "eth1"), end(&b, "sfp0"))),
+            Err(Error::IncompatiblePorts { .. })
+        ));
+        assert!(matches!(
+            layout.connect(Cable::new(end(&a, "eth1"), end(&b, "eth9"))),
+            Err(Error::PortNotFound { .. })
+        ));
+        assert_eq!(layout.cables.len(), 1);
+
+        assert_eq!(layout.disconnect(cable.id).unwrap(), cable);
+        assert!(matches!(
+            layout.disconnect(cable.id),
+            Err(Error::CableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn adds_and_removes_ports() {
+        let mut layout = layout();
+        let rack_id = layout.racks[0].id;
+        let (a, b) = (switch("sw-01", 1), switch("sw-02", 2));
+        layout.add_device(rack_id, a.clone()).unwrap();
+        layout.add_device(rack_id, b.clone()).unwrap();
+
+        layout
+            .insert_port(a.id, 1, Port::new("mgmt", PortKind::Rj45))
+            .unwrap();
+        assert!(matches!(
+            layout.insert_port(a.id, 0, Port::new("eth0", PortKind::Rj45)),
+            Err(Error::DuplicatePort { .. })
+        ));
+        let device = layout.find_device(a.id).unwrap().1;
+        let ports: Vec<_> = device.ports.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(ports, ["eth0", "mgmt", "eth1", "sfp0"]);
+
+        layout
+            .connect(Cable::new(end(&a, "mgmt"), end(&b, "eth0")))
+            .unwrap();
+        assert!(matches!(
+            layout.remove_port(&end(&a, "mgmt")),
+            Err(Error::PortInUse { .. })
+        ));
+        let (index, port) = layout.remove_port(&end(&a, "eth1")).unwrap();
+        assert_eq!((index, port.name.as_str()), (2, "eth1"));
+    }
+}
@@ -0,0 +1,17 @@
+mod category;
+mod device;
+mod floor;
+mod layout;
+mod port;
+mod power;
+mod rack;
+
+pub use category::Category;
+pub use device::{Airflow, Device, DeviceId, PowerDraw};
+pub use floor::{
+    footprint, Aisle, AisleKind, Clearance, Heading, RackRow, Rect, Room, RowId, RACK_WIDTH_MM,
+};
+pub use layout::{Layout, RemovedPdu};
+pub use port::{Cable, CableId, CableKind, Port, PortKind, PortRef, Transceiver};
+pub use power::{Circuit, Feed, Outlet, Pdu, PduId, Phase, PowerConnection};
+pub use rack::{Face, FloorPosition, Rack, RackId, UnitRange, DEFAULT_DEPTH_MM, UNIT_MM};
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::DeviceId;
+
+pub type CableId = Uuid;
+
+/// Physical connector type of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PortKind {
+    Rj45,
+    Sfp,
+    SfpPlus,
+    Qsfp28,
+    Console,
+    C13,
+    C14,
+    C19,
+    C20,
+}
+
+impl PortKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PortKind::Rj45 => "RJ45",
+            PortKind::Sfp => "SFP",
+            PortKind::SfpPlus => "SFP+",
+            PortKind::Qsfp28 => "QSFP28",
+            PortKind::Console => "console",
+            PortKind::C13 => "C13",
+            PortKind::C14 => "C14",
+            PortKind::C19 => "C19",
+            PortKind::C20 => "C20",
+        }
+    }
+
+    /// Whether the port is a cage for pluggable transceivers.
+    pub fn is_cage(self) -> bool {
+        matches!(self, PortKind::Sfp | PortKind::SfpPlus | PortKind::Qsfp28)
+    }
+
+    /// Whether a transceiver of type `transceiver` is made for this cage.
+    pub fn takes(self, transceiver: Transceiver) -> bool {
+        match transceiver {
+            Transceiver::BaseT => matches!(self, PortKind::Sfp | PortKind::SfpPlus),
+            Transceiver::Multimode | Transceiver::Singlemode => self.is_cage(),
+        }
+    }
+}
+
+/// A pluggable module fitted to an SFP, SFP+ or QSFP28 cage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Transceiver {
+    /// Copper module with an RJ45 socket, e.g. 1000BASE-T or 10GBASE-T.
+    BaseT,
+    /// Short-reach optic for multimode fiber, e.g. 10GBASE-SR.
+    Multimode,
+    /// Long-reach optic for singlemode fiber, e.g. 10GBASE-LR.
+    Singlemode,
+}
+
+/// A single port on a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Port {
+    pub name: String,
+    pub kind: PortKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transceiver: Option<Transceiver>,
+}
+
+/// The type of cable needed between two ports, for ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CableKind {
+    Copper,
+    Console,
+    Multimode,
+    Singlemode,
+    DirectAttach,
+    Power,
+}
+
+impl CableKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CableKind::Copper => "Copper",
+            CableKind::Console => "Console",
+            CableKind::Multimode => "Multimode fiber",
+            CableKind::Singlemode => "Singlemode fiber",
+            CableKind::DirectAttach => "Direct attach",
+            CableKind::Power => "Power",
+        }
+    }
+}
+
+/// What a cable plugged into a port has to end in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Media {
+    Copper,
+    Console,
+    Multimode,
+    Singlemode,
+    /// An empty cage, which only takes a direct-attach cable to a cage of the
+    /// same kind.
+    DirectAttach(PortKind),
+    PowerInlet,
+    PowerOutlet,
+}
+
+impl Port {
+    pub fn new(name: impl Into<String>, kind: PortKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            transceiver: None,
+        }
+    }
+
+    /// Why a cable cannot join this port to `other`, or `None` if it can.
+    pub fn incompatibility(&self, other: &Port) -> Option<&'static str> {
+        use Media::*;
+        match (self.media(), other.media()) {
+            (Copper | Console, Copper | Console)
+            | (Multimode, Multimode)
+            | (Singlemode, Singlemode)
+            | (PowerInlet, PowerOutlet)
+            | (PowerOutlet, PowerInlet) => None,
+            (DirectAttach(a), DirectAttach(b)) if a == b => None,
+            (DirectAttach(_), _) | (_, DirectAttach(_)) => {
+                Some("an empty cage needs a transceiver or a direct-attach cable to the same kind of cage")
+            }
+            (Multimode, Singlemode) | (Singlemode, Multimode) => {
+                Some("multimode and singlemode optics cannot be mixed")
+            }
+            (PowerInlet, PowerInlet) => Some("both ends are power inlets"),
+            (PowerOutlet, PowerOutlet) => Some("both ends are power outlets"),
+            (PowerInlet | PowerOutlet, _) | (_, PowerInlet | PowerOutlet) => {
+                Some("power ports only connect to other power ports")
+            }
+            _ => Some("copper and fiber ports cannot be connected"),
+        }
+    }
+
+    /// The cable needed to join this port to `other`, assuming they are
+    /// compatible.
+    pub fn cable_kind(&self, other: &Port) -> CableKind {
+        match (self.media(), other.media()) {
+            (Media::Console, _) | (_, Media::Console) => CableKind::Console,
+            (Media::Copper, _) => CableKind::Copper,
+            (Media::Multimode, _) => CableKind::Multimode,
+            (Media::Singlemode, _) => CableKind::Singlemode,
+            (Media::DirectAttach(_), _) => CableKind::DirectAttach,
+            (Media::PowerInlet | Media::PowerOutlet, _) => CableKind::Power,
+        }
+    }
+
+    fn media(&self) -> Media {
+        match (self.kind, self.transceiver) {
+            (PortKind::Rj45, _) => Media::Copper,
+            (PortKind::Console, _) => Media::Console,
+            (PortKind::C13 | PortKind::C19, _) => Media::PowerOutlet,
+            (PortKind::C14 | PortKind::C20, _) => Media::PowerInlet,
+            (kind, None) => Media::DirectAttach(kind),
+            (_, Some(Transceiver::BaseT)) => Media::Copper,
+            (_, Some(Transceiver::Multimode)) => Media::Multimode,
+            (_, Some(Transceiver::Singlemode)) => Media::Singlemode,
+        }
+    }
+}
+
+/// One end of a cable: a named port on a device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortRef {
+    pub device_id: DeviceId,
+    pub port: String,
+}
+
+/// A cable between two ports, possibly on devices in different racks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cable {
+    pub id: CableId,
+    pub a: PortRef,
+    pub b: PortRef,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl Cable {
+    pub fn new(a: PortRef, b: PortRef) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            a,
+            b,
+            color: None,
+        }
+    }
+
+    pub fn ends(&self) -> [&PortRef; 2] {
+        [&self.a, &self.b]
+    }
+
+    pub fn touches(&self, device_id: DeviceId) -> bool {
+        self.a.device_id == device_id || self.b.device_id == device_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cage(kind: PortKind, transceiver: Option<Transceiver>) -> Port {
+        Port {
+            transceiver,
+            ..Port::new("sfp1", kind)
+        }
+    }
+
+    #[test]
+    fn joins_copper_ports() {
+        let a = Port::new("eth0", PortKind::Rj45);
+        let b = Port::new("eth1", PortKind::Rj45);
+        assert_eq!(a.incompatibility(&b), None);
+        assert_eq!(a.cable_kind(&b), CableKind::Copper);
+    }
+
+    #[test]
+    fn rejects_empty_sfp_plus_to_rj45() {
+        let sfp = cage(PortKind::SfpPlus, None);
+        let rj45 = Port::new("eth0", PortKind::Rj45);
+        assert!(sfp.incompatibility(&rj45).is_some());
+        assert!(rj45.incompatibility(&sfp).is_some());
+    }
+
+    #[test]
+    fn joins_sfp_plus_to_rj45_through_a_copper_transceiver() {
+        let sfp = cage(PortKind::SfpPlus, Some(Transceiver::BaseT));
+        let rj45 = Port::new("eth0", PortKind::Rj45);
+        assert_eq!(sfp.incompatibility(&rj45), None);
+        assert_eq!(rj45.incompatibility(&sfp), None);
+        assert_eq!(sfp.cable_kind(&rj45), CableKind::Copper);
+    }
+
+    #[test]
+    fn joins_empty_cages_of_the_same_kind_with_direct_attach() {
+        let a = cage(PortKind::SfpPlus, None);
+        let b = cage(PortKind::SfpPlus, None);
+        assert_eq!(a.incompatibility(&b), None);
+        assert_eq!(a.cable_kind(&b), CableKind::DirectAttach);
+        assert!(a.incompatibility(&cage(PortKind::Qsfp28, None)).is_some());
+    }
+
+    #[test]
+    fn rejects_mixed_fiber_modes() {
+        let mm = cage(PortKind::SfpPlus, Some(Transceiver::Multimode));
+        let sm = cage(PortKind::SfpPlus, Some(Transceiver::Singlemode));
+        assert_eq!(
+            mm.incompatibility(&sm),
+            Some("multimode and singlemode optics cannot be mixed")
+        );
+        assert_eq!(mm.incompatibility(&mm), None);
+    }
+
+    #[test]
+    fn rejects_fiber_to_copper() {
+        let fiber = cage(PortKind::SfpPlus, Some(Transceiver::Multimode));
+        let rj45 = Port::new("eth0", PortKind::Rj45);
+        assert_eq!(
+            fiber.incompatibility(&rj45),
+            Some("copper and fiber ports cannot be connected")
+        );
+    }
+
+    #[test]
+    fn joins_power_inlets_only_to_outlets() {
+        let inlet = Port::new("psu1", PortKind::C14);
+        let outlet = Port::new("1", PortKind::C13);
+        assert_eq!(inlet.incompatibility(&outlet), None);
+        assert_eq!(inlet.cable_kind(&outlet), CableKind::Power);
+        assert!(inlet.incompatibility(&inlet).is_some());
+        assert!(outlet.incompatibility(&outlet).is_some());
+        assert!(inlet
+            .incompatibility(&Port::new("eth0", PortKind::Rj45))
+            .is_some());
+    }
+
+    #[test]
+    fn joins_console_to_copper() {
+        let console = Port::new("con", PortKind::Console);
+        let rj45 = Port::new("eth0", PortKind::Rj45);
+        assert_eq!(console.incompatibility(&rj45), None);
+        assert_eq!(rj45.cable_kind(&console), CableKind::Console);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::PortKind;
+
+pub type PduId = Uuid;
+
+/// The supply conductor a circuit is wired to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Phase {
+    L1,
+    L2,
+    L3,
+}
+
+/// One of the two independent utility feeds in a redundant power design.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Feed {
+    A,
+    B,
+}
+
+impl Feed {
+    pub fn other(self) -> Self {
+        match self {
+            Feed::A => Feed::B,
+            Feed::B => Feed::A,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Outlet {
+    pub name: String,
+    pub kind: PortKind,
+}
+
+/// A breaker-protected group of outlets on a PDU.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Circuit {
+    pub name: String,
+    pub phase: Phase,
+    pub breaker_a: f64,
+    pub outlets: Vec<Outlet>,
+}
+
+/// A power distribution unit, either rack-mounted or zero-U.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pdu {
+    pub id: PduId,
+    pub name: String,
+    /// Voltage across each outlet, i.e. line to neutral for a three-phase
+    /// wye PDU.
+    pub voltage_v: f64,
+    /// The feed the PDU is supplied from, if it is part of an A/B pair.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed: Option<Feed>,
+    pub circuits: Vec<Circuit>,
+}
+
+impl Pdu {
+    pub fn new(name: impl Into<String>, voltage_v: f64, circuits: Vec<Circuit>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            voltage_v,
+            feed: None,
+            circuits,
+        }
+    }
+
+    /// Finds the circuit an outlet belongs to.
+    pub fn circuit_of(&self, outlet: &str) -> Option<&Circuit> {
+        self.circuits
+            .iter()
+            .find(|c| c.outlets.iter().any(|o| o.name == outlet))
+    }
+}
+
+/// A device power supply plugged into a PDU outlet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerConnection {
+    pub pdu_id: PduId,
+    pub outlet: String,
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Device, DeviceId, Heading, Pdu, PduId};
+
+pub type RackId = Uuid;
+
+/// Height of one rack unit: 1.75 in.
+pub const UNIT_MM: f64 = 44.45;
+
+/// Standard depth of a four-post rack, used when none is given.
+pub const DEFAULT_DEPTH_MM: u32 = 1000;
+
+/// The side of a rack a device is mounted from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Face {
+    #[default]
+    Front,
+    Rear,
+}
+
+impl Face {
+    pub fn opposite(self) -> Self {
+        match self {
+            Face::Front => Face::Rear,
+            Face::Rear => Face::Front,
+        }
+    }
+}
+
+/// An inclusive range of rack units, numbered from 1 at the bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitRange {
+    pub bottom: u32,
+    pub top: u32,
+}
+
+impl UnitRange {
+    /// The units from `bottom` up. A range reaching past the highest unit
+    /// number ends there instead; see [`checked`](Self::checked).
+    pub fn new(bottom: u32, height_u: u32) -> Self {
+        Self {
+            bottom,
+            top: bottom.saturating_add(height_u.max(1) - 1),
+        }
+    }
+
+    /// Like [`new`](Self::new), or `None` if the range would reach past the
+    /// highest unit number.
+    pub fn checked(bottom: u32, height_u: u32) -> Option<Self> {
+        Some(Self {
+            bottom,
+            top: bottom.checked_add(height_u.max(1) - 1)?,
+        })
+    }
+
+    pub fn overlaps(&self, other: &UnitRange) -> bool {
+        self.bottom <= other.top && other.bottom <= self.top
+    }
+
+    pub fn contains(&self, unit: u32) -> bool {
+        (self.bottom..=self.top).contains(&unit)
+    }
+}
+
+/// Where a rack stands on the floor, measured to the center of its footprint.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloorPosition {
+    pub x_mm: f64,
+    pub y_mm: f64,
+    /// The direction the front of the rack faces.
+    #[serde(default)]
+    pub facing: Heading,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rack {
+    pub id: RackId,
+    pub name: String,
+    pub height_u: u32,
+    pub depth_mm: u32,
+    /// Weight of the empty rack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    /// Maximum static load the manufacturer rates the rack for, not counting
+    /// the rack itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_rating_kg: Option<f64>,
+    /// The side of the rack facing the cold aisle, where devices should draw
+    /// their air in.
+    #[serde(default)]
+    pub cold_aisle: Face,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<FloorPosition>,
+    /// Id of the rack in NetBox, for racks imported from there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netbox_id: Option<u64>,
+    #[serde(default)]
+    pub devices: Vec<Device>,
+    #[serde(default)]
+    pub pdus: Vec<Pdu>,
+}
+
+impl Rack {
+    pub fn new(name: impl Into<String>, height_u: u32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            height_u,
+            depth_mm: DEFAULT_DEPTH_MM,
+            weight_kg: None,
+            load_rating_kg: None,
+            cold_aisle: Face::Front,
+            position: None,
+            netbox_id: None,
+            devices: Vec::new(),
+            pdus: Vec::new(),
+        }
+    }
+
+    pub fn device(&self, id: DeviceId) -> Option<&Device> {
+        self.devices.iter().find(|d| d.id == id)
+    }
+
+    pub fn device_mut(&mut self, id: DeviceId) -> Option<&mut Device> {
+        self.devices.iter_mut().find(|d| d.id == id)
+    }
+
+    pub fn pdu(&self, id: PduId) -> Option<&Pdu> {
+        self.pdus.iter().find(|p| p.id == id)
+    }
+
+    /// Devices mounted on `face`, ordered from the bottom of the rack up.
+    pub fn devices_on(&self, face: Face) -> Vec<&Device> {
+        let mut devices: Vec<_> = self.devices.iter().filter(|d| d.face == face).collect();
+        devices.sort_by_key(|d| d.position_u);
+        devices
+    }
+}
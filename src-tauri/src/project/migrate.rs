@@ -0,0 +1,126 @@
+//! Upgrades project documents written by older versions of the app.
+//!
+//! Each migration takes the raw JSON of one schema version and rewrites it into
+//! the next, so a file of any age is walked forward one step at a time until it
+//! matches [`CURRENT_VERSION`]. Migrations must never be edited once released;
+//! add a new one and bump the version instead.
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use super::CURRENT_VERSION;
+use crate::error::{Error, Result};
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// `MIGRATIONS[n]` upgrades a document from version `n + 1` to `n + 2`.
+const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+/// Reads the schema version of a project document.
+pub fn version(value: &Value) -> Result<u32> {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+        .filter(|&v| v > 0)
+        .ok_or_else(|| invalid("version", "missing or invalid schema version"))
+}
+
+/// Upgrades `value` in place to [`CURRENT_VERSION`].
+pub fn migrate(value: &mut Value) -> Result<()> {
+    let from = version(value)?;
+    if from > CURRENT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            found: from.into(),
+            supported: CURRENT_VERSION,
+        });
+    }
+    let document = value
+        .as_object_mut()
+        .ok_or_else(|| invalid(".", "project file must be a JSON object"))?;
+    for (step, migration) in (from..).zip(&MIGRATIONS[from as usize - 1..]) {
+        migration(document)?;
+        document.insert("version".to_owned(), (step + 1).into());
+    }
+    Ok(())
+}
+
+fn invalid(path: &str, message: &str) -> Error {
+    Error::InvalidProject {
+        path: path.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+/// Version 2 gives every project a stable id.
+fn v1_to_v2(document: &mut Map<String, Value>) -> Result<()> {
+    let metadata = document
+        .get_mut("metadata")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| invalid("metadata", "expected an object"))?;
+    metadata
+        .entry("id")
+        .or_insert_with(|| Uuid::new_v4().to_string().into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len() + 1, CURRENT_VERSION as usize);
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let mut value = json!({ "version": CURRENT_VERSION, "metadata": { "name": "a" } });
+        let before = value.clone();
+        migrate(&mut value).unwrap();
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut value = json!({ "version": CURRENT_VERSION + 1 });
+        assert!(matches!(
+            migrate(&mut value),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_missing_version() {
+        let mut value = json!({ "metadata": {} });
+        assert!(matches!(
+            migrate(&mut value),
+            Err(Error::InvalidProject { path, .. }) if path == "version"
+        ));
+    }
+
+    #[test]
+    fn v1_to_v2_adds_project_id() {
+        let mut value = json!({
+            "format": "rackd",
+            "version": 1,
+            "metadata": { "name": "Cage 4" },
+            "layout": { "racks": [] },
+        });
+        migrate(&mut value).unwrap();
+        assert_eq!(value["version"], 2);
+        let id = value["metadata"]["id"].as_str().unwrap();
+        assert!(Uuid::parse_str(id).is_ok());
+        assert_eq!(value["metadata"]["name"], "Cage 4");
+    }
+
+    #[test]
+    fn v1_to_v2_keeps_existing_id() {
+        let id = Uuid::new_v4().to_string();
+        let mut value = json!({ "version": 1, "metadata": { "name": "a", "id": id } });
+        migrate(&mut value).unwrap();
+        assert_eq!(value["metadata"]["id"], id.as_str());
+    }
+}
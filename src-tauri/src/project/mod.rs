@@ -0,0 +1,420 @@
+//! The `.rackd` project file format.
+//!
+//! A project file is a pretty-printed JSON document:
+//!
+//! ```json
+//! {
+//!   "format": "rackd",
+//!   "version": 2,
+//!   "metadata": { "id": "…", "name": "Cage 4", ... },
+//!   "layout": { "racks": [ ... ] }
+//! }
+//! ```
+//!
+//! `version` is bumped whenever the shape of the document changes, and older
+//! documents are upgraded by [`migrate`] before being deserialized.
+
+pub mod migrate;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::model::Layout;
+use crate::validation::{self, Conflict};
+
+pub const FILE_EXTENSION: &str = "rackd";
+pub const FORMAT: &str = "rackd";
+/// The schema version written by this build.
+pub const CURRENT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub format: String,
+    pub version: u32,
+    pub metadata: Metadata,
+    #[serde(default)]
+    pub layout: Layout,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new("Untitled")
+    }
+}
+
+impl Project {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            format: FORMAT.to_owned(),
+            version: CURRENT_VERSION,
+            metadata: Metadata {
+                id: Uuid::new_v4(),
+                name: name.into(),
+                description: String::new(),
+                author: String::new(),
+            },
+            layout: Layout::default(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| Error::InvalidProject {
+                path: String::from("."),
+                message: e.to_string(),
+            })?;
+        Self::from_value(value)
+    }
+
+    /// Reads a project document, upgrading it from older versions and
+    /// checking that the layout in it is one the app could have made.
+    pub fn from_value(mut value: serde_json::Value) -> Result<Self> {
+        migrate::migrate(&mut value)?;
+        let project: Self =
+            serde_path_to_error::deserialize(value).map_err(|e| Error::InvalidProject {
+                path: e.path().to_string(),
+                message: e.into_inner().to_string(),
+            })?;
+        if project.format != FORMAT {
+            return Err(invalid("format", format!("expected \"{FORMAT}\"")));
+        }
+        check_layout(&project.layout)?;
+        Ok(project)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("project is always serializable")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Self::from_json(&json)
+    }
+
+    /// Writes the project to `path`, replacing it only once the new contents
+    /// are fully on disk.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension(format!("{FILE_EXTENSION}.tmp"));
+        fs::write(&tmp, self.to_json()).map_err(|e| Error::io(&tmp, e))?;
+        fs::rename(&tmp, path).map_err(|e| Error::io(path, e))
+    }
+}
+
+fn invalid(path: impl Into<String>, message: impl Into<String>) -> Error {
+    Error::InvalidProject {
+        path: path.into(),
+        message: message.into(),
+    }
+}
+
+/// Rejects layouts that break the rules edits are checked against, so a file
+/// edited by hand cannot put impossible devices or dangling references in
+/// front of the rest of the app.
+fn check_layout(layout: &Layout) -> Result<()> {
+    // Racks, devices, PDUs and cables are all looked up by id, so a copy
+    // pasted into the file would make edits hit the wrong one.
+    let mut ids = HashSet::new();
+    let mut unique = |path: String, id: Uuid| {
+        if ids.insert(id) {
+            Ok(())
+        } else {
+            Err(invalid(path, format!("duplicate id {id}")))
+        }
+    };
+    let mut outlets = HashSet::new();
+    for (r, rack) in layout.racks.iter().enumerate() {
+        let path = format!("layout.racks[{r}]");
+        unique(format!("{path}.id"), rack.id)?;
+        if rack.height_u == 0 {
+            return Err(invalid(
+                format!("{path}.heightU"),
+                "must be greater than zero",
+            ));
+        }
+        if rack.depth_mm == 0 {
+            return Err(invalid(
+                format!("{path}.depthMm"),
+                "must be greater than zero",
+            ));
+        }
+        if let Some((device_id, conflict)) = validation::check_rack(rack).into_iter().next() {
+            let d = rack.devices.iter().position(|d| d.id == device_id).unwrap();
+            return Err(invalid(
+                format!("{path}.devices[{d}]"),
+                conflict_message(&conflict),
+            ));
+        }
+        for (p, pdu) in rack.pdus.iter().enumerate() {
+            unique(format!("{path}.pdus[{p}].id"), pdu.id)?;
+        }
+        for (d, device) in rack.devices.iter().enumerate() {
+            unique(format!("{path}.devices[{d}].id"), device.id)?;
+            for (c, connection) in device.power_connections.iter().enumerate() {
+                let path = format!("{path}.devices[{d}].powerConnections[{c}]");
+                let known = layout
+                    .find_pdu(connection.pdu_id)
+                    .is_ok_and(|(_, pdu)| pdu.circuit_of(&connection.outlet).is_some());
+                if !known {
+                    return Err(invalid(
+                        path,
+                        format!("no PDU outlet {}", connection.outlet),
+                    ));
+                }
+                if !outlets.insert(connection) {
+                    return Err(invalid(
+                        path,
+                        format!("outlet {} is already in use", connection.outlet),
+                    ));
+                }
+            }
+        }
+    }
+    let mut ports = HashSet::new();
+    for (c, cable) in layout.cables.iter().enumerate() {
+        unique(format!("layout.cables[{c}].id"), cable.id)?;
+        for (end, port) in [("a", &cable.a), ("b", &cable.b)] {
+            let known = layout
+                .find_device(port.device_id)
+                .is_ok_and(|(_, device)| device.port(&port.port).is_some());
+            if !known {
+                return Err(invalid(
+                    format!("layout.cables[{c}].{end}"),
+                    format!("no port {} on device {}", port.port, port.device_id),
+                ));
+            }
+            if !ports.insert(port) {
+                return Err(invalid(
+                    format!("layout.cables[{c}].{end}"),
+                    format!(
+                        "port {} on device {} already has a cable",
+                        port.port, port.device_id
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn conflict_message(conflict: &Conflict) -> String {
+    match conflict {
+        Conflict::OutOfBounds {
+            units,
+            rack_height_u,
+        } => format!(
+            "units {}-{} are outside the {rack_height_u}U rack",
+            units.bottom, units.top
+        ),
+        Conflict::TooDeep {
+            depth_mm,
+            rack_depth_mm,
+        } => format!("{depth_mm} mm deep in a {rack_depth_mm} mm rack"),
+        Conflict::Collision {
+            device_name, units, ..
+        } => format!(
+            "overlaps {device_name} in units {}-{}",
+            units.bottom, units.top
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::{
+        Cable, Circuit, Device, Face, Outlet, Pdu, Phase, Port, PortKind, PortRef, PowerConnection,
+        Rack,
+    };
+
+    fn project() -> Project {
+        let mut project = Project::new("Cage 4");
+        let mut rack = Rack::new("A1", 42);
+        rack.devices
+            .push(Device::new("web-01", 2, 10, 700, Face::Front));
+        project.layout.racks.push(rack);
+        project
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let project = project();
+        let path = std::env::temp_dir().join(format!("{}.{FILE_EXTENSION}", project.metadata.id));
+        project.save(&path).unwrap();
+        let loaded = Project::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().to_json(), project.to_json());
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        let mut value = serde_json::to_value(project()).unwrap();
+        value["format"] = json!("something-else");
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "format"
+        ));
+    }
+
+    #[test]
+    fn rejects_devices_below_unit_one() {
+        let mut value = serde_json::to_value(project()).unwrap();
+        value["layout"]["racks"][0]["devices"][0]["positionU"] = json!(0);
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "layout.racks[0].devices[0]"
+        ));
+    }
+
+    #[test]
+    fn rejects_overlapping_devices() {
+        let mut project = project();
+        let rack = &mut project.layout.racks[0];
+        rack.devices
+            .push(Device::new("web-02", 2, 11, 700, Face::Front));
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_power_from_missing_pdus() {
+        let mut project = project();
+        project.layout.racks[0].devices[0]
+            .power_connections
+            .push(PowerConnection {
+                pdu_id: uuid::Uuid::new_v4(),
+                outlet: String::from("1"),
+            });
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. })
+                if path == "layout.racks[0].devices[0].powerConnections[0]"
+        ));
+    }
+
+    #[test]
+    fn rejects_cables_to_missing_ports() {
+        let mut value = serde_json::to_value(project()).unwrap();
+        let device_id = value["layout"]["racks"][0]["devices"][0]["id"].clone();
+        value["layout"]["cables"] = json!([{
+            "id": uuid::Uuid::new_v4(),
+            "a": { "deviceId": device_id, "port": "eth0" },
+            "b": { "deviceId": device_id, "port": "eth1" },
+        }]);
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "layout.cables[0].a"
+        ));
+    }
+
+    fn with_pdu() -> Project {
+        let mut project = project();
+        let pdu = Pdu::new(
+            "PDU-A",
+            230.0,
+            vec![Circuit {
+                name: String::from("C1"),
+                phase: Phase::L1,
+                breaker_a: 16.0,
+                outlets: vec![Outlet {
+                    name: String::from("1"),
+                    kind: PortKind::C13,
+                }],
+            }],
+        );
+        project.layout.racks[0].pdus.push(pdu);
+        project
+    }
+
+    #[test]
+    fn rejects_duplicate_pdu_ids() {
+        let mut project = with_pdu();
+        let mut copy = project.layout.racks[0].pdus[0].clone();
+        copy.name = String::from("PDU-B");
+        project.layout.racks[0].pdus.push(copy);
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "layout.racks[0].pdus[1].id"
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicate_device_ids() {
+        let mut project = project();
+        let rack = &mut project.layout.racks[0];
+        let mut copy = rack.devices[0].clone();
+        copy.position_u = 20;
+        rack.devices.push(copy);
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "layout.racks[0].devices[1].id"
+        ));
+    }
+
+    #[test]
+    fn rejects_outlets_used_twice() {
+        let mut project = with_pdu();
+        let rack = &mut project.layout.racks[0];
+        let connection = PowerConnection {
+            pdu_id: rack.pdus[0].id,
+            outlet: String::from("1"),
+        };
+        rack.devices
+            .push(Device::new("web-02", 1, 20, 700, Face::Front));
+        for device in &mut rack.devices {
+            device.power_connections.push(connection.clone());
+        }
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. })
+                if path == "layout.racks[0].devices[1].powerConnections[0]"
+        ));
+    }
+
+    #[test]
+    fn rejects_ports_with_two_cables() {
+        let mut project = project();
+        let device = &mut project.layout.racks[0].devices[0];
+        device.ports = ["eth0", "eth1", "eth2"]
+            .map(|name| Port::new(name, PortKind::Rj45))
+            .into();
+        let end = |port: &str| PortRef {
+            device_id: device.id,
+            port: port.to_owned(),
+        };
+        project.layout.cables = vec![
+            Cable::new(end("eth0"), end("eth1")),
+            Cable::new(end("eth2"), end("eth1")),
+        ];
+        let value = serde_json::to_value(project).unwrap();
+        assert!(matches!(
+            Project::from_value(value),
+            Err(Error::InvalidProject { path, .. }) if path == "layout.cables[1].b"
+        ));
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, Runtime, WebviewWindow};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::history::{Edit, History};
+use crate::library::user::UserLibrary;
+use crate::model::{Layout, RackId};
+use crate::project::{Metadata, Project};
+
+/// Label of the window opened at startup, which starts with an empty project.
+pub const MAIN_WINDOW: &str = "main";
+
+/// Shown after the project name in window titles.
+const APP_NAME: &str = "rack-designer";
+
+/// Emitted to the other windows showing a project after it changes, so they
+/// can refetch it.
+pub const PROJECT_CHANGED: &str = "project-changed";
+
+/// A project open in one window, and in any racks detached from it.
+#[derive(Debug, Default)]
+pub struct Session {
+    pub project: Project,
+    /// Where the project was last opened from or saved to.
+    pub path: Option<PathBuf>,
+    pub history: History,
+    /// Counts changes to the project, to tell whether a command made any.
+    revision: u64,
+}
+
+impl Session {
+    pub fn new(project: Project, path: Option<PathBuf>) -> Self {
+        Self {
+            project,
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// Whether there are edits not yet saved to `path`.
+    pub fn dirty(&self) -> bool {
+        self.history.is_modified()
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.project.layout
+    }
+
+    /// Applies and records an edit, marking the session as unsaved if it
+    /// succeeds.
+    pub fn apply(&mut self, edit: Edit) -> Result<()> {
+        self.apply_coalescing(edit, false)
+    }
+
+    /// Like [`apply`](Self::apply), optionally merging the edit into the
+    /// previous one; see [`History::apply`].
+    pub fn apply_coalescing(&mut self, edit: Edit, coalesce: bool) -> Result<()> {
+        self.history.apply(&mut self.project, edit, coalesce)?;
+        self.revision += 1;
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Result<Option<String>> {
+        let label = self.history.undo(&mut self.project)?;
+        if label.is_some() {
+            self.revision += 1;
+        }
+        Ok(label)
+    }
+
+    pub fn redo(&mut self) -> Result<Option<String>> {
+        let label = self.history.redo(&mut self.project)?;
+        if label.is_some() {
+            self.revision += 1;
+        }
+        Ok(label)
+    }
+
+    pub fn info(&self) -> ProjectInfo {
+        ProjectInfo {
+            metadata: self.project.metadata.clone(),
+            path: self.path.clone(),
+            dirty: self.dirty(),
+        }
+    }
+
+    /// The title of a window showing the project, or one of its racks.
+    pub fn title(&self, rack_id: Option<RackId>) -> String {
+        let marker = if self.dirty() { "*" } else { "" };
+        let project = format!("{marker}{}", self.project.metadata.name);
+        match rack_id.and_then(|id| self.layout().rack(id).ok()) {
+            Some(rack) => format!("{} - {project} - {APP_NAME}", rack.name),
+            None => format!("{project} - {APP_NAME}"),
+        }
+    }
+}
+
+/// What the frontend needs to know about the open project besides its layout.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectInfo {
+    pub metadata: Metadata,
+    pub path: Option<PathBuf>,
+    pub dirty: bool,
+}
+
+type SessionId = u64;
+
+/// What one window shows.
+#[derive(Debug)]
+struct WindowEntry {
+    session: SessionId,
+    /// The rack shown by a detached rack window.
+    rack_id: Option<RackId>,
+    /// The title last given to the window.
+    title: String,
+}
+
+#[derive(Debug, Default)]
+struct Sessions {
+    sessions: HashMap<SessionId, Session>,
+    /// Keyed by window label.
+    windows: HashMap<String, WindowEntry>,
+    next_id: u64,
+}
+
+impl Sessions {
+    /// Sessions with an empty project for the startup window.
+    fn new() -> Self {
+        let mut sessions = Self::default();
+        sessions.insert(MAIN_WINDOW.to_owned(), Session::default());
+        sessions
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn insert(&mut self, label: String, session: Session) -> SessionId {
+        let id = self.next_id();
+        self.sessions.insert(id, session);
+        self.attach(label, id, None);
+        id
+    }
+
+    fn attach(&mut self, label: String, session: SessionId, rack_id: Option<RackId>) {
+        let entry = WindowEntry {
+            session,
+            rack_id,
+            title: String::new(),
+        };
+        self.windows.insert(label, entry);
+    }
+
+    /// The session shown in the window labelled `label`. Only windows opened
+    /// through [`AppState`] have one.
+    fn session_of(&self, label: &str) -> Result<SessionId> {
+        self.windows
+            .get(label)
+            .map(|w| w.session)
+            .ok_or_else(|| Error::UnknownWindow(label.to_owned()))
+    }
+
+    fn open(&mut self, prefix: &str, session: Session) -> String {
+        let label = format!("{prefix}-{}", self.next_id());
+        self.insert(label.clone(), session);
+        label
+    }
+
+    fn detach(&mut self, prefix: &str, parent: &str, rack_id: RackId) -> Result<String> {
+        let session = self.session_of(parent)?;
+        let label = format!("{prefix}-{}", self.next_id());
+        self.attach(label.clone(), session, Some(rack_id));
+        Ok(label)
+    }
+
+    fn showing(&self, path: &Path) -> Option<String> {
+        self.windows
+            .iter()
+            .filter(|(_, w)| w.rack_id.is_none())
+            .find(|(_, w)| self.sessions[&w.session].path.as_deref() == Some(path))
+            .map(|(label, _)| label.clone())
+    }
+
+    /// Puts another project in session `id`. Rack windows detached from the
+    /// old project keep showing racks the new one also has, and are
+    /// forgotten otherwise; their labels are returned so they can be closed.
+    fn replace(&mut self, id: SessionId, session: Session) -> Vec<String> {
+        let stale: Vec<_> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| w.session == id)
+            .filter(|(_, w)| {
+                w.rack_id
+                    .is_some_and(|rack_id| session.layout().rack(rack_id).is_err())
+            })
+            .map(|(label, _)| label.clone())
+            .collect();
+        for label in &stale {
+            self.windows.remove(label);
+        }
+        self.sessions.insert(id, session);
+        stale
+    }
+
+    fn close(&mut self, label: &str) -> Vec<String> {
+        let Some(closed) = self.windows.remove(label) else {
+            return Vec::new();
+        };
+        if closed.rack_id.is_some() {
+            return Vec::new();
+        }
+        self.sessions.remove(&closed.session);
+        let detached: Vec<_> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| w.session == closed.session)
+            .map(|(label, _)| label.clone())
+            .collect();
+        for label in &detached {
+            self.windows.remove(label);
+        }
+        detached
+    }
+}
+
+/// Backend state managed by Tauri and shared between commands.
+///
+/// Every project window has its own [`Session`], keyed by the window's
+/// label. Windows showing a rack detached from a project share the session of
+/// the project's window. The window opened at startup, [`MAIN_WINDOW`], has a
+/// session from the start; any other window needs one from
+/// [`open_window`](Self::open_window) or [`detach_rack`](Self::detach_rack).
+#[derive(Debug)]
+pub struct AppState {
+    sessions: Mutex<Sessions>,
+    pub library: UserLibrary,
+}
+
+impl AppState {
+    pub fn new(library: UserLibrary) -> Self {
+        Self {
+            sessions: Mutex::new(Sessions::new()),
+            library,
+        }
+    }
+
+    fn sessions(&self) -> MutexGuard<'_, Sessions> {
+        // Mutations validate before touching the layout, so the state behind a
+        // poisoned lock is still consistent.
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the session shown in `window`.
+    ///
+    /// When the guard is dropped, the titles of every window showing the
+    /// session are brought up to date, and the other windows are told if the
+    /// project changed.
+    pub fn session<'a>(&'a self, window: &'a WebviewWindow) -> Result<SessionGuard<'a>> {
+        let sessions = self.sessions();
+        let id = sessions.session_of(window.label())?;
+        let session = &sessions.sessions[&id];
+        let before = (session.project.metadata.id, session.revision);
+        Ok(SessionGuard {
+            sessions,
+            id,
+            window,
+            before,
+        })
+    }
+
+    /// Gives a new project window labelled `prefix-N` its own session, and
+    /// returns the label.
+    pub fn open_window(&self, prefix: &str, session: Session) -> String {
+        self.sessions().open(prefix, session)
+    }
+
+    /// Shows one rack of the project in `parent` in a new window labelled
+    /// `prefix-N`, and returns the label.
+    pub fn detach_rack(&self, prefix: &str, parent: &str, rack_id: RackId) -> Result<String> {
+        self.sessions().detach(prefix, parent, rack_id)
+    }
+
+    /// The rack a detached window shows, or `None` for a project window.
+    pub fn window_rack(&self, label: &str) -> Option<RackId> {
+        self.sessions().windows.get(label)?.rack_id
+    }
+
+    /// The project window that already has the project at `path` open.
+    pub fn window_showing(&self, path: &Path) -> Option<String> {
+        self.sessions().showing(path)
+    }
+
+    /// Forgets a closed window. Closing a project window drops its session
+    /// and returns the labels of the rack windows detached from it, which
+    /// should be closed too.
+    pub fn close_window(&self, label: &str) -> Vec<String> {
+        self.sessions().close(label)
+    }
+}
+
+/// Closes the windows labelled `labels`, e.g. those returned by
+/// [`AppState::close_window`]. Call it without a session locked: closing a
+/// window forgets it, which takes the lock.
+pub fn close_windows<R: Runtime>(manager: &impl Manager<R>, labels: &[String]) {
+    for label in labels {
+        if let Some(window) = manager.get_webview_window(label) {
+            let _ = window.close();
+        }
+    }
+}
+
+/// A locked session; see [`AppState::session`].
+pub struct SessionGuard<'a> {
+    sessions: MutexGuard<'a, Sessions>,
+    id: SessionId,
+    window: &'a WebviewWindow,
+    /// The project id and revision when the lock was taken.
+    before: (Uuid, u64),
+}
+
+impl SessionGuard<'_> {
+    /// Replaces the project, e.g. with one opened from a file. Returns the
+    /// labels of rack windows showing racks the new project does not have,
+    /// which should be closed once the guard is dropped.
+    pub fn replace(&mut self, session: Session) -> Vec<String> {
+        self.sessions.replace(self.id, session)
+    }
+}
+
+impl Deref for SessionGuard<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        &self.sessions.sessions[&self.id]
+    }
+}
+
+impl DerefMut for SessionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Session {
+        self.sessions
+            .sessions
+            .get_mut(&self.id)
+            .expect("a locked session stays open")
+    }
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        let Sessions {
+            sessions, windows, ..
+        } = &mut *self.sessions;
+        let session = &sessions[&self.id];
+        let changed = (session.project.metadata.id, session.revision) != self.before;
+        for (label, entry) in windows.iter_mut().filter(|(_, w)| w.session == self.id) {
+            let title = session.title(entry.rack_id);
+            if title != entry.title {
+                if let Some(window) = self.window.get_webview_window(label) {
+                    // A window that is closing may refuse; it will not need a
+                    // title anyway.
+                    let _ = window.set_title(&title);
+                }
+                entry.title = title;
+            }
+            if changed && label != self.window.label() {
+                let _ = self.window.emit_to(label.as_str(), PROJECT_CHANGED, ());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Rack;
+    use crate::project::FILE_EXTENSION;
+
+    /// A session whose project has one rack, returning the rack's id.
+    fn with_rack(name: &str) -> (Session, RackId) {
+        let mut project = Project::new(name);
+        let rack = Rack::new("A1", 42);
+        let id = rack.id;
+        project.layout.racks.push(rack);
+        (Session::new(project, None), id)
+    }
+
+    #[test]
+    fn starts_with_the_main_window() {
+        let sessions = Sessions::new();
+        assert!(sessions.session_of(MAIN_WINDOW).is_ok());
+        assert_eq!(sessions.sessions.len(), 1);
+    }
+
+    #[test]
+    fn rejects_unknown_windows() {
+        let mut sessions = Sessions::new();
+        assert!(matches!(
+            sessions.session_of("project-9"),
+            Err(Error::UnknownWindow(label)) if label == "project-9"
+        ));
+        assert!(sessions
+            .detach("rack", "project-9", Uuid::new_v4())
+            .is_err());
+        assert_eq!(sessions.sessions.len(), 1);
+        assert_eq!(sessions.windows.len(), 1);
+    }
+
+    #[test]
+    fn gives_each_project_window_its_own_session() {
+        let mut sessions = Sessions::new();
+        let label = sessions.open("project", Session::new(Project::new("Cage 4"), None));
+        let main = sessions.session_of(MAIN_WINDOW).unwrap();
+        let other = sessions.session_of(&label).unwrap();
+        assert_ne!(main, other);
+        assert_eq!(sessions.sessions[&other].project.metadata.name, "Cage 4");
+    }
+
+    #[test]
+    fn shares_the_session_with_detached_racks() {
+        let mut sessions = Sessions::new();
+        let (session, rack_id) = with_rack("Cage 4");
+        let project = sessions.open("project", session);
+        let rack = sessions.detach("rack", &project, rack_id).unwrap();
+        assert_eq!(
+            sessions.session_of(&rack).unwrap(),
+            sessions.session_of(&project).unwrap()
+        );
+        assert_eq!(sessions.windows[&rack].rack_id, Some(rack_id));
+    }
+
+    #[test]
+    fn finds_the_window_showing_a_file() {
+        let mut sessions = Sessions::new();
+        let path = PathBuf::from(format!("/tmp/cage-4.{FILE_EXTENSION}"));
+        let (mut session, rack_id) = with_rack("Cage 4");
+        session.path = Some(path.clone());
+        let project = sessions.open("project", session);
+        sessions.detach("rack", &project, rack_id).unwrap();
+        assert_eq!(sessions.showing(&path), Some(project));
+        assert_eq!(
+            sessions.showing(Path::new(&format!("/tmp/other.{FILE_EXTENSION}"))),
+            None
+        );
+    }
+
+    #[test]
+    fn closing_a_rack_window_keeps_the_project() {
+        let mut sessions = Sessions::new();
+        let (session, rack_id) = with_rack("Cage 4");
+        let project = sessions.open("project", session);
+        let rack = sessions.detach("rack", &project, rack_id).unwrap();
+        assert!(sessions.close(&rack).is_empty());
+        assert!(sessions.session_of(&rack).is_err());
+        assert!(sessions.session_of(&project).is_ok());
+        assert_eq!(sessions.sessions.len(), 2);
+    }
+
+    #[test]
+    fn closing_a_project_window_drops_its_session_and_racks() {
+        let mut sessions = Sessions::new();
+        let (session, rack_id) = with_rack("Cage 4");
+        let project = sessions.open("project", session);
+        let rack = sessions.detach("rack", &project, rack_id).unwrap();
+        assert_eq!(sessions.close(&project), [rack.as_str()]);
+        assert!(sessions.session_of(&rack).is_err());
+        assert_eq!(sessions.sessions.len(), 1);
+        assert!(sessions.close(&project).is_empty());
+    }
+
+    #[test]
+    fn replacing_a_project_forgets_racks_it_does_not_have() {
+        let mut sessions = Sessions::new();
+        let (session, rack_id) = with_rack("Cage 4");
+        let (reopened, _) = with_rack("Cage 5");
+        let mut kept = Session::new(reopened.project.clone(), None);
+        kept.project.layout.racks[0].id = rack_id;
+
+        let main = sessions.session_of(MAIN_WINDOW).unwrap();
+        sessions.replace(main, session);
+        let rack = sessions.detach("rack", MAIN_WINDOW, rack_id).unwrap();
+
+        // The same rack in the new project: the window stays and follows it.
+        assert!(sessions.replace(main, kept).is_empty());
+        assert_eq!(sessions.session_of(&rack).unwrap(), main);
+        assert_eq!(sessions.sessions[&main].project.metadata.name, "Cage 5");
+
+        assert_eq!(sessions.replace(main, reopened), [rack.as_str()]);
+        assert!(sessions.session_of(&rack).is_err());
+        assert!(sessions.session_of(MAIN_WINDOW).is_ok());
+    }
+}
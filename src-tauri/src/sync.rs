@@ -0,0 +1,795 @@
+//! Keeping NetBox in step with a planned layout.
+//!
+//! Devices are matched to a NetBox snapshot by the ids an import recorded
+//! on them. [`diff`] reports what changed since the snapshot was taken, and
+//! [`push`] sends those changes to a NetBox instance through its bulk
+//! endpoints. Racks and devices created by a push are linked to their new
+//! ids with one undoable edit, so the next diff treats them as known.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::export::netbox::{device_payload, rack_payload, NetboxOptions};
+use crate::history::Edit;
+use crate::import::netbox::{self, Dump};
+use crate::model::{DeviceId, Face, Layout, RackId};
+
+/// Where a device is mounted, with units counted from 1 at the bottom.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub rack: String,
+    pub position_u: u32,
+    pub face: Face,
+}
+
+/// A device in the project that NetBox does not have yet.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Added {
+    pub device_id: DeviceId,
+    pub name: String,
+    pub to: Location,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Moved {
+    pub device_id: DeviceId,
+    pub netbox_id: u64,
+    pub name: String,
+    pub from: Location,
+    pub to: Location,
+}
+
+/// A racked device in the snapshot that is not in the project, either because
+/// it was removed or because the import could not place it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Removed {
+    pub netbox_id: u64,
+    pub name: String,
+    pub from: Location,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changed {
+    pub device_id: DeviceId,
+    pub netbox_id: u64,
+    pub name: String,
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncDiff {
+    /// Racks not linked to NetBox, which a push creates.
+    pub new_racks: Vec<String>,
+    pub added: Vec<Added>,
+    pub moved: Vec<Moved>,
+    pub removed: Vec<Removed>,
+    pub changed: Vec<Changed>,
+}
+
+impl SyncDiff {
+    pub fn is_empty(&self) -> bool {
+        self.new_racks.is_empty()
+            && self.added.is_empty()
+            && self.moved.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+/// Compares the project with a NetBox snapshot. A device that moved and was
+/// also edited is listed under both.
+pub fn diff(layout: &Layout, snapshot: &Dump) -> SyncDiff {
+    let mut diff = SyncDiff {
+        new_racks: layout
+            .racks
+            .iter()
+            .filter(|r| r.netbox_id.is_none())
+            .map(|r| r.name.clone())
+            .collect(),
+        ..SyncDiff::default()
+    };
+
+    for (rack, device) in layout.devices() {
+        let to = Location {
+            rack: rack.name.clone(),
+            position_u: device.position_u,
+            face: device.face,
+        };
+        let Some(known) = device.netbox_id.and_then(|id| snapshot.device(id)) else {
+            diff.added.push(Added {
+                device_id: device.id,
+                name: device.name.clone(),
+                to,
+            });
+            continue;
+        };
+
+        match snapshot_location(snapshot, known) {
+            Some((rack_id, from)) if same_place(rack.netbox_id, &to, rack_id, &from) => {}
+            Some((_, from)) => diff.moved.push(Moved {
+                device_id: device.id,
+                netbox_id: known.id,
+                name: device.name.clone(),
+                from,
+                to,
+            }),
+            // Unracked in NetBox but placed in the project.
+            None => diff.moved.push(Moved {
+                device_id: device.id,
+                netbox_id: known.id,
+                name: device.name.clone(),
+                from: Location {
+                    rack: String::new(),
+                    position_u: 0,
+                    face: known.face(),
+                },
+                to,
+            }),
+        }
+
+        let mut fields = Vec::new();
+        let mut compare = |field, from: String, to: &str| {
+            if from.trim() != to.trim() {
+                fields.push(FieldChange {
+                    field,
+                    from,
+                    to: to.to_owned(),
+                });
+            }
+        };
+        compare("name", snapshot.device_name(known), &device.name);
+        compare(
+            "serial",
+            known.serial.clone(),
+            device.serial.as_deref().unwrap_or_default(),
+        );
+        if let Some(device_type) = snapshot.device_type(known.device_type.id) {
+            let model = [device.manufacturer.as_deref(), device.model.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let from = format!("{} {}", device_type.manufacturer_name(), device_type.model);
+            if !from.eq_ignore_ascii_case(&model) {
+                compare("model", from, &model);
+            }
+        }
+        if !fields.is_empty() {
+            diff.changed.push(Changed {
+                device_id: device.id,
+                netbox_id: known.id,
+                name: device.name.clone(),
+                fields,
+            });
+        }
+    }
+
+    for known in &snapshot.devices {
+        let in_project = layout.devices().any(|(_, d)| d.netbox_id == Some(known.id));
+        if in_project {
+            continue;
+        }
+        if let Some((_, from)) = snapshot_location(snapshot, known) {
+            diff.removed.push(Removed {
+                netbox_id: known.id,
+                name: snapshot.device_name(known),
+                from,
+            });
+        }
+    }
+    diff
+}
+
+/// Whether a device at `to` in the project is where NetBox has it. Racks
+/// linked to NetBox are matched by id, so renaming one locally does not move
+/// its devices; unlinked racks can only be matched by name.
+fn same_place(linked: Option<u64>, to: &Location, rack_id: u64, from: &Location) -> bool {
+    let same_rack = match linked {
+        Some(id) => id == rack_id,
+        None => from.rack == to.rack,
+    };
+    same_rack && from.position_u == to.position_u && from.face == to.face
+}
+
+/// Where a snapshot device is mounted and the NetBox id of its rack.
+fn snapshot_location(snapshot: &Dump, device: &netbox::NetboxDevice) -> Option<(u64, Location)> {
+    let (rack_id, position_u) = snapshot.placement(device)?;
+    let rack = snapshot
+        .rack(rack_id)
+        .map(|r| r.name.clone())
+        .or_else(|| device.rack.as_ref().and_then(|r| r.name.clone()))
+        .unwrap_or_default();
+    Some((
+        rack_id,
+        Location {
+            rack,
+            position_u,
+            face: device.face(),
+        },
+    ))
+}
+
+/// Where to reach NetBox. The token needs write permission on racks and
+/// devices.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetboxServer {
+    /// Base URL of the instance, e.g. `https://netbox.example.com`.
+    pub url: String,
+    pub token: String,
+}
+
+/// The requests a push makes, worked out while the project is locked so the
+/// network calls can run without holding it.
+#[derive(Debug, Clone, Default)]
+pub struct PushPlan {
+    pub racks: Vec<(RackId, String, Value)>,
+    pub created: Vec<(DeviceId, String, Value)>,
+    pub updated: Vec<Value>,
+    /// NetBox ids of devices to delete.
+    pub deleted: Vec<u64>,
+}
+
+impl PushPlan {
+    /// The requests that bring NetBox in line with `diff`. Removed devices
+    /// are only deleted when `delete_removed` is set; devices without a model
+    /// cannot be created and are left out.
+    pub fn new(
+        layout: &Layout,
+        snapshot: &Dump,
+        diff: &SyncDiff,
+        options: &NetboxOptions,
+        delete_removed: bool,
+    ) -> Self {
+        let mut plan = PushPlan::default();
+        for rack in layout.racks.iter().filter(|r| r.netbox_id.is_none()) {
+            plan.racks
+                .push((rack.id, rack.name.clone(), rack_payload(rack, options)));
+        }
+        for (rack, device) in layout.devices() {
+            let created = diff.added.iter().any(|a| a.device_id == device.id);
+            let updated = diff.moved.iter().any(|m| m.device_id == device.id)
+                || diff.changed.iter().any(|c| c.device_id == device.id);
+            if !created && !updated {
+                continue;
+            }
+            let Some(mut payload) = device_payload(rack, device, Some(snapshot), options) else {
+                continue;
+            };
+            if created {
+                // Linked to a device that has since been deleted from NetBox.
+                if let Some(fields) = payload.as_object_mut() {
+                    if fields.remove("id").is_some() {
+                        fields.insert("site".into(), json!({ "slug": options.site }));
+                        fields.insert("status".into(), json!(options.status));
+                    }
+                }
+                plan.created.push((device.id, device.name.clone(), payload));
+            } else {
+                plan.updated.push(payload);
+            }
+        }
+        if delete_removed {
+            plan.deleted = diff.removed.iter().map(|r| r.netbox_id).collect();
+        }
+        plan
+    }
+}
+
+/// What a push did. If a request fails, the objects created before it are
+/// still reported and linked, and `error` says what went wrong.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushReport {
+    pub created_racks: Vec<String>,
+    pub created_devices: Vec<String>,
+    pub updated: usize,
+    pub deleted: usize,
+    pub error: Option<String>,
+    #[serde(skip)]
+    links: Vec<Link>,
+}
+
+#[derive(Debug, Clone)]
+enum Link {
+    Rack(RackId, u64),
+    Device(DeviceId, u64),
+}
+
+impl PushReport {
+    /// Records the NetBox ids of created objects on the project, as one edit.
+    /// `None` if nothing was created.
+    pub fn link_edit(&self, layout: &Layout) -> Option<Edit> {
+        let edits: Vec<Edit> = self
+            .links
+            .iter()
+            .filter_map(|link| match *link {
+                Link::Rack(rack_id, id) => {
+                    let rack = layout.rack(rack_id).ok()?;
+                    Some(Edit::SetRackNetboxId {
+                        rack_id,
+                        name: rack.name.clone(),
+                        from: rack.netbox_id,
+                        to: Some(id),
+                    })
+                }
+                Link::Device(device_id, id) => {
+                    let (_, device) = layout.devices().find(|(_, d)| d.id == device_id)?;
+                    Some(Edit::SetDeviceNetboxId {
+                        device_id,
+                        name: device.name.clone(),
+                        from: device.netbox_id,
+                        to: Some(id),
+                    })
+                }
+            })
+            .collect();
+        (!edits.is_empty()).then(|| Edit::Batch {
+            label: String::from("Push to NetBox"),
+            edits,
+        })
+    }
+}
+
+/// Sends a plan to NetBox: new racks first so new devices can refer to them,
+/// then new devices, updates and deletions.
+pub async fn push(server: &NetboxServer, plan: PushPlan) -> PushReport {
+    let client = Client::new(server);
+    let mut report = PushReport::default();
+    if let Err(e) = client.run(plan, &mut report).await {
+        report.error = Some(e.to_string());
+    }
+    report
+}
+
+struct Client {
+    base: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    fn new(server: &NetboxServer) -> Self {
+        let base = server.url.trim().trim_end_matches('/');
+        Self {
+            base: base.strip_suffix("/api").unwrap_or(base).to_owned(),
+            token: server.token.trim().to_owned(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn run(&self, plan: PushPlan, report: &mut PushReport) -> Result<()> {
+        if !plan.racks.is_empty() {
+            let bodies = plan.racks.iter().map(|(_, _, body)| body.clone()).collect();
+            let ids = self.create(netbox::RACKS, bodies).await?;
+            for ((rack_id, name, _), id) in plan.racks.into_iter().zip(ids) {
+                report.links.push(Link::Rack(rack_id, id));
+                report.created_racks.push(name);
+            }
+        }
+        if !plan.created.is_empty() {
+            let bodies = plan
+                .created
+                .iter()
+                .map(|(_, _, body)| body.clone())
+                .collect();
+            let ids = self.create(netbox::DEVICES, bodies).await?;
+            for ((device_id, name, _), id) in plan.created.into_iter().zip(ids) {
+                report.links.push(Link::Device(device_id, id));
+                report.created_devices.push(name);
+            }
+        }
+        if !plan.updated.is_empty() {
+            let count = plan.updated.len();
+            self.send(
+                reqwest::Method::PATCH,
+                netbox::DEVICES,
+                Value::Array(plan.updated),
+            )
+            .await?;
+            report.updated = count;
+        }
+        if !plan.deleted.is_empty() {
+            let count = plan.deleted.len();
+            let bodies = plan.deleted.iter().map(|id| json!({ "id": id })).collect();
+            self.send(
+                reqwest::Method::DELETE,
+                netbox::DEVICES,
+                Value::Array(bodies),
+            )
+            .await?;
+            report.deleted = count;
+        }
+        Ok(())
+    }
+
+    /// Creates objects in bulk, returning their new ids in order.
+    async fn create(&self, endpoint: &'static str, bodies: Vec<Value>) -> Result<Vec<u64>> {
+        let count = bodies.len();
+        let created = self
+            .send(reqwest::Method::POST, endpoint, Value::Array(bodies))
+            .await?;
+        let ids: Vec<u64> = created
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|object| object.get("id").and_then(Value::as_u64))
+            .collect();
+        if ids.len() != count {
+            return Err(Error::NetboxRequest {
+                status: None,
+                message: format!("expected {count} created objects from {endpoint}"),
+            });
+        }
+        Ok(ids)
+    }
+
+    async fn send(&self, method: reqwest::Method, endpoint: &str, body: Value) -> Result<Value> {
+        let failed =
+            |status: Option<u16>, message: String| Error::NetboxRequest { status, message };
+        let response = self
+            .http
+            .request(method, format!("{}/api/{endpoint}/", self.base))
+            .header("Authorization", format!("Token {}", self.token))
+            .header("Accept", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| failed(None, e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| failed(Some(status.as_u16()), e.to_string()))?;
+        if !status.is_success() {
+            return Err(failed(Some(status.as_u16()), text));
+        }
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text).map_err(|e| failed(Some(status.as_u16()), e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::*;
+    use crate::library;
+    use crate::model::Device;
+    use crate::project::Project;
+
+    /// A recorded request: method, path and JSON body.
+    type Request = (String, String, Value);
+
+    /// Answers NetBox bulk requests on a local port, giving created objects
+    /// ids from 1000 up, and records every request it receives.
+    fn mock_netbox() -> (String, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            let mut next_id = 1000;
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_owned();
+                let path = parts.next().unwrap_or_default().to_owned();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+                let (status, reply) = match method.as_str() {
+                    "POST" => {
+                        let mut objects = body.as_array().cloned().unwrap_or_default();
+                        for object in &mut objects {
+                            object["id"] = json!(next_id);
+                            next_id += 1;
+                        }
+                        ("201 Created", Value::Array(objects).to_string())
+                    }
+                    "PATCH" => ("200 OK", body.to_string()),
+                    _ => ("204 No Content", String::new()),
+                };
+                recorded.lock().unwrap().push((method, path, body));
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                    reply.len()
+                );
+            }
+        });
+        (url, requests)
+    }
+
+    fn snapshot() -> Dump {
+        Dump::parse(
+            include_str!("../tests/fixtures/netbox/racks.json"),
+            include_str!("../tests/fixtures/netbox/devices.json"),
+            include_str!("../tests/fixtures/netbox/device-types.json"),
+        )
+        .unwrap()
+    }
+
+    fn options() -> NetboxOptions {
+        NetboxOptions {
+            site: String::from("dc1"),
+            status: String::from("planned"),
+        }
+    }
+
+    /// A project imported from the fixture snapshot, then edited: one device
+    /// moved and given a new serial number, one removed and one added in a new rack.
+    fn edited_project(snapshot: &Dump) -> Project {
+        let mut project = Project::new("Cage 4");
+        let (plan, _) = netbox::plan(&project.layout, library::builtin(), snapshot);
+        plan.into_edit(&project.layout, "Import")
+            .apply(&mut project)
+            .unwrap();
+        let layout = &mut project.layout;
+        let id_of = |layout: &Layout, netbox_id| {
+            layout
+                .devices()
+                .find(|(_, d)| d.netbox_id == Some(netbox_id))
+                .unwrap()
+                .1
+                .id
+        };
+        let a1 = layout.racks[0].id;
+        let server = id_of(layout, 101);
+        layout.move_device(server, a1, 20, Face::Front).unwrap();
+        layout
+            .set_serial(server, Some(String::from("SN-0101-RMA")))
+            .unwrap();
+        let unnamed = id_of(layout, 103);
+        layout.remove_device(unnamed).unwrap();
+
+        let rack = layout
+            .insert_rack(layout.racks.len(), crate::model::Rack::new("C1", 42))
+            .unwrap()
+            .id;
+        let mut device = Device::new("c1-srv-01", 1, 1, 1000, Face::Front);
+        device.manufacturer = Some(String::from("Dell"));
+        device.model = Some(String::from("PowerEdge R650"));
+        layout.add_device(rack, device).unwrap();
+        project
+    }
+
+    #[test]
+    fn unchanged_import_has_no_differences() {
+        let snapshot = snapshot();
+        let mut project = Project::new("Cage 4");
+        let (plan, _) = netbox::plan(&project.layout, library::builtin(), &snapshot);
+        plan.into_edit(&project.layout, "Import")
+            .apply(&mut project)
+            .unwrap();
+        let diff = diff(&project.layout, &snapshot);
+        // The skipped duplicate at A1 U10 was never imported.
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].netbox_id, 106);
+        assert!(diff.added.is_empty() && diff.moved.is_empty() && diff.changed.is_empty());
+        assert!(diff.new_racks.is_empty());
+    }
+
+    #[test]
+    fn finds_added_moved_removed_and_changed_devices() {
+        let snapshot = snapshot();
+        let project = edited_project(&snapshot);
+        let diff = diff(&project.layout, &snapshot);
+
+        assert_eq!(diff.new_racks, ["C1"]);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "c1-srv-01");
+
+        assert_eq!(diff.moved.len(), 1);
+        let moved = &diff.moved[0];
+        assert_eq!(moved.netbox_id, 101);
+        assert_eq!((moved.from.position_u, moved.to.position_u), (10, 20));
+
+        let removed: Vec<_> = diff.removed.iter().map(|r| r.netbox_id).collect();
+        assert_eq!(removed, [103, 106]);
+        assert_eq!(diff.removed[0].from.position_u, 41);
+
+        assert_eq!(diff.changed.len(), 1);
+        let fields = &diff.changed[0].fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(
+            (fields[0].field, fields[0].to.as_str()),
+            ("serial", "SN-0101-RMA")
+        );
+    }
+
+    #[test]
+    fn renaming_a_linked_rack_does_not_move_its_devices() {
+        let snapshot = snapshot();
+        let mut project = Project::new("Cage 4");
+        let (plan, _) = netbox::plan(&project.layout, library::builtin(), &snapshot);
+        plan.into_edit(&project.layout, "Import")
+            .apply(&mut project)
+            .unwrap();
+        let rack = project.layout.racks[0].id;
+        project
+            .layout
+            .rename_rack(rack, String::from("A1 (renamed)"))
+            .unwrap();
+        let diff = diff(&project.layout, &snapshot);
+        assert!(diff.moved.is_empty());
+        assert!(diff.new_racks.is_empty());
+    }
+
+    #[test]
+    fn matches_unlinked_racks_by_name() {
+        let snapshot = snapshot();
+        let mut project = Project::new("Cage 4");
+        let (plan, _) = netbox::plan(&project.layout, library::builtin(), &snapshot);
+        plan.into_edit(&project.layout, "Import")
+            .apply(&mut project)
+            .unwrap();
+        let a1 = &mut project.layout.racks[0];
+        a1.netbox_id = None;
+        let devices = a1.devices.len();
+        assert!(diff(&project.layout, &snapshot).moved.is_empty());
+
+        project.layout.racks[0].name = String::from("A9");
+        assert_eq!(diff(&project.layout, &snapshot).moved.len(), devices);
+    }
+
+    #[test]
+    fn payloads_refer_to_unlinked_objects_by_attributes() {
+        let snapshot = snapshot();
+        let project = edited_project(&snapshot);
+        let (payloads, unexported) =
+            crate::export::netbox::payloads(&project.layout, Some(&snapshot), &options());
+        assert!(unexported.is_empty());
+        assert_eq!(payloads.racks.len(), 4);
+        assert_eq!(payloads.racks[0]["id"], 1);
+        assert_eq!(payloads.racks[3]["site"]["slug"], "dc1");
+
+        let new = payloads
+            .devices
+            .iter()
+            .find(|d| d["name"] == "c1-srv-01")
+            .unwrap();
+        assert!(new.get("id").is_none());
+        assert_eq!(new["rack"]["name"], "C1");
+        assert_eq!(new["device_type"]["model"], "PowerEdge R650");
+        assert_eq!(new["role"]["slug"], "other");
+        assert_eq!(new["status"], "planned");
+
+        let switch = payloads.devices.iter().find(|d| d["id"] == 102).unwrap();
+        assert_eq!(
+            (switch["rack"].clone(), switch["position"].clone()),
+            (json!(1), json!(42))
+        );
+        assert_eq!(switch["face"], "rear");
+        assert_eq!(switch["device_type"], 2);
+    }
+
+    #[test]
+    fn payload_positions_follow_descending_racks() {
+        let snapshot = snapshot();
+        let mut project = Project::new("Cage 4");
+        let (plan, _) = netbox::plan(&project.layout, library::builtin(), &snapshot);
+        plan.into_edit(&project.layout, "Import")
+            .apply(&mut project)
+            .unwrap();
+        let (payloads, _) =
+            crate::export::netbox::payloads(&project.layout, Some(&snapshot), &options());
+        let unnamed = payloads.devices.iter().find(|d| d["id"] == 103).unwrap();
+        assert_eq!(unnamed["position"], 1);
+    }
+
+    #[tokio::test]
+    async fn pushes_changes_and_links_created_objects() {
+        let (url, requests) = mock_netbox();
+        let snapshot = snapshot();
+        let mut project = edited_project(&snapshot);
+        let diff = diff(&project.layout, &snapshot);
+        let plan = PushPlan::new(&project.layout, &snapshot, &diff, &options(), true);
+        let server = NetboxServer {
+            url: format!("{url}/api/"),
+            token: String::from("0123456789abcdef"),
+        };
+
+        let report = push(&server, plan).await;
+        assert_eq!(report.error, None);
+        assert_eq!(report.created_racks, ["C1"]);
+        assert_eq!(report.created_devices, ["c1-srv-01"]);
+        assert_eq!((report.updated, report.deleted), (1, 2));
+
+        let requests = requests.lock().unwrap();
+        let calls: Vec<_> = requests
+            .iter()
+            .map(|(method, path, _)| format!("{method} {path}"))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "POST /api/dcim/racks/",
+                "POST /api/dcim/devices/",
+                "PATCH /api/dcim/devices/",
+                "DELETE /api/dcim/devices/",
+            ]
+        );
+        assert_eq!(requests[2].2[0]["position"], 20);
+        assert_eq!(requests[3].2, json!([{ "id": 103 }, { "id": 106 }]));
+
+        let edit = report.link_edit(&project.layout).unwrap();
+        edit.apply(&mut project).unwrap();
+        let rack = project
+            .layout
+            .racks
+            .iter()
+            .find(|r| r.name == "C1")
+            .unwrap();
+        assert_eq!(rack.netbox_id, Some(1000));
+        assert_eq!(rack.devices[0].netbox_id, Some(1001));
+    }
+
+    #[tokio::test]
+    async fn reports_failed_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buffer = [0; 4096];
+                let _ = stream.read(&mut buffer);
+                let body = r#"{"detail":"Invalid token"}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 403 Forbidden\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        let snapshot = snapshot();
+        let project = edited_project(&snapshot);
+        let diff = diff(&project.layout, &snapshot);
+        let plan = PushPlan::new(&project.layout, &snapshot, &diff, &options(), false);
+        let server = NetboxServer {
+            url,
+            token: String::from("wrong"),
+        };
+        let report = push(&server, plan).await;
+        assert!(report.error.as_deref().unwrap().contains("Invalid token"));
+        assert!(report.created_racks.is_empty());
+        assert!(report.link_edit(&project.layout).is_none());
+    }
+}
@@ -0,0 +1,228 @@
+//! Placement and airflow rules for devices within a rack.
+
+use serde::Serialize;
+
+use crate::model::{Airflow, Device, DeviceId, Face, Rack, UnitRange};
+
+/// A single reason a device cannot go where it was asked to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Conflict {
+    /// The device extends past the top or below the bottom of the rack.
+    #[serde(rename_all = "camelCase")]
+    OutOfBounds {
+        units: UnitRange,
+        rack_height_u: u32,
+    },
+    /// The device is deeper than the rack itself.
+    #[serde(rename_all = "camelCase")]
+    TooDeep { depth_mm: u32, rack_depth_mm: u32 },
+    /// The device shares rack units and depth with an existing device.
+    #[serde(rename_all = "camelCase")]
+    Collision {
+        device_id: DeviceId,
+        device_name: String,
+        face: Face,
+        /// The units both devices occupy.
+        units: UnitRange,
+    },
+}
+
+/// Checks `device` against the bounds of `rack` and every other device in it.
+///
+/// A device already in the rack with the same id is ignored, so this can be
+/// used to validate a move in place.
+pub fn check_placement(rack: &Rack, device: &Device) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let units = device.units();
+    let fits = UnitRange::checked(device.position_u, device.height_u)
+        .is_some_and(|u| u.bottom >= 1 && u.top <= rack.height_u);
+    if !fits {
+        conflicts.push(Conflict::OutOfBounds {
+            units,
+            rack_height_u: rack.height_u,
+        });
+    }
+    if device.depth_mm > rack.depth_mm {
+        conflicts.push(Conflict::TooDeep {
+            depth_mm: device.depth_mm,
+            rack_depth_mm: rack.depth_mm,
+        });
+    }
+    for other in rack.devices.iter().filter(|d| d.id != device.id) {
+        if let Some(shared) = intersection(units, other.units()) {
+            if collides(rack, device, other) {
+                conflicts.push(Conflict::Collision {
+                    device_id: other.id,
+                    device_name: other.name.clone(),
+                    face: other.face,
+                    units: shared,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Checks every device in `rack`, e.g. after loading a file edited by hand.
+pub fn check_rack(rack: &Rack) -> Vec<(DeviceId, Conflict)> {
+    rack.devices
+        .iter()
+        .flat_map(|d| check_placement(rack, d).into_iter().map(|c| (d.id, c)))
+        .collect()
+}
+
+/// A device blowing its exhaust into the rack's cold aisle, which usually
+/// means it was mounted the wrong way round.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirflowWarning {
+    pub device_id: DeviceId,
+    pub device_name: String,
+    pub airflow: Airflow,
+    pub face: Face,
+}
+
+/// Finds devices in `rack` that exhaust into its cold aisle. Devices with
+/// side-to-side or unknown airflow are not checked.
+pub fn check_airflow(rack: &Rack) -> Vec<AirflowWarning> {
+    rack.devices
+        .iter()
+        .filter(|d| d.exhaust() == Some(rack.cold_aisle))
+        .filter_map(|d| {
+            Some(AirflowWarning {
+                device_id: d.id,
+                device_name: d.name.clone(),
+                airflow: d.airflow?,
+                face: d.face,
+            })
+        })
+        .collect()
+}
+
+/// Whether two devices sharing rack units also share physical space.
+///
+/// Devices on the same face always do. Devices on opposite faces only do when
+/// together they are deeper than the rack, so two half-depth devices can sit
+/// back to back in the same units.
+fn collides(rack: &Rack, a: &Device, b: &Device) -> bool {
+    a.face == b.face || u64::from(a.depth_mm) + u64::from(b.depth_mm) > u64::from(rack.depth_mm)
+}
+
+fn intersection(a: UnitRange, b: UnitRange) -> Option<UnitRange> {
+    a.overlaps(&b).then(|| UnitRange {
+        bottom: a.bottom.max(b.bottom),
+        top: a.top.min(b.top),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rack() -> Rack {
+        let mut rack = Rack::new("A1", 42);
+        rack.depth_mm = 1000;
+        rack
+    }
+
+    fn device(position_u: u32, height_u: u32, depth_mm: u32, face: Face) -> Device {
+        Device::new("d", height_u, position_u, depth_mm, face)
+    }
+
+    #[test]
+    fn fits_in_an_empty_rack() {
+        assert!(check_placement(&rack(), &device(41, 2, 800, Face::Front)).is_empty());
+    }
+
+    #[test]
+    fn rejects_devices_past_the_top() {
+        let conflicts = check_placement(&rack(), &device(42, 2, 800, Face::Front));
+        assert_eq!(
+            conflicts,
+            [Conflict::OutOfBounds {
+                units: UnitRange {
+                    bottom: 42,
+                    top: 43
+                },
+                rack_height_u: 42,
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_unit_zero() {
+        let conflicts = check_placement(&rack(), &device(0, 1, 800, Face::Front));
+        assert!(matches!(conflicts[..], [Conflict::OutOfBounds { .. }]));
+    }
+
+    #[test]
+    fn rejects_heights_that_overflow() {
+        let conflicts = check_placement(&rack(), &device(2, u32::MAX, 800, Face::Front));
+        assert!(matches!(conflicts[..], [Conflict::OutOfBounds { .. }]));
+    }
+
+    #[test]
+    fn rejects_devices_deeper_than_the_rack() {
+        let conflicts = check_placement(&rack(), &device(1, 1, 1100, Face::Front));
+        assert_eq!(
+            conflicts,
+            [Conflict::TooDeep {
+                depth_mm: 1100,
+                rack_depth_mm: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_the_units_shared_with_another_device() {
+        let mut rack = rack();
+        let existing = device(10, 4, 800, Face::Front);
+        let existing_id = existing.id;
+        rack.devices.push(existing);
+        let conflicts = check_placement(&rack, &device(12, 4, 800, Face::Front));
+        assert!(matches!(
+            &conflicts[..],
+            [Conflict::Collision { device_id, units: UnitRange { bottom: 12, top: 13 }, .. }]
+                if *device_id == existing_id
+        ));
+    }
+
+    #[test]
+    fn adjacent_devices_do_not_collide() {
+        let mut rack = rack();
+        rack.devices.push(device(10, 2, 800, Face::Front));
+        assert!(check_placement(&rack, &device(12, 2, 800, Face::Front)).is_empty());
+    }
+
+    #[test]
+    fn half_depth_devices_fit_back_to_back() {
+        let mut rack = rack();
+        rack.devices.push(device(10, 1, 500, Face::Front));
+        assert!(check_placement(&rack, &device(10, 1, 500, Face::Rear)).is_empty());
+    }
+
+    #[test]
+    fn back_to_back_devices_deeper_than_the_rack_collide() {
+        let mut rack = rack();
+        rack.devices.push(device(10, 1, 500, Face::Front));
+        let conflicts = check_placement(&rack, &device(10, 1, 501, Face::Rear));
+        assert!(matches!(conflicts[..], [Conflict::Collision { .. }]));
+    }
+
+    #[test]
+    fn a_device_does_not_collide_with_itself() {
+        let mut rack = rack();
+        let existing = device(10, 2, 800, Face::Front);
+        rack.devices.push(existing.clone());
+        assert!(check_placement(&rack, &existing).is_empty());
+    }
+
+    #[test]
+    fn check_rack_reports_both_sides_of_a_collision() {
+        let mut rack = rack();
+        rack.devices.push(device(10, 2, 800, Face::Front));
+        rack.devices.push(device(11, 2, 800, Face::Front));
+        assert_eq!(check_rack(&rack).len(), 2);
+    }
+}